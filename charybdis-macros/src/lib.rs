@@ -11,8 +11,8 @@ use charybdis_parser::traits::CharybdisMacroArgs;
 
 use crate::model::*;
 use crate::native::{
-    decrement_counter_methods, delete_by_primary_key_functions, find_by_global_secondary_index,
-    find_by_local_secondary_index, find_by_primary_keys_functions, increment_counter_methods,
+    decrement_counter_methods, delete_by_primary_key_functions, find_by_global_secondary_index, find_by_index,
+    find_by_local_secondary_index, find_by_primary_keys_functions, find_by_vector_index_ann, increment_counter_methods,
     pull_from_collection_consts, pull_from_collection_consts_if_exists, pull_from_collection_methods,
     pull_from_collection_methods_if_exists, push_to_collection_consts, push_to_collection_consts_if_exists,
     push_to_collection_methods, push_to_collection_methods_if_exists,
@@ -33,6 +33,10 @@ pub fn charybdis_model(args: TokenStream, input: TokenStream) -> TokenStream {
     let mut new_fields = CharybdisFields::from_input(&input, &args);
     let fields = new_fields.populate(&args);
 
+    if let Err(e) = fields.validate_vector_indexes(&args) {
+        return e.to_compile_error().into();
+    }
+
     let struct_name = &input.ident.clone();
 
     // partial_<model_name>!(StructName, field1, field2, ...);
@@ -53,6 +57,10 @@ pub fn charybdis_model(args: TokenStream, input: TokenStream) -> TokenStream {
     // Charybdis::Model consts
     let insert_if_not_exists_query_const = insert_if_not_exists_query_const(&args, fields);
     let update_query_const = update_query_const(&args, fields);
+    let insert_with_ttl_query_const = insert_with_ttl_query_const(&args, fields);
+    let insert_if_not_exists_with_ttl_query_const = insert_if_not_exists_with_ttl_query_const(&args, fields);
+    let update_with_ttl_query_const = update_with_ttl_query_const(&args, fields);
+    let default_ttl_const = default_ttl_const(&args);
     let delete_query_const = delete_query_const(&args, fields);
     let delete_by_partition_key_query_const = delete_by_partition_key_query_const(&args, fields);
 
@@ -76,10 +84,14 @@ pub fn charybdis_model(args: TokenStream, input: TokenStream) -> TokenStream {
     let increment_counter_methods = increment_counter_methods(&args, fields);
     let decrement_counter_methods = decrement_counter_methods(&args, fields);
 
+    // WritetimeModel::Writetime struct and impl
+    let writetime_model = writetime_model(&input, &args, fields);
+
     // Current model macro rules
     let find_model_query_rule = find_model_query_rule(struct_name, &args, fields);
     let find_model_rule = find_model_rule(struct_name, &args, fields);
     let find_first_model_rule = find_first_model_rule(struct_name, &args, fields);
+    let find_model_ann_rule = find_model_ann_rule(struct_name, &args, fields);
     let update_model_query_rule = update_model_query_rule(struct_name, &args, fields);
     let delete_model_query_rule = delete_model_query_rule(struct_name, &args);
     let delete_model_rule = delete_model_rule(struct_name, &args);
@@ -88,6 +100,8 @@ pub fn charybdis_model(args: TokenStream, input: TokenStream) -> TokenStream {
     let find_by_primary_keys_functions = find_by_primary_keys_functions(struct_name, &args, fields);
     let find_by_local_secondary_index_funs = find_by_local_secondary_index(struct_name, &args, fields);
     let find_by_global_secondary_index_funs = find_by_global_secondary_index(struct_name, &args, fields);
+    let find_by_vector_index_ann_funs = find_by_vector_index_ann(struct_name, &args, fields);
+    let find_by_index_funs = find_by_index(struct_name, &args, fields);
     let delete_by_cks_funs = delete_by_primary_key_functions(&args, fields);
 
     CharybdisFields::proxy_charybdis_attrs_to_scylla(&mut input);
@@ -104,6 +118,8 @@ pub fn charybdis_model(args: TokenStream, input: TokenStream) -> TokenStream {
 
             #find_by_local_secondary_index_funs
             #find_by_global_secondary_index_funs
+            #find_by_vector_index_ann_funs
+            #find_by_index_funs
 
             #push_to_collection_consts
             #push_to_collection_consts_if_exists
@@ -146,12 +162,19 @@ pub fn charybdis_model(args: TokenStream, input: TokenStream) -> TokenStream {
             #update_query_const
             #delete_query_const
             #delete_by_partition_key_query_const
+            #insert_with_ttl_query_const
+            #insert_if_not_exists_with_ttl_query_const
+            #update_with_ttl_query_const
+            #default_ttl_const
 
         }
 
+        #writetime_model
+
         #find_model_query_rule
         #find_model_rule
         #find_first_model_rule
+        #find_model_ann_rule
         #update_model_query_rule
         #partial_model_generator
         #delete_model_query_rule
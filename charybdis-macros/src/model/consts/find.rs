@@ -1,17 +1,18 @@
 use quote::quote;
 use syn::ImplItem;
 
-use charybdis_parser::fields::CharybdisFields;
+use charybdis_parser::fields::{find_all_query, CharybdisFields};
 use charybdis_parser::traits::CharybdisMacroArgs;
 
 use crate::traits::fields::FieldsQuery;
 
 pub(crate) fn find_all_query_const(ch_args: &CharybdisMacroArgs, fields: &CharybdisFields) -> ImplItem {
-    let query_str = format!(
-        "SELECT {} FROM {}",
-        fields.db_fields.comma_sep_cols(),
-        ch_args.table_name()
-    );
+    let column_names = fields
+        .db_fields
+        .iter()
+        .map(|field| field.column_name())
+        .collect::<Vec<&str>>();
+    let query_str = find_all_query(&ch_args.table_name(), &column_names);
 
     let generated = quote! {
         const FIND_ALL_QUERY: &'static str = #query_str;
@@ -67,3 +68,28 @@ pub(crate) fn find_first_by_partition_key_query_const(
 
     syn::parse_quote!(#generated)
 }
+
+pub(crate) fn find_writetime_by_primary_key_query_const(
+    ch_args: &CharybdisMacroArgs,
+    fields: &CharybdisFields,
+) -> ImplItem {
+    let selectors = fields
+        .writetime_fields()
+        .iter()
+        .map(|field| format!("WRITETIME({name}), TTL({name})", name = field.cql_name()))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    let query_str = format!(
+        "SELECT {} FROM {} WHERE {}",
+        selectors,
+        ch_args.table_name(),
+        fields.primary_key_fields.where_placeholders(),
+    );
+
+    let generated = quote! {
+        const FIND_WRITETIME_BY_PRIMARY_KEY_QUERY: &'static str = #query_str;
+    };
+
+    syn::parse_quote!(#generated)
+}
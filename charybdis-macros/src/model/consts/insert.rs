@@ -1,36 +1,69 @@
 use quote::quote;
 use syn::ImplItem;
 
-use charybdis_parser::fields::CharybdisFields;
+use charybdis_parser::fields::{insert_query, CharybdisFields};
 use charybdis_parser::traits::CharybdisMacroArgs;
 
 use crate::traits::fields::FieldsQuery;
 
 pub(crate) fn insert_query_const(ch_args: &CharybdisMacroArgs, fields: &CharybdisFields) -> ImplItem {
+    let column_names = fields
+        .db_fields
+        .iter()
+        .map(|field| field.column_name())
+        .collect::<Vec<&str>>();
+    let query_str: String = insert_query(&ch_args.table_name(), &column_names);
+
+    let generated = quote! {
+        const INSERT_QUERY: &'static str = #query_str;
+    };
+
+    syn::parse_quote!(#generated)
+}
+
+pub(crate) fn insert_if_not_exists_query_const(ch_args: &CharybdisMacroArgs, fields: &CharybdisFields) -> ImplItem {
     let query_str: String = format!(
-        "INSERT INTO {} ({}) VALUES ({})",
+        "INSERT INTO {} ({}) VALUES ({}) IF NOT EXISTS",
         ch_args.table_name(),
         fields.db_fields.comma_sep_cols(),
         fields.db_fields.insert_bind_markers(),
     );
 
     let generated = quote! {
-        const INSERT_QUERY: &'static str = #query_str;
+        const INSERT_IF_NOT_EXIST_QUERY: &'static str = #query_str;
     };
 
     syn::parse_quote!(#generated)
 }
 
-pub(crate) fn insert_if_not_exists_query_const(ch_args: &CharybdisMacroArgs, fields: &CharybdisFields) -> ImplItem {
+pub(crate) fn insert_with_ttl_query_const(ch_args: &CharybdisMacroArgs, fields: &CharybdisFields) -> ImplItem {
     let query_str: String = format!(
-        "INSERT INTO {} ({}) VALUES ({}) IF NOT EXISTS",
+        "INSERT INTO {} ({}) VALUES ({}) USING TTL ?",
         ch_args.table_name(),
         fields.db_fields.comma_sep_cols(),
         fields.db_fields.insert_bind_markers(),
     );
 
     let generated = quote! {
-        const INSERT_IF_NOT_EXIST_QUERY: &'static str = #query_str;
+        const INSERT_WITH_TTL_QUERY: &'static str = #query_str;
+    };
+
+    syn::parse_quote!(#generated)
+}
+
+pub(crate) fn insert_if_not_exists_with_ttl_query_const(
+    ch_args: &CharybdisMacroArgs,
+    fields: &CharybdisFields,
+) -> ImplItem {
+    let query_str: String = format!(
+        "INSERT INTO {} ({}) VALUES ({}) IF NOT EXISTS USING TTL ?",
+        ch_args.table_name(),
+        fields.db_fields.comma_sep_cols(),
+        fields.db_fields.insert_bind_markers(),
+    );
+
+    let generated = quote! {
+        const INSERT_IF_NOT_EXIST_WITH_TTL_QUERY: &'static str = #query_str;
     };
 
     syn::parse_quote!(#generated)
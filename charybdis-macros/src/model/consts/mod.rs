@@ -2,6 +2,7 @@ pub(crate) use delete::*;
 pub(crate) use find::*;
 pub(crate) use insert::*;
 pub(crate) use model_name::*;
+pub(crate) use ttl::*;
 pub(crate) use update::*;
 
 mod delete;
@@ -9,4 +10,5 @@ mod find;
 mod insert;
 
 mod model_name;
+mod ttl;
 mod update;
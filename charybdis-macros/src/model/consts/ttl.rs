@@ -0,0 +1,17 @@
+use quote::quote;
+use syn::ImplItem;
+
+use charybdis_parser::traits::CharybdisMacroArgs;
+
+pub(crate) fn default_ttl_const(ch_args: &CharybdisMacroArgs) -> ImplItem {
+    let generated = match ch_args.default_ttl {
+        Some(seconds) => quote! {
+            const DEFAULT_TTL: Option<std::time::Duration> = Some(std::time::Duration::from_secs(#seconds));
+        },
+        None => quote! {
+            const DEFAULT_TTL: Option<std::time::Duration> = None;
+        },
+    };
+
+    syn::parse_quote!(#generated)
+}
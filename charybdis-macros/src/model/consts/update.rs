@@ -20,3 +20,18 @@ pub(crate) fn update_query_const(ch_args: &CharybdisMacroArgs, fields: &Charybdi
 
     syn::parse_quote!(#generated)
 }
+
+pub(crate) fn update_with_ttl_query_const(ch_args: &CharybdisMacroArgs, fields: &CharybdisFields) -> ImplItem {
+    let query_str: String = format!(
+        "UPDATE {} USING TTL ? SET {} WHERE {}",
+        ch_args.table_name(),
+        fields.non_primary_key_db_fields().set_bind_markers(),
+        fields.primary_key_fields.where_bind_markers(),
+    );
+
+    let generated = quote! {
+        const UPDATE_WITH_TTL_QUERY: &'static str = #query_str;
+    };
+
+    syn::parse_quote!(#generated)
+}
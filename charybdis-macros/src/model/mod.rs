@@ -1,5 +1,7 @@
 pub(crate) use consts::*;
 pub(crate) use values::*;
+pub(crate) use writetime::*;
 
 mod consts;
 mod values;
+mod writetime;
@@ -0,0 +1,66 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::DeriveInput;
+
+use charybdis_parser::fields::CharybdisFields;
+use charybdis_parser::traits::CharybdisMacroArgs;
+
+use crate::model::find_writetime_by_primary_key_query_const;
+
+fn writetime_struct_name(input: &DeriveInput, ch_args: &CharybdisMacroArgs) -> syn::Ident {
+    match &ch_args.writetime_struct {
+        Some(name) => format_ident!("{}", name),
+        None => format_ident!("{}Writetime", input.ident),
+    }
+}
+
+/// Struct holding `WRITETIME` and `TTL` of model's columns, deserialized in order of
+/// `FIND_WRITETIME_BY_PRIMARY_KEY_QUERY` selectors, together with `WritetimeModel` implementation. Nothing is
+/// generated for models without columns that support these selectors.
+pub(crate) fn writetime_model(
+    input: &DeriveInput,
+    ch_args: &CharybdisMacroArgs,
+    fields: &CharybdisFields,
+) -> TokenStream {
+    let writetime_fields = fields.writetime_fields();
+
+    if writetime_fields.is_empty() {
+        return TokenStream::new();
+    }
+
+    let vis = &input.vis;
+    let model_name = &input.ident;
+    let struct_name = writetime_struct_name(input, ch_args);
+    let doc = format!(
+        "`WRITETIME` and `TTL` of [`{}`] columns, as returned by `find_writetime_by_primary_key`.",
+        model_name
+    );
+
+    let struct_fields = writetime_fields.into_iter().map(|field| {
+        let writetime = format_ident!("{}_writetime", field.ident);
+        let ttl = format_ident!("{}_ttl", field.ident);
+
+        quote! {
+            pub #writetime: Option<i64>,
+            pub #ttl: Option<i32>,
+        }
+    });
+
+    let find_writetime_by_primary_key_query_const = find_writetime_by_primary_key_query_const(ch_args, fields);
+
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Default, Clone, PartialEq, Eq)]
+        #[derive(charybdis::macros::scylla::DeserializeRow)]
+        #[scylla(flavor = "enforce_order", skip_name_checks)]
+        #vis struct #struct_name {
+            #(#struct_fields)*
+        }
+
+        impl charybdis::model::WritetimeModel for #model_name {
+            type Writetime = #struct_name;
+
+            #find_writetime_by_primary_key_query_const
+        }
+    }
+}
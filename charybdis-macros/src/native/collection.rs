@@ -20,16 +20,26 @@ pub(crate) fn push_to_collection_consts(ch_args: &CharybdisMacroArgs, fields: &C
             let query_str = format!(
                 "UPDATE {} SET {} = {} + ? WHERE {}",
                 ch_args.table_name(),
-                field.name,
-                field.name,
+                field.cql_name(),
+                field.cql_name(),
+                fields.primary_key_fields.where_placeholders(),
+            );
+            let ttl_query_str = format!(
+                "UPDATE {} USING TTL ? SET {} = {} + ? WHERE {}",
+                ch_args.table_name(),
+                field.cql_name(),
+                field.cql_name(),
                 fields.primary_key_fields.where_placeholders(),
             );
 
             let const_name = format!("PUSH_{}_QUERY", field.name.to_uppercase());
             let const_name: TokenStream = parse_str::<TokenStream>(&const_name).unwrap();
+            let ttl_const_name = format!("PUSH_{}_WITH_TTL_QUERY", field.name.to_uppercase());
+            let ttl_const_name: TokenStream = parse_str::<TokenStream>(&ttl_const_name).unwrap();
 
             let expanded = quote! {
                 pub const #const_name: &'static str = #query_str;
+                pub const #ttl_const_name: &'static str = #ttl_query_str;
             };
 
             Some(expanded)
@@ -58,16 +68,26 @@ pub(crate) fn push_to_collection_consts_if_exists(
             let query_str = format!(
                 "UPDATE {} SET {} = {} + ? WHERE {} IF EXISTS",
                 ch_args.table_name(),
-                field.name,
-                field.name,
+                field.cql_name(),
+                field.cql_name(),
+                fields.primary_key_fields.where_placeholders(),
+            );
+            let ttl_query_str = format!(
+                "UPDATE {} USING TTL ? SET {} = {} + ? WHERE {} IF EXISTS",
+                ch_args.table_name(),
+                field.cql_name(),
+                field.cql_name(),
                 fields.primary_key_fields.where_placeholders(),
             );
 
             let const_name = format!("PUSH_{}_IF_EXISTS_QUERY", field.name.to_uppercase());
             let const_name: TokenStream = parse_str::<TokenStream>(&const_name).unwrap();
+            let ttl_const_name = format!("PUSH_{}_IF_EXISTS_WITH_TTL_QUERY", field.name.to_uppercase());
+            let ttl_const_name: TokenStream = parse_str::<TokenStream>(&ttl_const_name).unwrap();
 
             let expanded = quote! {
                 pub const #const_name: &'static str = #query_str;
+                pub const #ttl_const_name: &'static str = #ttl_query_str;
             };
 
             Some(expanded)
@@ -93,16 +113,26 @@ pub(crate) fn pull_from_collection_consts(ch_args: &CharybdisMacroArgs, fields:
             let query_str = format!(
                 "UPDATE {} SET {} = {} - ? WHERE {}",
                 ch_args.table_name(),
-                field.name,
-                field.name,
+                field.cql_name(),
+                field.cql_name(),
+                fields.primary_key_fields.where_placeholders(),
+            );
+            let ttl_query_str = format!(
+                "UPDATE {} USING TTL ? SET {} = {} - ? WHERE {}",
+                ch_args.table_name(),
+                field.cql_name(),
+                field.cql_name(),
                 fields.primary_key_fields.where_placeholders(),
             );
 
             let const_name = format!("PULL_{}_QUERY", field.name.to_uppercase());
             let const_name: TokenStream = parse_str::<TokenStream>(&const_name).unwrap();
+            let ttl_const_name = format!("PULL_{}_WITH_TTL_QUERY", field.name.to_uppercase());
+            let ttl_const_name: TokenStream = parse_str::<TokenStream>(&ttl_const_name).unwrap();
 
             let expanded = quote! {
                 pub const #const_name: &'static str = #query_str;
+                pub const #ttl_const_name: &'static str = #ttl_query_str;
             };
 
             Some(expanded)
@@ -131,16 +161,26 @@ pub(crate) fn pull_from_collection_consts_if_exists(
             let query_str = format!(
                 "UPDATE {} SET {} = {} - ? WHERE {} IF EXISTS",
                 ch_args.table_name(),
-                field.name,
-                field.name,
+                field.cql_name(),
+                field.cql_name(),
+                fields.primary_key_fields.where_placeholders(),
+            );
+            let ttl_query_str = format!(
+                "UPDATE {} USING TTL ? SET {} = {} - ? WHERE {} IF EXISTS",
+                ch_args.table_name(),
+                field.cql_name(),
+                field.cql_name(),
                 fields.primary_key_fields.where_placeholders(),
             );
 
             let const_name = format!("PULL_{}_IF_EXISTS_QUERY", field.name.to_uppercase());
             let const_name: TokenStream = parse_str::<TokenStream>(&const_name).unwrap();
+            let ttl_const_name = format!("PULL_{}_IF_EXISTS_WITH_TTL_QUERY", field.name.to_uppercase());
+            let ttl_const_name: TokenStream = parse_str::<TokenStream>(&ttl_const_name).unwrap();
 
             let expanded = quote! {
                 pub const #const_name: &'static str = #query_str;
+                pub const #ttl_const_name: &'static str = #ttl_query_str;
             };
 
             Some(expanded)
@@ -165,6 +205,8 @@ pub(crate) fn push_to_collection_methods(fields: &CharybdisFields) -> TokenStrea
 
             let push_to_query_str = format!("Self::PUSH_{}_QUERY", field.name.to_uppercase());
             let push_to_query = parse_str::<TokenStream>(&push_to_query_str).unwrap();
+            let push_to_ttl_query_str = format!("Self::PUSH_{}_WITH_TTL_QUERY", field.name.to_uppercase());
+            let push_to_ttl_query = parse_str::<TokenStream>(&push_to_ttl_query_str).unwrap();
             let fun_name_str = format!("push_{}", field.name);
             let fun_name = parse_str::<TokenStream>(&fun_name_str).unwrap();
             let types = fields.primary_key_fields.types();
@@ -179,6 +221,7 @@ pub(crate) fn push_to_collection_methods(fields: &CharybdisFields) -> TokenStrea
                         #push_to_query,
                         charybdis::query::QueryValue::Owned((value, #(#values),*)),
                     )
+                    .ttl_query(#push_to_ttl_query, <Self as charybdis::model::Model>::DEFAULT_TTL)
                 }
             };
 
@@ -204,6 +247,8 @@ pub(crate) fn push_to_collection_methods_if_exists(fields: &CharybdisFields) ->
 
             let push_to_query_str = format!("Self::PUSH_{}_IF_EXISTS_QUERY", field.name.to_uppercase());
             let push_to_query = parse_str::<TokenStream>(&push_to_query_str).unwrap();
+            let push_to_ttl_query_str = format!("Self::PUSH_{}_IF_EXISTS_WITH_TTL_QUERY", field.name.to_uppercase());
+            let push_to_ttl_query = parse_str::<TokenStream>(&push_to_ttl_query_str).unwrap();
             let fun_name_str = format!("push_{}_if_exists", field.name);
             let fun_name = parse_str::<TokenStream>(&fun_name_str).unwrap();
             let types = fields.primary_key_fields.types();
@@ -218,6 +263,7 @@ pub(crate) fn push_to_collection_methods_if_exists(fields: &CharybdisFields) ->
                         #push_to_query,
                         charybdis::query::QueryValue::Owned((value, #(#values),*)),
                     )
+                    .ttl_query(#push_to_ttl_query, <Self as charybdis::model::Model>::DEFAULT_TTL)
                 }
             };
 
@@ -243,6 +289,8 @@ pub(crate) fn pull_from_collection_methods(fields: &CharybdisFields) -> TokenStr
 
             let pull_from_query_str = format!("Self::PULL_{}_QUERY", field.name.to_uppercase());
             let pull_from_query = parse_str::<TokenStream>(&pull_from_query_str).unwrap();
+            let pull_from_ttl_query_str = format!("Self::PULL_{}_WITH_TTL_QUERY", field.name.to_uppercase());
+            let pull_from_ttl_query = parse_str::<TokenStream>(&pull_from_ttl_query_str).unwrap();
             let fun_name_str = format!("pull_{}", field.name);
             let fun_name = parse_str::<TokenStream>(&fun_name_str).unwrap();
             let types = fields.primary_key_fields.types();
@@ -257,6 +305,7 @@ pub(crate) fn pull_from_collection_methods(fields: &CharybdisFields) -> TokenStr
                         #pull_from_query,
                         charybdis::query::QueryValue::Owned((value, #(#values),*)),
                     )
+                    .ttl_query(#pull_from_ttl_query, <Self as charybdis::model::Model>::DEFAULT_TTL)
                 }
             };
 
@@ -282,6 +331,8 @@ pub(crate) fn pull_from_collection_methods_if_exists(fields: &CharybdisFields) -
 
             let pull_from_query_str = format!("Self::PULL_{}_IF_EXISTS_QUERY", field.name.to_uppercase());
             let pull_from_query = parse_str::<TokenStream>(&pull_from_query_str).unwrap();
+            let pull_from_ttl_query_str = format!("Self::PULL_{}_IF_EXISTS_WITH_TTL_QUERY", field.name.to_uppercase());
+            let pull_from_ttl_query = parse_str::<TokenStream>(&pull_from_ttl_query_str).unwrap();
             let fun_name_str = format!("pull_{}_if_exists", field.name);
             let fun_name = parse_str::<TokenStream>(&fun_name_str).unwrap();
             let types = fields.primary_key_fields.types();
@@ -296,6 +347,7 @@ pub(crate) fn pull_from_collection_methods_if_exists(fields: &CharybdisFields) -
                         #pull_from_query,
                         charybdis::query::QueryValue::Owned((value, #(#values),*)),
                     )
+                    .ttl_query(#pull_from_ttl_query, <Self as charybdis::model::Model>::DEFAULT_TTL)
                 }
             };
 
@@ -20,8 +20,8 @@ pub(crate) fn increment_counter_methods(ch_args: &CharybdisMacroArgs, fields: &C
             let increment_query = format!(
                 "UPDATE {} SET {} = {} + ? WHERE {}",
                 ch_args.table_name(),
-                field.name,
-                field.name,
+                field.cql_name(),
+                field.cql_name(),
                 fields.primary_key_fields.where_placeholders()
             );
 
@@ -65,8 +65,8 @@ pub(crate) fn decrement_counter_methods(ch_args: &CharybdisMacroArgs, fields: &C
             let decrement_query = format!(
                 "UPDATE {} SET {} = {} - ? WHERE {}",
                 ch_args.table_name(),
-                field.name,
-                field.name,
+                field.cql_name(),
+                field.cql_name(),
                 fields.primary_key_fields.where_placeholders()
             );
 
@@ -1,10 +1,14 @@
+use std::collections::HashSet;
+
 use proc_macro2::TokenStream;
 use quote::quote;
 
 use charybdis_parser::fields::{CharybdisFields, Field};
+use charybdis_parser::schema::secondary_indexes::IndexTargetKind;
 use charybdis_parser::traits::CharybdisMacroArgs;
 
-use crate::traits::fields::{FieldsFindFirstFns, FieldsFindFn, FieldsQuery};
+use crate::traits::fields::{FieldsFindFirstFns, FieldsFindFn, FieldsFindFnNames, FieldsQuery};
+use crate::traits::string::ToIdent;
 
 const MAX_FIND_BY_FIELDS: usize = 3;
 
@@ -151,7 +155,12 @@ pub(crate) fn find_by_global_secondary_index(
     let mut generated = quote! {};
 
     gsi_fields.iter().for_each(|gsi| {
-        let query_str = format!("SELECT {} FROM {} WHERE {} = ?", comma_sep_cols, table_name, gsi.name);
+        let query_str = format!(
+            "SELECT {} FROM {} WHERE {} = ?",
+            comma_sep_cols,
+            table_name,
+            gsi.cql_name()
+        );
         let find_fn = gsi.find_fn(struct_name, &query_str);
         let find_first_fn = gsi.find_first_fn(struct_name, &query_str);
         let maybe_find_first_fn = gsi.maybe_find_first_fn(struct_name, &query_str);
@@ -163,3 +172,130 @@ pub(crate) fn find_by_global_secondary_index(
 
     generated
 }
+
+/// for each vector index field, generate approximate nearest neighbor search function e.g.
+/// `Product::find_by_embedding_ann(embedding, 10)` that queries
+/// `SELECT ... FROM products ORDER BY embedding ANN OF ? LIMIT ?`
+pub(crate) fn find_by_vector_index_ann(
+    struct_name: &syn::Ident,
+    ch_args: &CharybdisMacroArgs,
+    fields: &CharybdisFields,
+) -> TokenStream {
+    let table_name = ch_args.table_name();
+    let comma_sep_cols = fields.db_fields.comma_sep_cols();
+
+    let mut generated = quote! {};
+
+    fields.vector_index_fields.iter().for_each(|field| {
+        let query_str = format!(
+            "SELECT {} FROM {} ORDER BY {} ANN OF ? LIMIT ?",
+            comma_sep_cols,
+            table_name,
+            field.cql_name()
+        );
+        let fn_name = format!("find_by_{}_ann", field.name).to_ident();
+        let field_ident = &field.ident;
+        let field_type = &field.ty;
+
+        generated.extend(quote! {
+            pub fn #fn_name<'a>(
+                #field_ident: #field_type,
+                limit: i32,
+            ) -> charybdis::query::CharybdisQuery<'a, (#field_type, i32), Self, charybdis::query::ModelStream> {
+                <#struct_name as charybdis::operations::Find>::find(#query_str, (#field_ident, limit))
+            }
+        });
+    });
+
+    generated
+}
+
+/// for indexes declared with `indexes` model argument, generate find functions e.g.
+/// ```ignore
+/// // indexes = [sai(title), keys(attributes), values(tags), entries(attributes)]
+/// Post::find_by_title(title);
+/// Post::find_by_attributes_containing_key(key);
+/// Post::find_by_tags_containing(tag);
+/// Post::find_by_attributes_entry(key, value);
+/// ```
+pub(crate) fn find_by_index(
+    struct_name: &syn::Ident,
+    ch_args: &CharybdisMacroArgs,
+    fields: &CharybdisFields,
+) -> TokenStream {
+    let table_name = ch_args.table_name();
+    let comma_sep_cols = fields.db_fields.comma_sep_cols();
+    let select = format!("SELECT {} FROM {}", comma_sep_cols, table_name);
+
+    let mut generated_fns = HashSet::new();
+    let mut generated = quote! {};
+
+    fields.index_fields.iter().for_each(|(field, kind)| {
+        let column = field.cql_name();
+
+        match kind {
+            IndexTargetKind::Column | IndexTargetKind::Full => {
+                // global secondary index on the same field already generates these functions
+                if ch_args.global_secondary_indexes().contains(&field.name)
+                    || !generated_fns.insert(field.find_by_fn_name())
+                {
+                    return;
+                }
+
+                let query_str = format!("{} WHERE {} = ?", select, column);
+
+                generated.extend(field.find_fn(struct_name, &query_str));
+                generated.extend(field.find_first_fn(struct_name, &query_str));
+                generated.extend(field.maybe_find_first_fn(struct_name, &query_str));
+            }
+            IndexTargetKind::Values | IndexTargetKind::Keys => {
+                let (fn_name, query_str) = if *kind == IndexTargetKind::Values {
+                    (
+                        format!("find_by_{}_containing", field.name),
+                        format!("{} WHERE {} CONTAINS ?", select, column),
+                    )
+                } else {
+                    (
+                        format!("find_by_{}_containing_key", field.name),
+                        format!("{} WHERE {} CONTAINS KEY ?", select, column),
+                    )
+                };
+
+                if !generated_fns.insert(fn_name.clone()) {
+                    return;
+                }
+
+                let fn_name = fn_name.to_ident();
+
+                generated.extend(quote! {
+                    pub fn #fn_name<'a, V: charybdis::scylla::SerializeValue>(
+                        value: V,
+                    ) -> charybdis::query::CharybdisQuery<'a, (V,), Self, charybdis::query::ModelStream> {
+                        <#struct_name as charybdis::operations::Find>::find(#query_str, (value,))
+                    }
+                });
+            }
+            IndexTargetKind::Entries => {
+                let fn_name = format!("find_by_{}_entry", field.name);
+
+                if !generated_fns.insert(fn_name.clone()) {
+                    return;
+                }
+
+                let fn_name = fn_name.to_ident();
+                let query_str = format!("{} WHERE {}[?] = ?", select, column);
+
+                generated.extend(quote! {
+                    pub fn #fn_name<'a, K: charybdis::scylla::SerializeValue, V: charybdis::scylla::SerializeValue>(
+                        key: K,
+                        value: V,
+                    ) -> charybdis::query::CharybdisQuery<'a, (K, V), Self, charybdis::query::ModelStream> {
+                        <#struct_name as charybdis::operations::Find>::find(#query_str, (key, value))
+                    }
+                });
+            }
+        }
+    });
+
+    generated
+}
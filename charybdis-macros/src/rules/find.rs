@@ -87,3 +87,56 @@ pub(crate) fn find_first_model_rule(
 
     expanded
 }
+
+/// `find_<model>_ann!(embedding, (vector, limit))` and
+/// `find_<model>_ann!(embedding, "category_id = ?", (category_id, vector, limit))`
+/// for models with vector indexes.
+pub(crate) fn find_model_ann_rule(
+    struct_name: &Ident,
+    args: &CharybdisMacroArgs,
+    fields: &CharybdisFields,
+) -> TokenStream {
+    if fields.vector_index_fields.is_empty() {
+        return quote! {};
+    }
+
+    let macro_name_str: String = format!("find_{}_ann", struct_name.to_string().to_snake_case());
+    let macro_name: TokenStream = parse_str::<TokenStream>(&macro_name_str).unwrap();
+    let comma_sep_cols = fields.db_fields.comma_sep_cols();
+    let table_name = args.table_name();
+
+    let arms = fields.vector_index_fields.iter().map(|field| {
+        let field_ident = &field.ident;
+        let query_str = format!(
+            "SELECT {} FROM {} ORDER BY {} ANN OF ? LIMIT ?",
+            comma_sep_cols,
+            table_name,
+            field.cql_name()
+        );
+        let where_query_str = format!("SELECT {} FROM {} WHERE ", comma_sep_cols, table_name);
+        let order_by_str = format!(" ORDER BY {} ANN OF ? LIMIT ?", field.cql_name());
+
+        quote! {
+            (#field_ident, $values: expr) => {
+                <#struct_name as charybdis::operations::Find>::find(#query_str, $values)
+            };
+            (#field_ident, $query: literal, $values: expr) => {
+                <#struct_name as charybdis::operations::Find>::find(
+                    concat!(#where_query_str, $query, #order_by_str),
+                    $values
+                )
+            };
+        }
+    });
+
+    let expanded = quote! {
+        #[allow(unused_macros)]
+        macro_rules! #macro_name {
+            #(#arms)*
+        }
+
+        pub(crate) use #macro_name;
+    };
+
+    expanded
+}
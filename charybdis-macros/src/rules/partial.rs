@@ -83,6 +83,7 @@ pub(crate) fn partial_model_macro_generator(
     let clustering_keys: Vec<syn::Ident> = fields.clustering_key_fields.to_idents();
     let global_secondary_indexes: Vec<syn::Ident> = fields.global_secondary_index_fields.to_idents();
     let local_secondary_indexes: Vec<syn::Ident> = fields.local_secondary_index_fields.to_idents();
+    let default_ttl = args.default_ttl.map(|seconds| quote! { default_ttl=#seconds, });
 
     // attributes that are not charybdis_model
     let other_attrs = &input
@@ -106,6 +107,7 @@ pub(crate) fn partial_model_macro_generator(
                     clustering_keys=[ #(#clustering_keys),* ],
                     global_secondary_indexes=[ #(#global_secondary_indexes),* ],
                     local_secondary_indexes=[ #(#local_secondary_indexes),* ],
+                    #default_ttl
                     exclude_partial_model=true
                 )]
                 #(#other_attrs)*
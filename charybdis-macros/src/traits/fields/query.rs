@@ -1,7 +1,5 @@
 use charybdis_parser::fields::Field;
 
-use crate::traits::fields::FieldsNames;
-
 pub(crate) trait FieldsQuery {
     fn comma_sep_cols(&self) -> String;
     fn insert_bind_markers(&self) -> String;
@@ -12,13 +10,16 @@ pub(crate) trait FieldsQuery {
 
 impl FieldsQuery for Vec<&Field<'_>> {
     fn comma_sep_cols(&self) -> String {
-        self.names().join(", ")
+        self.iter()
+            .map(|field| field.cql_name())
+            .collect::<Vec<String>>()
+            .join(", ")
     }
 
     fn insert_bind_markers(&self) -> String {
         let str_vec = self
             .iter()
-            .map(|field| format!(":{}", field.name))
+            .map(|field| format!(":{}", field.cql_name()))
             .collect::<Vec<String>>()
             .join(", ");
 
@@ -27,21 +28,21 @@ impl FieldsQuery for Vec<&Field<'_>> {
 
     fn set_bind_markers(&self) -> String {
         self.iter()
-            .map(|field| format!("{} = :{}", field.name, field.name))
+            .map(|field| format!("{} = :{}", field.cql_name(), field.cql_name()))
             .collect::<Vec<String>>()
             .join(", ")
     }
 
     fn where_placeholders(&self) -> String {
         self.iter()
-            .map(|field| format!("{} = ?", field.name))
+            .map(|field| format!("{} = ?", field.cql_name()))
             .collect::<Vec<String>>()
             .join(" AND ")
     }
 
     fn where_bind_markers(&self) -> String {
         self.iter()
-            .map(|field| format!("{} = :{}", field.name, field.name))
+            .map(|field| format!("{} = :{}", field.cql_name(), field.cql_name()))
             .collect::<Vec<String>>()
             .join(" AND ")
     }
@@ -1,15 +1,37 @@
-use clap::Parser;
+use crate::config::{non_empty_hosts, Config, ConnectionConfig, CONFIG_FILE, PROFILE_ENV};
+use crate::errors::MigrationError;
+use crate::report::OutputFormat;
+use clap::{Parser, Subcommand};
 use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
 
-#[derive(Parser, Debug)]
+/// Storage-attached index class, supported by Cassandra 5.
+pub const DEFAULT_VECTOR_INDEX_CLASS: &str = "StorageAttachedIndex";
+
+/// Seconds to wait for connection, if not configured.
+pub const DEFAULT_TIMEOUT: u64 = 30;
+
+/// Seconds to wait for schema agreement, if not configured.
+pub const DEFAULT_SCHEMA_AGREEMENT_TIMEOUT: u64 = 60;
+
+#[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
-    /// Scylla Host
-    #[arg(long)]
-    pub host: String,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Scylla hosts used as contact points, e.g. `--host 10.0.0.1:9042,10.0.0.2:9042`. Required by all commands
+    /// except `diff`
+    #[arg(long, alias = "hosts", value_delimiter = ',')]
+    pub host: Vec<String>,
 
-    /// Keyspace
-    #[arg(short, long)]
+    /// Datacenter that is preferred by load balancing
+    #[arg(long, default_value = None)]
+    pub local_dc: Option<String>,
+
+    /// Keyspace. Required by all commands except `diff`
+    #[arg(short, long, default_value_t, hide_default_value = true)]
     pub keyspace: String,
 
     #[arg(short, long, default_value = None)]
@@ -18,17 +40,87 @@ pub struct Args {
     #[arg(short, long, default_value = None)]
     pub password: Option<String>,
 
-    #[arg(short, long, default_value_t = 30)]
-    pub timeout: u64,
+    /// Seconds to wait for connection. Defaults to 30
+    #[arg(short, long, default_value = None)]
+    pub timeout: Option<u64>,
+
+    /// Seconds to wait for all nodes to agree on schema after each schema altering statement. Defaults to 60
+    #[arg(long, default_value = None)]
+    pub schema_agreement_timeout: Option<u64>,
+
+    /// Seconds after which migration lock expires if migrator stops renewing it, e.g. because it crashed
+    #[arg(long, default_value_t = 60)]
+    pub lock_ttl: u64,
+
+    /// Seconds to wait for migration lock held by another migrator
+    #[arg(long, default_value_t = 300)]
+    pub lock_timeout: u64,
 
     /// Drop and recreate columns in case of type change
     #[arg(short, long, default_value_t = false)]
     pub drop_and_replace: bool,
 
+    /// Drop and recreate materialized views whose columns, keys, base table or clustering order changed.
+    /// Also enabled by `--drop-and-replace`.
+    #[arg(long, default_value_t = false)]
+    pub recreate_views: bool,
+
+    /// Rebuild tables with changed partition or clustering keys into `<table>_rebuild` table with new key layout
+    /// and copy rows into it
+    #[arg(long, default_value_t = false)]
+    pub rebuild_tables: bool,
+
+    /// After rebuild, drop original table, recreate it with new key layout and copy rows back from
+    /// `<table>_rebuild` table, which is dropped afterwards
+    #[arg(long, default_value_t = false, requires = "rebuild_tables")]
+    pub swap_rebuilt_tables: bool,
+
+    /// Drop tables, UDTs and materialized views that are no longer defined in code
+    #[arg(long, default_value_t = false)]
+    pub drop_removed_models: bool,
+
+    /// Comma separated names of removed models, and `table.column` or `table.index` names of removed columns and
+    /// indexes that can be dropped without confirmation
+    #[arg(long, value_delimiter = ',')]
+    pub allow_drop: Vec<String>,
+
+    /// Drop columns and indexes that are no longer defined in code. Otherwise, only drops listed in `--allow-drop`
+    /// are executed and others are reported as blocked
+    #[arg(long, default_value_t = false)]
+    pub allow_destructive: bool,
+
+    /// Index class used to create vector indexes, e.g. `vector_index` for ScyllaDB or `StorageAttachedIndex` for
+    /// Cassandra
+    #[arg(long, default_value = DEFAULT_VECTOR_INDEX_CLASS)]
+    pub vector_index_class: String,
+
+    /// Files or directories to parse models from, as globs relative to the current directory, e.g.
+    /// `--models-path crates/*/src/models`. Overrides `models.paths` of the configuration file
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub models_path: Vec<String>,
+
+    /// Files or directories to skip when parsing models, as globs relative to the current directory, e.g.
+    /// `--exclude **/tests`. Added to `models.exclude` of the configuration file
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub exclude: Vec<String>,
+
+    /// Features to evaluate `cfg(feature = "...")` gates of models with, e.g. `--features search,analytics`. If not
+    /// set, gated models are always migrated
+    #[arg(long, value_delimiter = ',', global = true)]
+    pub features: Option<Vec<String>>,
+
+    /// Prints migration plan without executing it
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+
     /// Prints alter table options queries
     #[arg(long, default_value_t = false)]
     pub verbose: bool,
 
+    /// Output format. With `json`, migration report or schema diff is printed to stdout and progress to stderr
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, global = true)]
+    pub format: OutputFormat,
+
     /// Path to the CA file if using TLS
     #[arg(long, default_value = None)]
     pub ca: Option<String>,
@@ -41,33 +133,191 @@ pub struct Args {
     #[arg(long, default_value = None)]
     pub key: Option<String>,
 
+    /// Directory of versioned migration files, relative to the current directory
+    #[arg(long, default_value = "migrations")]
+    pub migrations_dir: String,
+
+    /// Configuration file, relative to the current directory
+    #[arg(long, default_value = CONFIG_FILE, global = true)]
+    pub config: String,
+
+    /// Configuration profile, e.g. `prod`, that overrides top level configuration. Defaults to `CHARYBDIS_PROFILE`
+    /// environment variable
+    #[arg(long, default_value = None, global = true)]
+    pub profile: Option<String>,
+
     #[arg(skip = get_current_dir())]
     pub current_dir: String,
 
     /// If set, use the given JSON-serialized CodeSchema instead of scanning the current directory
     #[arg(default_value = None)]
     pub code_schema_override_json: Option<String>,
+
+    /// Configuration loaded by `apply_config`, so that it's resolved only once.
+    #[arg(skip)]
+    pub resolved_config: Option<Config>,
 }
 
 impl Default for Args {
     fn default() -> Self {
         Args {
-            host: String::new(),
+            command: None,
+            host: vec![],
+            local_dc: None,
             keyspace: String::new(),
             user: None,
             password: None,
-            timeout: 30,
+            timeout: None,
+            schema_agreement_timeout: None,
+            lock_ttl: 60,
+            lock_timeout: 300,
             drop_and_replace: false,
+            recreate_views: false,
+            rebuild_tables: false,
+            swap_rebuilt_tables: false,
+            drop_removed_models: false,
+            allow_drop: vec![],
+            allow_destructive: false,
+            vector_index_class: DEFAULT_VECTOR_INDEX_CLASS.to_string(),
+            models_path: vec![],
+            exclude: vec![],
+            features: None,
+            dry_run: false,
             verbose: false,
+            format: OutputFormat::Text,
             ca: None,
             cert: None,
             key: None,
+            migrations_dir: "migrations".to_string(),
+            config: CONFIG_FILE.to_string(),
+            profile: None,
             current_dir: get_current_dir(),
             code_schema_override_json: None,
+            resolved_config: None,
         }
     }
 }
 
+impl Args {
+    pub fn migrations_path(&self) -> PathBuf {
+        PathBuf::from(&self.current_dir).join(&self.migrations_dir)
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        PathBuf::from(&self.current_dir).join(&self.config)
+    }
+
+    /// Profile given by `--profile` or `CHARYBDIS_PROFILE` environment variable.
+    pub fn profile(&self) -> Option<String> {
+        self.profile
+            .clone()
+            .or_else(|| env::var(PROFILE_ENV).ok().filter(|profile| !profile.is_empty()))
+    }
+
+    /// Loads configuration file with selected profile, and fills connection settings that are not given by
+    /// arguments from `CHARYBDIS_*` environment variables and then from configuration file. Configuration is
+    /// resolved only once, later calls return already resolved configuration.
+    pub fn apply_config(&mut self) -> Result<Config, MigrationError> {
+        match &self.resolved_config {
+            Some(config) => Ok(config.clone()),
+            None => self.resolve_config(ConnectionConfig::from_env()?),
+        }
+    }
+
+    fn resolve_config(&mut self, env_connection: ConnectionConfig) -> Result<Config, MigrationError> {
+        let config = Config::load_profile(&self.config_path(), self.profile().as_deref())?;
+        let connection = env_connection.or(config.connection.clone().unwrap_or_default());
+
+        self.host = non_empty_hosts(self.host.iter().map(String::as_str));
+
+        if self.host.is_empty() {
+            self.host = non_empty_hosts(connection.hosts.iter().flatten().map(String::as_str));
+        }
+
+        if self.keyspace.is_empty() {
+            self.keyspace = connection.keyspace.unwrap_or_default();
+        }
+
+        self.local_dc = self.local_dc.take().or(connection.local_dc);
+        self.user = self.user.take().or(connection.user);
+        self.password = self.password.take().or(connection.password);
+        self.ca = self.ca.take().or(connection.ca);
+        self.cert = self.cert.take().or(connection.cert);
+        self.key = self.key.take().or(connection.key);
+        self.timeout = self.timeout.or(connection.timeout);
+        self.schema_agreement_timeout = self.schema_agreement_timeout.or(connection.schema_agreement_timeout);
+        self.resolved_config = Some(config.clone());
+
+        Ok(config)
+    }
+
+    /// Checks if column or index of given table can be dropped, either with `--allow-destructive` or with
+    /// `table.name` listed in `--allow-drop`.
+    pub fn is_destructive_allowed(&self, object_name: &str, name: &str) -> bool {
+        self.allow_destructive || self.allow_drop.contains(&allow_drop_name(object_name, name))
+    }
+
+    /// Time to wait for all nodes to agree on schema after each schema altering statement.
+    pub fn schema_agreement_timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.schema_agreement_timeout
+                .unwrap_or(DEFAULT_SCHEMA_AGREEMENT_TIMEOUT),
+        )
+    }
+}
+
+/// Versioned migrations. Without a command, migration is run directly from the detected schema diff.
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Writes detected migration plan as the next numbered `.cql` file into migrations directory
+    Generate {
+        /// Name appended to migration version, e.g. `0002_add_posts.cql`
+        #[arg(long, default_value = "migration")]
+        name: String,
+    },
+    /// Applies pending migration files and records them in `charybdis_migrations` table
+    Up,
+    /// Prints applied, pending and modified migration files
+    Status,
+    /// Removes migration lock regardless of its owner, e.g. after migrator was killed while holding it
+    Unlock,
+    /// Generates model files from existing keyspace
+    Modelize {
+        /// Directory of generated model files, relative to the current directory
+        #[arg(long, default_value = "src/models")]
+        models_dir: String,
+
+        /// Overwrite existing model files
+        #[arg(long, default_value_t = false)]
+        overwrite: bool,
+    },
+    /// Compares two schema JSON snapshots without connecting to the database. Fails on unsafe changes.
+    Diff {
+        /// Schema JSON to compare from, e.g. checked in `current_schema.json`
+        #[arg(long)]
+        from: String,
+
+        /// Schema JSON to compare to. Defaults to schema parsed from models in the current directory
+        #[arg(long)]
+        to: Option<String>,
+
+        /// Don't fail on unsafe changes
+        #[arg(long, default_value_t = false)]
+        allow_unsafe: bool,
+    },
+}
+
+impl Command {
+    pub fn requires_session(&self) -> bool {
+        !matches!(self, Command::Diff { .. })
+    }
+}
+
+/// `--allow-drop` entry of column or index of given table, e.g. `users.bio`.
+pub fn allow_drop_name(object_name: &str, name: &str) -> String {
+    format!("{}.{}", object_name, name)
+}
+
 pub(crate) fn get_current_dir() -> String {
     let path = env::current_dir().expect("Failed to find project root: Could not get current directory");
 
@@ -75,3 +325,107 @@ pub(crate) fn get_current_dir() -> String {
         .expect("Failed to find project root: Could not convert path to string")
         .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [connection]
+        hosts = ["file:9042"]
+        keyspace = "file_keyspace"
+        user = "file_user"
+        password = "file_password"
+        local_dc = "file_dc"
+        timeout = 10
+
+        [profiles.prod.connection]
+        user = "profile_user"
+        password = "profile_password"
+        local_dc = "profile_dc"
+    "#;
+
+    fn args(name: &str, cli_args: &[&str]) -> Args {
+        let dir = env::temp_dir().join(format!("charybdis_args_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(CONFIG_FILE), CONFIG).unwrap();
+
+        let mut args = Args::try_parse_from([&["migrate"], cli_args].concat()).unwrap();
+        args.current_dir = dir.display().to_string();
+
+        args
+    }
+
+    fn env(vars: &[(&str, &str)]) -> ConnectionConfig {
+        ConnectionConfig::from_vars(|name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| value.to_string())
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn arguments_take_precedence_over_env_profile_and_file() {
+        let mut args = args("precedence", &["--profile", "prod", "--user", "cli_user"]);
+        let env = env(&[("CHARYBDIS_USER", "env_user"), ("CHARYBDIS_PASSWORD", "env_password")]);
+
+        args.resolve_config(env).unwrap();
+
+        assert_eq!(args.user.as_deref(), Some("cli_user"));
+        assert_eq!(args.password.as_deref(), Some("env_password"));
+        assert_eq!(args.local_dc.as_deref(), Some("profile_dc"));
+        assert_eq!(args.keyspace, "file_keyspace");
+        assert_eq!(args.host, vec!["file:9042"]);
+        assert_eq!(args.timeout, Some(10));
+
+        std::fs::remove_dir_all(&args.current_dir).unwrap();
+    }
+
+    #[test]
+    fn file_is_used_without_profile() {
+        let mut args = args("file", &[]);
+
+        args.resolve_config(env(&[])).unwrap();
+
+        assert_eq!(args.user.as_deref(), Some("file_user"));
+        assert_eq!(args.local_dc.as_deref(), Some("file_dc"));
+
+        std::fs::remove_dir_all(&args.current_dir).unwrap();
+    }
+
+    #[test]
+    fn empty_hosts_are_ignored() {
+        let cases = [
+            ("cli_hosts", vec!["--host", "cli:9042,,"], "env:9042", vec!["cli:9042"]),
+            ("env_hosts", vec![], " a:9042,, b:9042 ,", vec!["a:9042", "b:9042"]),
+            ("only_commas", vec![], ",,", vec!["file:9042"]),
+        ];
+
+        for (name, cli_args, env_hosts, expected_hosts) in cases {
+            let mut args = args(name, &cli_args);
+
+            args.resolve_config(env(&[("CHARYBDIS_HOSTS", env_hosts)])).unwrap();
+
+            assert_eq!(args.host, expected_hosts);
+
+            std::fs::remove_dir_all(&args.current_dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn config_is_resolved_once() {
+        let mut args = args("resolved_once", &[]);
+        let current_dir = args.current_dir.clone();
+
+        args.apply_config().unwrap();
+        std::fs::remove_dir_all(&current_dir).unwrap();
+
+        args.keyspace.clear();
+        args.config = "missing_charybdis.toml".to_string();
+        let config = args.apply_config().unwrap();
+
+        assert!(config.connection.is_some());
+        assert!(args.keyspace.is_empty());
+    }
+}
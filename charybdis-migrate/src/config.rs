@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::{env, fs};
+
+use serde::Deserialize;
+
+use crate::errors::MigrationError;
+
+/// Default configuration file, looked up in the current directory.
+pub const CONFIG_FILE: &str = "charybdis.toml";
+
+/// Environment variable that selects configuration profile if `--profile` is not given.
+pub const PROFILE_ENV: &str = "CHARYBDIS_PROFILE";
+
+/// Contents of `charybdis.toml`. Missing file results in default configuration.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub connection: Option<ConnectionConfig>,
+    pub keyspace: Option<KeyspaceConfig>,
+    pub models: Option<ModelsConfig>,
+    /// Named environments, e.g. `[profiles.prod]`, that override sections of top level configuration.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Config, MigrationError> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let content =
+            fs::read_to_string(path).map_err(|e| MigrationError::ConfigReadError(path.display().to_string(), e))?;
+
+        toml::from_str(&content).map_err(|e| MigrationError::ConfigParseError(path.display().to_string(), e))
+    }
+
+    /// Loads configuration with given profile applied. Connection settings of profile override top level ones
+    /// one by one, while its `keyspace` and `models` sections replace top level sections.
+    pub fn load_profile(path: &Path, profile: Option<&str>) -> Result<Config, MigrationError> {
+        let mut config = Config::load(path)?;
+
+        let Some(profile) = profile else {
+            return Ok(config);
+        };
+
+        let profile_config = config
+            .profiles
+            .remove(profile)
+            .ok_or_else(|| MigrationError::ProfileNotFound(profile.to_string(), path.display().to_string()))?;
+
+        if let Some(connection) = profile_config.connection {
+            config.connection = Some(connection.or(config.connection.unwrap_or_default()));
+        }
+
+        if profile_config.keyspace.is_some() {
+            config.keyspace = profile_config.keyspace;
+        }
+
+        if profile_config.models.is_some() {
+            config.models = profile_config.models;
+        }
+
+        Ok(config)
+    }
+}
+
+/// Sections of configuration that can be overridden by profile.
+///
+/// ```toml
+/// [profiles.prod.connection]
+/// hosts = ["10.0.1.1:9042", "10.0.1.2:9042"]
+/// local_dc = "eu-west"
+/// ```
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    pub connection: Option<ConnectionConfig>,
+    pub keyspace: Option<KeyspaceConfig>,
+    pub models: Option<ModelsConfig>,
+}
+
+/// Connection settings. Each of them can also be set by `CHARYBDIS_<NAME>` environment variable, e.g.
+/// `CHARYBDIS_HOSTS=10.0.0.1:9042,10.0.0.2:9042` or `CHARYBDIS_PASSWORD`, which takes precedence over file.
+///
+/// ```toml
+/// [connection]
+/// hosts = ["10.0.0.1:9042", "10.0.0.2:9042"]
+/// local_dc = "dc1"
+/// keyspace = "app"
+/// user = "migrator"
+/// ca = "certs/ca.pem"
+/// timeout = 30
+/// schema_agreement_timeout = 60
+/// ```
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionConfig {
+    pub hosts: Option<Vec<String>>,
+    /// Datacenter that is preferred by load balancing.
+    pub local_dc: Option<String>,
+    pub keyspace: Option<String>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub ca: Option<String>,
+    pub cert: Option<String>,
+    pub key: Option<String>,
+    pub timeout: Option<u64>,
+    pub schema_agreement_timeout: Option<u64>,
+}
+
+impl ConnectionConfig {
+    /// Connection settings given by `CHARYBDIS_*` environment variables.
+    pub fn from_env() -> Result<ConnectionConfig, MigrationError> {
+        Self::from_vars(|name| env::var(name).ok())
+    }
+
+    /// Connection settings given by `CHARYBDIS_*` variables looked up by `var`. Empty values and empty hosts, e.g.
+    /// of `CHARYBDIS_HOSTS=a,,b,`, are ignored.
+    pub(crate) fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<ConnectionConfig, MigrationError> {
+        let var = |name: &str| var(&format!("CHARYBDIS_{}", name)).filter(|value| !value.is_empty());
+        let seconds = |name: &str| -> Result<Option<u64>, MigrationError> {
+            var(name)
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| MigrationError::InvalidEnvVar(format!("CHARYBDIS_{}", name), value))
+                })
+                .transpose()
+        };
+        let hosts = var("HOSTS")
+            .map(|hosts| non_empty_hosts(hosts.split(',')))
+            .filter(|hosts| !hosts.is_empty());
+
+        Ok(ConnectionConfig {
+            hosts,
+            local_dc: var("LOCAL_DC"),
+            keyspace: var("KEYSPACE"),
+            user: var("USER"),
+            password: var("PASSWORD"),
+            ca: var("CA"),
+            cert: var("CERT"),
+            key: var("KEY"),
+            timeout: seconds("TIMEOUT")?,
+            schema_agreement_timeout: seconds("SCHEMA_AGREEMENT_TIMEOUT")?,
+        })
+    }
+
+    /// Settings of `self`, with missing ones taken from `other`.
+    pub fn or(self, other: ConnectionConfig) -> ConnectionConfig {
+        ConnectionConfig {
+            hosts: self.hosts.or(other.hosts),
+            local_dc: self.local_dc.or(other.local_dc),
+            keyspace: self.keyspace.or(other.keyspace),
+            user: self.user.or(other.user),
+            password: self.password.or(other.password),
+            ca: self.ca.or(other.ca),
+            cert: self.cert.or(other.cert),
+            key: self.key.or(other.key),
+            timeout: self.timeout.or(other.timeout),
+            schema_agreement_timeout: self.schema_agreement_timeout.or(other.schema_agreement_timeout),
+        }
+    }
+}
+
+/// Trimmed hosts, without empty ones left by repeated or trailing commas.
+pub(crate) fn non_empty_hosts<'a>(hosts: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    hosts
+        .into_iter()
+        .map(str::trim)
+        .filter(|host| !host.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Keyspace definition that is created if missing and altered when replication or durable writes change.
+///
+/// ```toml
+/// [keyspace]
+/// durable_writes = true
+/// replication = { class = "NetworkTopologyStrategy", dc1 = 3, dc2 = 2 }
+/// tablets = { enabled = true, initial = 8 }
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct KeyspaceConfig {
+    pub replication: ReplicationConfig,
+    pub durable_writes: Option<bool>,
+    /// Scylla tablets options. They are only applied when keyspace is created.
+    pub tablets: Option<TabletsConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "class")]
+pub enum ReplicationConfig {
+    SimpleStrategy {
+        replication_factor: u32,
+    },
+    /// Replication factor per datacenter, or `replication_factor` for all datacenters.
+    NetworkTopologyStrategy {
+        #[serde(flatten)]
+        datacenters: BTreeMap<String, u32>,
+    },
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TabletsConfig {
+    pub enabled: bool,
+    pub initial: Option<u32>,
+}
+
+/// Scope of model discovery, as globs relative to the current directory.
+///
+/// ```toml
+/// [models]
+/// paths = ["crates/*/src/models"]
+/// exclude = ["**/tests", "crates/legacy"]
+/// features = ["search"]
+/// ```
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ModelsConfig {
+    /// Files and directories to parse models from. All `.rs` files are parsed if empty.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Files and directories to skip. `target` directories are always skipped.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Enabled features that `cfg(feature = "...")` gates of models are evaluated with.
+    pub features: Option<Vec<String>>,
+}
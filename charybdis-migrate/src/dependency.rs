@@ -0,0 +1,230 @@
+use crate::errors::MigrationError;
+use crate::model::ModelType;
+use charybdis_parser::schema::code_schema::CodeSchema;
+use charybdis_parser::schema::{ModelName, SchemaObject, SchemaObjects};
+use std::collections::BTreeMap;
+
+/// Schema objects of a single type, sorted by name so that orders are deterministic.
+pub(crate) type SortedObjects<'a> = BTreeMap<&'a ModelName, &'a SchemaObject>;
+
+pub(crate) fn sorted_objects(objects: &SchemaObjects) -> SortedObjects<'_> {
+    objects.iter().collect()
+}
+
+/// UDTs, tables and materialized views of code schema in order in which they can be created: UDTs after UDTs
+/// their fields reference, tables after UDTs they use and views after their base tables. Objects that don't
+/// depend on each other are ordered by type and name, so plans are deterministic.
+pub(crate) fn creation_order(code_schema: &CodeSchema) -> Result<Vec<(ModelType, &ModelName)>, MigrationError> {
+    dependency_order(
+        sorted_objects(&code_schema.udts),
+        sorted_objects(&code_schema.tables),
+        sorted_objects(&code_schema.materialized_views),
+    )
+}
+
+/// Orders given objects so that each one comes after objects it depends on. Dependencies on objects that are not
+/// given are ignored, so the same order can be computed for a subset of schema, e.g. for models that are dropped in
+/// reverse of it.
+pub(crate) fn dependency_order<'a>(
+    udts: SortedObjects<'a>,
+    tables: SortedObjects<'a>,
+    materialized_views: SortedObjects<'a>,
+) -> Result<Vec<(ModelType, &'a ModelName)>, MigrationError> {
+    let mut pending: Vec<(ModelType, &ModelName, &SchemaObject)> = vec![];
+
+    for (model_type, objects) in [
+        (ModelType::Udt, &udts),
+        (ModelType::Table, &tables),
+        (ModelType::MaterializedView, &materialized_views),
+    ] {
+        pending.extend(objects.iter().map(|(name, object)| (model_type, *name, *object)));
+    }
+
+    let dependencies = |object: &SchemaObject, model_type: ModelType| -> Vec<(ModelType, &ModelName)> {
+        match model_type {
+            ModelType::MaterializedView => tables
+                .get_key_value(&object.base_table)
+                .map(|(name, _)| (ModelType::Table, *name))
+                .or_else(|| {
+                    materialized_views
+                        .get_key_value(&object.base_table)
+                        .map(|(name, _)| (ModelType::MaterializedView, *name))
+                })
+                .into_iter()
+                .collect(),
+            _ => udts
+                .keys()
+                .filter(|udt| {
+                    object
+                        .fields
+                        .iter()
+                        .any(|(_, field_type, _)| type_references(field_type, udt))
+                })
+                .map(|udt| (ModelType::Udt, *udt))
+                .collect(),
+        }
+    };
+
+    let mut ordered: Vec<(ModelType, &ModelName)> = vec![];
+
+    while let Some((first_type, first_name, _)) = pending.first().copied() {
+        let ready = pending.iter().position(|(model_type, _, object)| {
+            dependencies(object, *model_type)
+                .iter()
+                .all(|dependency| ordered.contains(dependency))
+        });
+
+        if let Some(position) = ready {
+            let (model_type, name, _) = pending.remove(position);
+            ordered.push((model_type, name));
+
+            continue;
+        }
+
+        // each pending object depends on another pending object, so following first pending dependency from any
+        // of them leads to a cycle
+        let mut path: Vec<(ModelType, &ModelName)> = vec![];
+        let mut next = Some((first_type, first_name));
+
+        while let Some(current) = next {
+            if let Some(start) = path.iter().position(|visited| *visited == current) {
+                path.drain(..start);
+                path.push(current);
+
+                break;
+            }
+
+            path.push(current);
+
+            next = pending
+                .iter()
+                .find(|(model_type, name, _)| (*model_type, *name) == current)
+                .and_then(|(model_type, _, object)| {
+                    dependencies(object, *model_type)
+                        .into_iter()
+                        .find(|dependency| !ordered.contains(dependency))
+                });
+        }
+
+        let cycle = path
+            .iter()
+            .map(|(model_type, name)| format!("{} {}", model_type, name))
+            .collect();
+
+        return Err(MigrationError::DependencyCycle(cycle));
+    }
+
+    Ok(ordered)
+}
+
+/// Checks if CQL type, e.g. `frozen<list<frozen<address>>>`, references given type name.
+pub(crate) fn type_references(cql_type: &str, type_name: &str) -> bool {
+    cql_type
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .any(|part| part.eq_ignore_ascii_case(type_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(field_types: &[&str], base_table: &str) -> SchemaObject {
+        SchemaObject {
+            fields: field_types
+                .iter()
+                .enumerate()
+                .map(|(i, field_type)| (format!("field_{}", i), field_type.to_string(), false))
+                .collect(),
+            base_table: base_table.to_string(),
+            ..SchemaObject::default()
+        }
+    }
+
+    fn objects(objects: &[(&str, SchemaObject)]) -> SchemaObjects {
+        objects
+            .iter()
+            .map(|(name, object)| (name.to_string(), object.clone()))
+            .collect()
+    }
+
+    fn names(order: &[(ModelType, &ModelName)]) -> Vec<String> {
+        order
+            .iter()
+            .map(|(model_type, name)| format!("{} {}", model_type, name))
+            .collect()
+    }
+
+    #[test]
+    fn objects_are_ordered_after_their_dependencies() {
+        let udts = objects(&[
+            ("address", object(&["frozen<country>", "text"], "")),
+            ("country", object(&["text"], "")),
+        ]);
+        let tables = objects(&[
+            ("posts", object(&["uuid", "text"], "")),
+            ("users", object(&["uuid", "frozen<list<frozen<address>>>"], "")),
+        ]);
+        let materialized_views = objects(&[
+            ("users_by_email", object(&["uuid"], "users")),
+            ("active_users_by_email", object(&["uuid"], "users_by_email")),
+        ]);
+
+        let order = dependency_order(
+            sorted_objects(&udts),
+            sorted_objects(&tables),
+            sorted_objects(&materialized_views),
+        )
+        .unwrap();
+
+        assert_eq!(
+            names(&order),
+            vec![
+                "UDT country",
+                "UDT address",
+                "Table posts",
+                "Table users",
+                "Materialized View users_by_email",
+                "Materialized View active_users_by_email",
+            ]
+        );
+    }
+
+    #[test]
+    fn dependencies_outside_of_given_objects_are_ignored() {
+        let udts = objects(&[("address", object(&["frozen<country>"], ""))]);
+        let materialized_views = objects(&[("users_by_email", object(&["uuid"], "users"))]);
+
+        let order = dependency_order(
+            sorted_objects(&udts),
+            SortedObjects::new(),
+            sorted_objects(&materialized_views),
+        )
+        .unwrap();
+
+        assert_eq!(names(&order), vec!["UDT address", "Materialized View users_by_email"]);
+    }
+
+    #[test]
+    fn cycle_is_reported() {
+        let udts = objects(&[
+            ("a", object(&["frozen<b>"], "")),
+            ("b", object(&["frozen<c>"], "")),
+            ("c", object(&["frozen<b>"], "")),
+        ]);
+        let tables = objects(&[("posts", object(&["frozen<a>"], ""))]);
+
+        let result = dependency_order(sorted_objects(&udts), sorted_objects(&tables), SortedObjects::new());
+
+        match result {
+            Err(MigrationError::DependencyCycle(cycle)) => assert_eq!(cycle, vec!["UDT b", "UDT c", "UDT b"]),
+            other => panic!("expected dependency cycle, got {:?}", other.map(|order| names(&order))),
+        }
+    }
+
+    #[test]
+    fn type_references_match_whole_type_names() {
+        assert!(type_references("frozen<list<frozen<address>>>", "address"));
+        assert!(type_references("map<text, frozen<Address>>", "address"));
+        assert!(!type_references("frozen<home_address>", "address"));
+    }
+}
@@ -0,0 +1,377 @@
+use std::error::Error;
+use std::fmt;
+
+use charybdis_parser::errors::{CodeSchemaError, DbSchemaParserError};
+use colored::Colorize;
+use scylla::deserialize::DeserializationError;
+use scylla::errors::{
+    ExecutionError, IntoRowsResultError, MaybeFirstRowError, NewSessionError, NextRowError, PagerExecutionError,
+    PrepareError, RowsError, SchemaAgreementError, TypeCheckError, UseKeyspaceError,
+};
+
+use crate::model::ModelType;
+use crate::report::{MigrationReport, StepStatus};
+
+#[derive(Debug)]
+pub enum MigrationError {
+    // scylla
+    ExecutionError(String, ExecutionError),
+    IntoRowsResultError(String, IntoRowsResultError),
+    RowsError(String, RowsError),
+    DeserializationError(String, DeserializationError),
+    MaybeFirstRowError(String, MaybeFirstRowError),
+    SchemaAgreementError(String, SchemaAgreementError),
+    PagerExecutionError(String, PagerExecutionError),
+    NextRowError(String, NextRowError),
+    TypeCheckError(String, TypeCheckError),
+    DbSchemaParserError(DbSchemaParserError),
+    CodeSchemaError(CodeSchemaError),
+    SkippedModelFiles(Vec<String>),
+    JsonError(serde_json::Error),
+    KeyspaceNotSet,
+    SchemaFileError(String, std::io::Error),
+    UnsafeSchemaChanges(usize),
+    // session
+    ConnectionNotConfigured,
+    NewSessionError(NewSessionError),
+    UseKeyspaceError(String, UseKeyspaceError),
+    TlsError(String, openssl::error::ErrorStack),
+    PrivateKeyRequired,
+    // configuration
+    ConfigReadError(String, std::io::Error),
+    ConfigParseError(String, toml::de::Error),
+    ProfileNotFound(String, String),
+    InvalidEnvVar(String, String),
+    // migration files
+    MigrationFileError(String, std::io::Error),
+    ChecksumMismatch(String),
+    DuplicateMigrationVersion(i32, String, String),
+    PendingMigrations(Vec<String>),
+    CopyRowsInMigrationFile(String),
+    // migration lock
+    LockTimeout(String),
+    LockLost(String),
+    // migration run
+    MigrationFailed(Box<MigrationReport>, Box<MigrationError>),
+    // table rebuild
+    PrepareError(String, PrepareError),
+    TableRebuildWithViews(String, Vec<String>),
+    CounterTableRebuild(String),
+    CounterFieldRename(String, String),
+    CopyVerificationFailed(String, u64, u64),
+    // migration order
+    DependencyCycle(Vec<String>),
+    // illegal changes
+    PartitionKeyChange(String, ModelType),
+    ClusteringKeyChange(String, ModelType),
+    FieldTypeChange(String, ModelType),
+    UdtFieldsRemoval(String, ModelType),
+    MaterializedViewChange(String, ModelType),
+}
+
+impl MigrationError {
+    fn illegal_change(f: &mut fmt::Formatter<'_>, name: &str, model_type: &ModelType, reason: &str) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}\n{}",
+            "Illegal change in".bright_red(),
+            name.bright_yellow(),
+            model_type.to_string().bright_magenta(),
+            reason.bright_red(),
+        )
+    }
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::ExecutionError(cql, e) => {
+                write!(
+                    f,
+                    "CQL execution failed! ❌\nStatement: {}\nExecutionError: {}",
+                    cql.bright_purple(),
+                    e
+                )
+            }
+            MigrationError::IntoRowsResultError(cql, e) => {
+                write!(f, "Statement: {}\nIntoRowsResultError: {}", cql.bright_purple(), e)
+            }
+            MigrationError::RowsError(cql, e) => write!(f, "Statement: {}\nRowsError: {:?}", cql.bright_purple(), e),
+            MigrationError::DeserializationError(cql, e) => {
+                write!(f, "Statement: {}\nDeserializationError: {:?}", cql.bright_purple(), e)
+            }
+            MigrationError::MaybeFirstRowError(cql, e) => {
+                write!(f, "Statement: {}\nMaybeFirstRowError: {}", cql.bright_purple(), e)
+            }
+            MigrationError::SchemaAgreementError(cql, e) => write!(
+                f,
+                "{}\nStatement: {}\nSchemaAgreementError: {}\n{}",
+                "Nodes didn't agree on schema after statement! ❌".bright_red(),
+                cql.bright_purple(),
+                e,
+                "Statement was applied, but some nodes may not see it yet. Check cluster health or increase \
+                `--schema-agreement-timeout` and run migration again!"
+                    .bright_red(),
+            ),
+            MigrationError::PagerExecutionError(cql, e) => {
+                write!(f, "Statement: {}\nPagerExecutionError: {}", cql.bright_purple(), e)
+            }
+            MigrationError::NextRowError(cql, e) => {
+                write!(f, "Statement: {}\nNextRowError: {}", cql.bright_purple(), e)
+            }
+            MigrationError::TypeCheckError(cql, e) => {
+                write!(f, "Statement: {}\nTypeCheckError: {}", cql.bright_purple(), e)
+            }
+            MigrationError::DbSchemaParserError(e) => write!(f, "Error getting schema from system_schema: {}", e),
+            MigrationError::CodeSchemaError(e) => write!(f, "{}", e.to_string().bright_red()),
+            MigrationError::SkippedModelFiles(paths) => write!(
+                f,
+                "{}\n{}\n{}",
+                "Models are missing in code, but some model files couldn't be parsed:".bright_red(),
+                paths.join("\n").bright_yellow(),
+                "Fix these files first, so that their models are not dropped!".bright_red(),
+            ),
+            MigrationError::JsonError(e) => write!(f, "JsonError: {:?}", e),
+            MigrationError::KeyspaceNotSet => write!(f, "No keyspace provided and no default keyspace set"),
+            MigrationError::SchemaFileError(path, e) => {
+                write!(f, "Schema file error: {}\n{}", path.bright_yellow(), e)
+            }
+            MigrationError::UnsafeSchemaChanges(count) => write!(
+                f,
+                "{} {}\n{}",
+                "Unsafe schema changes detected:".bright_red(),
+                count.to_string().bright_yellow(),
+                "Use `--allow-unsafe` to allow them!".bright_red(),
+            ),
+            MigrationError::ConnectionNotConfigured => write!(
+                f,
+                "{}",
+                "Both `--host` and `--keyspace` are required, as arguments, `CHARYBDIS_HOSTS` and `CHARYBDIS_KEYSPACE` \
+                environment variables or in `[connection]` section of configuration file"
+                    .bright_red()
+            ),
+            MigrationError::NewSessionError(e) => write!(f, "{} {}", "Failed to create session:".bright_red(), e),
+            MigrationError::UseKeyspaceError(keyspace, e) => write!(
+                f,
+                "{} {}\n{}",
+                "Failed to use keyspace".bright_red(),
+                keyspace.bright_yellow(),
+                e
+            ),
+            MigrationError::TlsError(context, e) => write!(f, "{} {}", context.bright_red(), e),
+            MigrationError::PrivateKeyRequired => write!(
+                f,
+                "{}",
+                "Private key file `--key` is required when certificate is provided".bright_red()
+            ),
+            MigrationError::ConfigReadError(path, e) => {
+                write!(f, "Configuration file error: {}\n{}", path.bright_yellow(), e)
+            }
+            MigrationError::ConfigParseError(path, e) => {
+                write!(f, "Invalid configuration file: {}\n{}", path.bright_yellow(), e)
+            }
+            MigrationError::ProfileNotFound(profile, path) => write!(
+                f,
+                "{} {} {} {}",
+                "Profile".bright_red(),
+                profile.bright_yellow(),
+                "is not defined in".bright_red(),
+                path.bright_yellow()
+            ),
+            MigrationError::InvalidEnvVar(name, value) => write!(
+                f,
+                "{} {}={}",
+                "Invalid value of environment variable".bright_red(),
+                name.bright_yellow(),
+                value
+            ),
+            MigrationError::MigrationFileError(path, e) => {
+                write!(f, "Migration file error: {}\n{}", path.bright_yellow(), e)
+            }
+            MigrationError::ChecksumMismatch(file_name) => write!(
+                f,
+                "{} {}\n{}",
+                "Checksum mismatch for already applied migration".bright_red(),
+                file_name.bright_yellow(),
+                "Applied migration files must not be modified!".bright_red(),
+            ),
+            MigrationError::DuplicateMigrationVersion(version, first, second) => write!(
+                f,
+                "{} {} {} {}\n{}",
+                "Migration files".bright_red(),
+                first.bright_yellow(),
+                "and".bright_red(),
+                second.bright_yellow(),
+                format!(
+                    "have the same version {}. Each version must be used only once!",
+                    version
+                )
+                .bright_red(),
+            ),
+            MigrationError::PendingMigrations(file_names) => write!(
+                f,
+                "{}\n{}",
+                "There are pending migration files. Apply them with `migrate up` first:".bright_red(),
+                file_names.join("\n").bright_yellow(),
+            ),
+            MigrationError::CopyRowsInMigrationFile(name) => write!(
+                f,
+                "{} {}\n{}",
+                "Rebuild of table".bright_red(),
+                name.bright_yellow(),
+                "copies rows and can't be written to migration file. Run `migrate --rebuild-tables` instead!"
+                    .bright_red(),
+            ),
+            MigrationError::LockTimeout(owner) => write!(
+                f,
+                "{} {}\n{}",
+                "Timed out waiting for migration lock held by".bright_red(),
+                owner.bright_yellow(),
+                "If its migrator is no longer running, remove the lock with `migrate unlock`!".bright_red(),
+            ),
+            MigrationError::LockLost(owner) => write!(
+                f,
+                "{} {}\n{}",
+                "Migration lock was lost and is now held by".bright_red(),
+                owner.bright_yellow(),
+                "Migration was interrupted. Increase `--lock-ttl` and run migration again!".bright_red(),
+            ),
+            MigrationError::MigrationFailed(report, e) => write!(
+                f,
+                "{}\n{} {} {} {}",
+                e,
+                "Migration failed! Executed steps:".bright_red(),
+                report
+                    .steps_with_status(StepStatus::Executed)
+                    .to_string()
+                    .bright_yellow(),
+                "skipped steps:".bright_red(),
+                report
+                    .steps_with_status(StepStatus::Skipped)
+                    .to_string()
+                    .bright_yellow(),
+            ),
+            MigrationError::PrepareError(cql, e) => {
+                write!(f, "Statement: {}\nPrepareError: {}", cql.bright_purple(), e)
+            }
+            MigrationError::TableRebuildWithViews(name, views) => write!(
+                f,
+                "{} {} {} {}\n{}",
+                "Table".bright_red(),
+                name.bright_yellow(),
+                "can't be swapped while it has materialized views:".bright_red(),
+                views.join(", ").bright_yellow(),
+                "Drop the views or rebuild without `--swap-rebuilt-tables`!".bright_red(),
+            ),
+            MigrationError::CounterTableRebuild(name) => write!(
+                f,
+                "{} {} {}",
+                "Counter table".bright_red(),
+                name.bright_yellow(),
+                "can't be rebuilt, as counter values can't be inserted into new table!".bright_red(),
+            ),
+            MigrationError::CounterFieldRename(name, field) => write!(
+                f,
+                "{} {} {} {} {}",
+                "Counter field".bright_red(),
+                field.bright_yellow(),
+                "of".bright_red(),
+                name.bright_yellow(),
+                "can't be renamed, as counter values can't be copied into new field!".bright_red(),
+            ),
+            MigrationError::CopyVerificationFailed(name, copied, found) => write!(
+                f,
+                "{} {} {} {} {}\n{}",
+                "Copied".bright_red(),
+                copied.to_string().bright_yellow(),
+                "rows, but table".bright_red(),
+                name.bright_yellow(),
+                format!("contains {} rows!", found).bright_red(),
+                "Rows with the same new primary key are merged. Tables were not dropped, check them before running \
+                migration again!"
+                    .bright_red(),
+            ),
+            MigrationError::DependencyCycle(cycle) => write!(
+                f,
+                "{}\n{}",
+                "Cyclic dependency between models detected:".bright_red(),
+                cycle.join(" -> ").bright_yellow(),
+            ),
+            MigrationError::PartitionKeyChange(name, model_type) => Self::illegal_change(
+                f,
+                name,
+                model_type,
+                "Partition key change is not allowed. Use `--rebuild-tables` flag to rebuild table with new keys!",
+            ),
+            MigrationError::ClusteringKeyChange(name, model_type) => Self::illegal_change(
+                f,
+                name,
+                model_type,
+                "Clustering key change is not allowed. Use `--rebuild-tables` flag to rebuild table with new keys!",
+            ),
+            MigrationError::FieldTypeChange(name, model_type) => Self::illegal_change(
+                f,
+                name,
+                model_type,
+                "Field type change is not allowed. Use `-d` flag to drop and recreate field with new type!",
+            ),
+            MigrationError::UdtFieldsRemoval(name, model_type) => {
+                Self::illegal_change(f, name, model_type, "UDT fields removal is not allowed!")
+            }
+            MigrationError::MaterializedViewChange(name, model_type) => Self::illegal_change(
+                f,
+                name,
+                model_type,
+                "Materialized view change requires recreating the view. Use `--recreate-views` flag to drop and \
+                recreate it!",
+            ),
+        }
+    }
+}
+
+impl Error for MigrationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MigrationError::ExecutionError(_, e) => Some(e),
+            MigrationError::IntoRowsResultError(_, e) => Some(e),
+            MigrationError::RowsError(_, e) => Some(e),
+            MigrationError::DeserializationError(_, e) => Some(e),
+            MigrationError::MaybeFirstRowError(_, e) => Some(e),
+            MigrationError::SchemaAgreementError(_, e) => Some(e),
+            MigrationError::PagerExecutionError(_, e) => Some(e),
+            MigrationError::NextRowError(_, e) => Some(e),
+            MigrationError::TypeCheckError(_, e) => Some(e),
+            MigrationError::DbSchemaParserError(e) => Some(e),
+            MigrationError::CodeSchemaError(e) => Some(e),
+            MigrationError::SchemaFileError(_, e) => Some(e),
+            MigrationError::NewSessionError(e) => Some(e),
+            MigrationError::UseKeyspaceError(_, e) => Some(e),
+            MigrationError::TlsError(_, e) => Some(e),
+            MigrationError::ConfigReadError(_, e) => Some(e),
+            MigrationError::ConfigParseError(_, e) => Some(e),
+            MigrationError::JsonError(e) => Some(e),
+            MigrationError::MigrationFileError(_, e) => Some(e),
+            MigrationError::PrepareError(_, e) => Some(e),
+            MigrationError::MigrationFailed(_, e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<DbSchemaParserError> for MigrationError {
+    fn from(e: DbSchemaParserError) -> Self {
+        MigrationError::DbSchemaParserError(e)
+    }
+}
+
+impl From<CodeSchemaError> for MigrationError {
+    fn from(e: CodeSchemaError) -> Self {
+        MigrationError::CodeSchemaError(e)
+    }
+}
+
+impl From<serde_json::Error> for MigrationError {
+    fn from(e: serde_json::Error) -> Self {
+        MigrationError::JsonError(e)
+    }
+}
@@ -0,0 +1,733 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use scylla::client::session::Session;
+use sha2::{Digest, Sha256};
+
+use crate::args::DEFAULT_SCHEMA_AGREEMENT_TIMEOUT;
+use crate::errors::MigrationError;
+use crate::lock::MigrationLock;
+use crate::migration::execute_cql;
+use crate::plan::MigrationPlan;
+use crate::report::OutputFormat;
+
+/// Table in the migrated keyspace that keeps track of applied migration files.
+pub const MIGRATIONS_TABLE: &str = "charybdis_migrations";
+
+const MIGRATION_FILE_EXTENSION: &str = "cql";
+
+/// Numbered `.cql` file from migrations directory, e.g. `0003_add_posts.cql`.
+#[derive(Debug, Clone)]
+pub struct MigrationFile {
+    pub version: i32,
+    pub name: String,
+    pub path: PathBuf,
+    pub content: String,
+    pub checksum: String,
+}
+
+impl MigrationFile {
+    fn read(path: &Path) -> Result<Option<Self>, MigrationError> {
+        let file_name = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(file_name) => file_name,
+            None => return Ok(None),
+        };
+
+        if path.extension().and_then(|ext| ext.to_str()) != Some(MIGRATION_FILE_EXTENSION) {
+            return Ok(None);
+        }
+
+        let (version, name) = match file_name.split_once('_') {
+            Some((version, name)) => match version.parse::<i32>() {
+                Ok(version) => (version, name.to_string()),
+                Err(_) => return Ok(None),
+            },
+            None => return Ok(None),
+        };
+
+        let content =
+            fs::read_to_string(path).map_err(|e| MigrationError::MigrationFileError(path.display().to_string(), e))?;
+        let checksum = checksum(&content);
+
+        Ok(Some(Self {
+            version,
+            name,
+            path: path.to_path_buf(),
+            content,
+            checksum,
+        }))
+    }
+
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// CQL statements of the file. Statements are terminated by `;`, while `--`, `//` and `/* */` comments are
+    /// removed. Semicolons within string literals, quoted identifiers and `$$` strings don't terminate statements.
+    pub fn statements(&self) -> Vec<String> {
+        split_statements(&self.content)
+    }
+
+    /// Checks which statements of partially applied file were already executed and returns number of statements
+    /// to skip. Statements that were executed must not be modified, while the rest of the file can be fixed.
+    fn applied_statements(&self, applied: &AppliedMigration) -> Result<usize, MigrationError> {
+        let statements = self.statements();
+        let applied_statements = applied.applied_statements as usize;
+
+        if applied_statements > statements.len()
+            || statements_checksum(&statements[..applied_statements]) != applied.checksum
+        {
+            return Err(MigrationError::ChecksumMismatch(self.file_name()));
+        }
+
+        Ok(applied_statements)
+    }
+}
+
+fn split_statements(cql: &str) -> Vec<String> {
+    let mut statements = vec![];
+    let mut current = String::new();
+    let mut chars = cql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            // quote within literal is escaped by doubling it, which is the same as closing and reopening literal
+            '\'' | '"' => {
+                current.push(c);
+
+                for next in chars.by_ref() {
+                    current.push(next);
+
+                    if next == c {
+                        break;
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'$') => {
+                current.push(c);
+                current.push(chars.next().unwrap_or(c));
+
+                let mut previous = None;
+
+                for next in chars.by_ref() {
+                    current.push(next);
+
+                    if previous == Some('$') && next == '$' {
+                        break;
+                    }
+
+                    previous = Some(next);
+                }
+            }
+            '-' | '/' if chars.peek() == Some(&c) => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        current.push(next);
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+
+                let mut previous = None;
+
+                for next in chars.by_ref() {
+                    if previous == Some('*') && next == '/' {
+                        break;
+                    }
+
+                    previous = Some(next);
+                }
+
+                current.push(' ');
+            }
+            ';' => {
+                push_statement(&mut statements, &current);
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+
+    push_statement(&mut statements, &current);
+
+    statements
+}
+
+fn push_statement(statements: &mut Vec<String>, statement: &str) {
+    let statement = statement.trim();
+
+    if !statement.is_empty() {
+        statements.push(statement.to_string());
+    }
+}
+
+/// Checksum of executed statements of partially applied file.
+fn statements_checksum(statements: &[String]) -> String {
+    checksum(&statements.join(";\n"))
+}
+
+/// Migration files of the directory sorted by version. Fails if two files have the same version.
+fn read_migration_files(migrations_dir: &Path) -> Result<Vec<MigrationFile>, MigrationError> {
+    let mut files = vec![];
+
+    if !migrations_dir.exists() {
+        return Ok(files);
+    }
+
+    let entries = fs::read_dir(migrations_dir)
+        .map_err(|e| MigrationError::MigrationFileError(migrations_dir.display().to_string(), e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| MigrationError::MigrationFileError(migrations_dir.display().to_string(), e))?;
+
+        if let Some(file) = MigrationFile::read(&entry.path())? {
+            files.push(file);
+        }
+    }
+
+    files.sort_by(|a, b| a.version.cmp(&b.version).then_with(|| a.path.cmp(&b.path)));
+
+    for pair in files.windows(2) {
+        if pair[0].version == pair[1].version {
+            return Err(MigrationError::DuplicateMigrationVersion(
+                pair[0].version,
+                pair[0].file_name(),
+                pair[1].file_name(),
+            ));
+        }
+    }
+
+    Ok(files)
+}
+
+fn checksum(content: &str) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Row of `charybdis_migrations` table.
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: i32,
+    pub name: String,
+    /// Checksum of the file, or checksum of its executed statements if migration is not complete.
+    pub checksum: String,
+    pub applied_at: DateTime<Utc>,
+    pub applied_by: String,
+    /// Number of executed statements.
+    pub applied_statements: i32,
+    /// `false` if migration failed after some of its statements were executed.
+    pub complete: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum MigrationStatus {
+    Applied(MigrationFile, AppliedMigration),
+    Pending(MigrationFile),
+    /// Migration failed after some of its statements were executed. `up` continues with the remaining statements.
+    Partial(MigrationFile, AppliedMigration),
+    /// File content changed after it was applied.
+    ChecksumMismatch(MigrationFile, AppliedMigration),
+    /// Migration is recorded as applied, but its file is not present in migrations directory.
+    Missing(AppliedMigration),
+}
+
+impl Display for MigrationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrationStatus::Applied(file, applied) => write!(
+                f,
+                "{} {} (applied at {} by {})",
+                "applied ".bright_green(),
+                file.file_name().bright_yellow(),
+                applied.applied_at.to_rfc3339(),
+                applied.applied_by,
+            ),
+            MigrationStatus::Pending(file) => {
+                write!(f, "{} {}", "pending ".bright_cyan(), file.file_name().bright_yellow())
+            }
+            MigrationStatus::Partial(file, applied) => write!(
+                f,
+                "{} {} ({} of {} statements applied)",
+                "partial ".bright_red(),
+                file.file_name().bright_yellow(),
+                applied.applied_statements,
+                file.statements().len(),
+            ),
+            MigrationStatus::ChecksumMismatch(file, _) => write!(
+                f,
+                "{} {} (file changed after it was applied)",
+                "modified".bright_red(),
+                file.file_name().bright_yellow(),
+            ),
+            MigrationStatus::Missing(applied) => write!(
+                f,
+                "{} {:04}_{} (applied, but file is missing)",
+                "missing ".bright_red(),
+                applied.version,
+                applied.name,
+            ),
+        }
+    }
+}
+
+/// Versioned migration files and their history in the database.
+pub struct MigrationHistory<'a> {
+    session: &'a Session,
+    keyspace: String,
+    migrations_dir: PathBuf,
+    lock: MigrationLock<'a>,
+    format: OutputFormat,
+    schema_agreement_timeout: Duration,
+}
+
+impl<'a> MigrationHistory<'a> {
+    pub fn new(session: &'a Session, keyspace: String, migrations_dir: PathBuf) -> Self {
+        Self {
+            session,
+            lock: MigrationLock::new(session, &keyspace),
+            keyspace,
+            migrations_dir,
+            format: OutputFormat::default(),
+            schema_agreement_timeout: Duration::from_secs(DEFAULT_SCHEMA_AGREEMENT_TIMEOUT),
+        }
+    }
+
+    /// Output format of progress messages.
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self.lock = self.lock.format(format);
+        self
+    }
+
+    /// Time to wait for all nodes to agree on schema after each schema altering statement.
+    pub fn schema_agreement_timeout(mut self, schema_agreement_timeout: Duration) -> Self {
+        self.schema_agreement_timeout = schema_agreement_timeout;
+        self.lock = self.lock.schema_agreement_timeout(schema_agreement_timeout);
+        self
+    }
+
+    /// Time after which lock held while pending migrations are applied expires if it's not renewed.
+    pub fn lock_ttl(mut self, lock_ttl: Duration) -> Self {
+        self.lock = self.lock.ttl(lock_ttl);
+        self
+    }
+
+    /// Time to wait for lock held by another migrator before pending migrations are applied.
+    pub fn lock_timeout(mut self, lock_timeout: Duration) -> Self {
+        self.lock = self.lock.timeout(lock_timeout);
+        self
+    }
+
+    /// Removes migration lock regardless of its owner, e.g. after migrator was killed and lock shouldn't wait for
+    /// its TTL.
+    pub async fn unlock(&self) -> Result<(), MigrationError> {
+        self.lock.force_unlock().await
+    }
+
+    /// Migration files sorted by version.
+    pub fn files(&self) -> Result<Vec<MigrationFile>, MigrationError> {
+        read_migration_files(&self.migrations_dir)
+    }
+
+    pub async fn applied(&self) -> Result<Vec<AppliedMigration>, MigrationError> {
+        self.create_table_if_not_exists().await?;
+
+        let cql = format!(
+            "SELECT version, name, checksum, applied_at, applied_by, applied_statements, complete FROM {}.{}",
+            self.keyspace, MIGRATIONS_TABLE
+        );
+
+        let result = self
+            .session
+            .query_unpaged(cql.clone(), ())
+            .await
+            .map_err(|e| MigrationError::ExecutionError(cql.clone(), e))?
+            .into_rows_result()
+            .map_err(|e| MigrationError::IntoRowsResultError(cql.clone(), e))?;
+
+        let mut applied = vec![];
+
+        for row in result
+            .rows::<(i32, String, String, DateTime<Utc>, String, Option<i32>, Option<bool>)>()
+            .map_err(|e| MigrationError::RowsError(cql.clone(), e))?
+        {
+            let (version, name, checksum, applied_at, applied_by, applied_statements, complete) =
+                row.map_err(|e| MigrationError::DeserializationError(cql.clone(), e))?;
+
+            applied.push(AppliedMigration {
+                version,
+                name,
+                checksum,
+                applied_at,
+                applied_by,
+                applied_statements: applied_statements.unwrap_or_default(),
+                complete: complete.unwrap_or(true),
+            });
+        }
+
+        applied.sort_by_key(|migration| migration.version);
+
+        Ok(applied)
+    }
+
+    pub async fn status(&self) -> Result<Vec<MigrationStatus>, MigrationError> {
+        let files = self.files()?;
+        let mut applied: HashMap<i32, AppliedMigration> = self
+            .applied()
+            .await?
+            .into_iter()
+            .map(|migration| (migration.version, migration))
+            .collect();
+
+        let mut statuses = vec![];
+
+        for file in files {
+            match applied.remove(&file.version) {
+                Some(migration) if !migration.complete => statuses.push(MigrationStatus::Partial(file, migration)),
+                Some(migration) if migration.checksum == file.checksum => {
+                    statuses.push(MigrationStatus::Applied(file, migration));
+                }
+                Some(migration) => statuses.push(MigrationStatus::ChecksumMismatch(file, migration)),
+                None => statuses.push(MigrationStatus::Pending(file)),
+            }
+        }
+
+        let mut missing: Vec<AppliedMigration> = applied.into_values().collect();
+        missing.sort_by_key(|migration| migration.version);
+        statuses.extend(missing.into_iter().map(MigrationStatus::Missing));
+
+        Ok(statuses)
+    }
+
+    /// Writes plan as the next numbered migration file. Returns `None` if plan is empty.
+    pub fn generate(&self, plan: &MigrationPlan, name: &str) -> Result<Option<MigrationFile>, MigrationError> {
+        if plan.is_empty() {
+            return Ok(None);
+        }
+
+        let version = self.files()?.last().map_or(1, |file| file.version + 1);
+        let name = name.trim().replace([' ', '-'], "_").to_lowercase();
+        let path = self
+            .migrations_dir
+            .join(format!("{:04}_{}.{}", version, name, MIGRATION_FILE_EXTENSION));
+
+        let mut content = format!("-- Generated by charybdis-migrate at {}\n\n", Utc::now().to_rfc3339());
+        content.push_str(&plan.to_cql());
+
+        fs::create_dir_all(&self.migrations_dir)
+            .map_err(|e| MigrationError::MigrationFileError(self.migrations_dir.display().to_string(), e))?;
+        fs::write(&path, &content).map_err(|e| MigrationError::MigrationFileError(path.display().to_string(), e))?;
+
+        Ok(Some(MigrationFile {
+            version,
+            name,
+            checksum: checksum(&content),
+            path,
+            content,
+        }))
+    }
+
+    /// Applies pending migration files in version order while holding migration lock. Fails before executing
+    /// anything if any of the applied migration files were modified. Partially applied file is continued after its
+    /// last executed statement.
+    pub async fn up(&self) -> Result<Vec<MigrationFile>, MigrationError> {
+        self.lock.hold(self.apply_pending()).await
+    }
+
+    async fn apply_pending(&self) -> Result<Vec<MigrationFile>, MigrationError> {
+        let statuses = self.status().await?;
+        let mut pending = vec![];
+
+        for status in statuses {
+            match status {
+                MigrationStatus::ChecksumMismatch(file, _) => {
+                    return Err(MigrationError::ChecksumMismatch(file.file_name()));
+                }
+                MigrationStatus::Partial(file, applied) => {
+                    let applied_statements = file.applied_statements(&applied)?;
+                    pending.push((file, applied_statements));
+                }
+                MigrationStatus::Pending(file) => pending.push((file, 0)),
+                MigrationStatus::Applied(..) | MigrationStatus::Missing(_) => {}
+            }
+        }
+
+        for (file, applied_statements) in pending.iter() {
+            progress!(
+                self.format,
+                "\n{} {}",
+                "Applying migration".bright_cyan(),
+                file.file_name().bright_yellow()
+            );
+
+            let statements = file.statements();
+
+            for (index, statement) in statements.iter().enumerate().skip(*applied_statements) {
+                execute_cql(
+                    self.session,
+                    statement,
+                    self.format,
+                    self.schema_agreement_timeout,
+                    true,
+                )
+                .await?;
+
+                // progress is recorded, so failed file is continued from the next statement instead of running
+                // already executed statements again
+                if index + 1 < statements.len() {
+                    self.record_partial(file, &statements[..=index]).await?;
+                }
+            }
+
+            self.record(file, statements.len()).await?;
+        }
+
+        Ok(pending.into_iter().map(|(file, _)| file).collect())
+    }
+
+    /// Files that are not yet recorded as applied.
+    pub async fn pending(&self) -> Result<Vec<MigrationFile>, MigrationError> {
+        let pending = self
+            .status()
+            .await?
+            .into_iter()
+            .filter_map(|status| match status {
+                MigrationStatus::Pending(file) | MigrationStatus::Partial(file, _) => Some(file),
+                _ => None,
+            })
+            .collect();
+
+        Ok(pending)
+    }
+
+    async fn record(&self, file: &MigrationFile, statements: usize) -> Result<(), MigrationError> {
+        self.insert(file, &file.checksum, statements, true).await
+    }
+
+    async fn record_partial(&self, file: &MigrationFile, executed: &[String]) -> Result<(), MigrationError> {
+        self.insert(file, &statements_checksum(executed), executed.len(), false)
+            .await
+    }
+
+    async fn insert(
+        &self,
+        file: &MigrationFile,
+        checksum: &str,
+        applied_statements: usize,
+        complete: bool,
+    ) -> Result<(), MigrationError> {
+        let cql = format!(
+            "INSERT INTO {}.{} (version, name, checksum, applied_at, applied_by, applied_statements, complete) \
+            VALUES (?, ?, ?, ?, ?, ?, ?)",
+            self.keyspace, MIGRATIONS_TABLE
+        );
+
+        self.session
+            .query_unpaged(
+                cql.clone(),
+                (
+                    file.version,
+                    &file.name,
+                    checksum,
+                    Utc::now(),
+                    applied_by(),
+                    applied_statements as i32,
+                    complete,
+                ),
+            )
+            .await
+            .map_err(|e| MigrationError::ExecutionError(cql, e))?;
+
+        Ok(())
+    }
+
+    async fn create_table_if_not_exists(&self) -> Result<(), MigrationError> {
+        let cql = format!(
+            "CREATE TABLE IF NOT EXISTS {}.{} (
+                version int PRIMARY KEY,
+                name text,
+                checksum text,
+                applied_at timestamp,
+                applied_by text,
+                applied_statements int,
+                complete boolean
+            )",
+            self.keyspace, MIGRATIONS_TABLE
+        );
+
+        execute_cql(self.session, &cql, self.format, self.schema_agreement_timeout, false).await
+    }
+}
+
+pub(crate) fn applied_by() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrations_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("charybdis_migrations_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    fn migration_file(content: &str) -> MigrationFile {
+        MigrationFile {
+            version: 1,
+            name: "test".to_string(),
+            path: PathBuf::from("migrations/0001_test.cql"),
+            content: content.to_string(),
+            checksum: checksum(content),
+        }
+    }
+
+    fn applied(checksum: String, applied_statements: i32) -> AppliedMigration {
+        AppliedMigration {
+            version: 1,
+            name: "test".to_string(),
+            checksum,
+            applied_at: Utc::now(),
+            applied_by: "test".to_string(),
+            applied_statements,
+            complete: false,
+        }
+    }
+
+    #[test]
+    fn statements_are_split_by_semicolon() {
+        let file = migration_file(
+            "-- Generated by charybdis-migrate\n\nCREATE TABLE a (id int PRIMARY KEY);\nALTER TABLE a ADD b text; \
+            ALTER TABLE a ADD c text\n",
+        );
+
+        assert_eq!(
+            file.statements(),
+            vec![
+                "CREATE TABLE a (id int PRIMARY KEY)",
+                "ALTER TABLE a ADD b text",
+                "ALTER TABLE a ADD c text",
+            ]
+        );
+    }
+
+    #[test]
+    fn semicolons_in_literals_and_comments_are_ignored() {
+        let file = migration_file(
+            "ALTER TABLE a WITH comment = 'it''s; fine';\n\
+            CREATE FUNCTION f() RETURNS NULL ON NULL INPUT RETURNS int LANGUAGE lua AS $$ return 1; $$;\n\
+            /* block; comment */ ALTER TABLE \"b;c\" ADD d text; // trailing; comment\n\
+            -- line; comment\n",
+        );
+
+        assert_eq!(
+            file.statements(),
+            vec![
+                "ALTER TABLE a WITH comment = 'it''s; fine'",
+                "CREATE FUNCTION f() RETURNS NULL ON NULL INPUT RETURNS int LANGUAGE lua AS $$ return 1; $$",
+                "ALTER TABLE \"b;c\" ADD d text",
+            ]
+        );
+    }
+
+    #[test]
+    fn file_name_is_kept() {
+        let dir = migrations_dir("file_name");
+        fs::write(dir.join("3_add_posts.cql"), "ALTER TABLE a ADD b text;").unwrap();
+        fs::write(dir.join("README.md"), "").unwrap();
+        fs::write(dir.join("draft.cql"), "").unwrap();
+
+        let files = read_migration_files(&dir).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].version, 3);
+        assert_eq!(files[0].name, "add_posts");
+        assert_eq!(files[0].file_name(), "3_add_posts.cql");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn files_are_sorted_by_version() {
+        let dir = migrations_dir("sorted");
+        fs::write(dir.join("0010_c.cql"), "").unwrap();
+        fs::write(dir.join("0002_b.cql"), "").unwrap();
+        fs::write(dir.join("0001_a.cql"), "").unwrap();
+
+        let versions: Vec<i32> = read_migration_files(&dir)
+            .unwrap()
+            .iter()
+            .map(|file| file.version)
+            .collect();
+
+        assert_eq!(versions, vec![1, 2, 10]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn duplicate_versions_are_error() {
+        let dir = migrations_dir("duplicate");
+        fs::write(dir.join("0001_a.cql"), "").unwrap();
+        fs::write(dir.join("1_b.cql"), "").unwrap();
+
+        let result = read_migration_files(&dir);
+
+        assert!(matches!(
+            result,
+            Err(MigrationError::DuplicateMigrationVersion(1, _, _))
+        ));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn partial_migration_continues_after_applied_statements() {
+        let file = migration_file("ALTER TABLE a ADD b text;\nALTER TABLE a ADD c text;\nALTER TABLE a ADD d text;");
+        let executed = &file.statements()[..2];
+
+        let applied_statements = file.applied_statements(&applied(statements_checksum(executed), 2));
+
+        assert_eq!(applied_statements.unwrap(), 2);
+    }
+
+    #[test]
+    fn partial_migration_with_fixed_failed_statement() {
+        let applied_file = migration_file("ALTER TABLE a ADD b text;\nALTER TABLE a ADD c txt;");
+        let fixed_file = migration_file("ALTER TABLE a ADD b text;\nALTER TABLE a ADD c text;");
+        let executed = &applied_file.statements()[..1];
+
+        let applied_statements = fixed_file.applied_statements(&applied(statements_checksum(executed), 1));
+
+        assert_eq!(applied_statements.unwrap(), 1);
+    }
+
+    #[test]
+    fn partial_migration_with_modified_applied_statement() {
+        let applied_file = migration_file("ALTER TABLE a ADD b text;\nALTER TABLE a ADD c text;");
+        let modified_file = migration_file("ALTER TABLE a ADD e text;\nALTER TABLE a ADD c text;");
+        let executed = &applied_file.statements()[..1];
+
+        let result = modified_file.applied_statements(&applied(statements_checksum(executed), 1));
+
+        assert!(matches!(result, Err(MigrationError::ChecksumMismatch(_))));
+    }
+}
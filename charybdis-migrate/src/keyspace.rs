@@ -0,0 +1,272 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use colored::Colorize;
+use scylla::client::session::Session;
+
+use crate::args::DEFAULT_SCHEMA_AGREEMENT_TIMEOUT;
+use crate::config::{KeyspaceConfig, ReplicationConfig};
+use crate::errors::MigrationError;
+use crate::migration::execute_cql;
+use crate::report::OutputFormat;
+
+const REPLICATION_FACTOR: &str = "replication_factor";
+
+/// Replication and durable writes of existing keyspace, as stored in `system_schema.keyspaces`.
+struct CurrentKeyspace {
+    replication: HashMap<String, String>,
+    durable_writes: bool,
+}
+
+impl CurrentKeyspace {
+    /// Strategy class without package, e.g. `NetworkTopologyStrategy`.
+    fn class(&self) -> &str {
+        self.replication
+            .get("class")
+            .and_then(|class| class.rsplit('.').next())
+            .unwrap_or_default()
+    }
+
+    fn datacenters(&self) -> BTreeMap<String, String> {
+        self.replication
+            .iter()
+            .filter(|(key, _)| key.as_str() != "class")
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+/// Creates keyspace from `[keyspace]` configuration if it doesn't exist and alters it when replication or durable
+/// writes change.
+pub struct KeyspaceMigration<'a> {
+    session: &'a Session,
+    keyspace: &'a str,
+    config: &'a KeyspaceConfig,
+    format: OutputFormat,
+    schema_agreement_timeout: Duration,
+}
+
+impl<'a> KeyspaceMigration<'a> {
+    pub fn new(session: &'a Session, keyspace: &'a str, config: &'a KeyspaceConfig) -> Self {
+        Self {
+            session,
+            keyspace,
+            config,
+            format: OutputFormat::default(),
+            schema_agreement_timeout: Duration::from_secs(DEFAULT_SCHEMA_AGREEMENT_TIMEOUT),
+        }
+    }
+
+    /// Output format of progress messages.
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Time to wait for all nodes to agree on schema after each schema altering statement.
+    pub fn schema_agreement_timeout(mut self, schema_agreement_timeout: Duration) -> Self {
+        self.schema_agreement_timeout = schema_agreement_timeout;
+        self
+    }
+
+    /// Returns CQL statement required to bring keyspace in line with configuration, if any.
+    pub async fn plan(&self) -> Result<Option<String>, MigrationError> {
+        let cql = match self.current().await? {
+            None => {
+                progress!(
+                    self.format,
+                    "\n{} {}",
+                    "Detected missing keyspace".bright_cyan(),
+                    self.keyspace.bright_yellow()
+                );
+
+                self.create_cql()
+            }
+            Some(current) if self.is_changed(&current) => {
+                progress!(
+                    self.format,
+                    "\n{} {}",
+                    "Detected replication change in keyspace".bright_cyan(),
+                    self.keyspace.bright_yellow()
+                );
+
+                self.alter_cql(&current)
+            }
+            Some(_) => return Ok(None),
+        };
+
+        Ok(Some(cql))
+    }
+
+    pub async fn run(&self, dry_run: bool) -> Result<(), MigrationError> {
+        let cql = match self.plan().await? {
+            Some(cql) => cql,
+            None => return Ok(()),
+        };
+
+        if dry_run {
+            progress!(
+                self.format,
+                "\n{}\n",
+                "Keyspace migration (dry run):".on_bright_green().black()
+            );
+            progress!(self.format, "{};\n", cql.bright_purple());
+
+            return Ok(());
+        }
+
+        execute_cql(self.session, &cql, self.format, self.schema_agreement_timeout, true).await
+    }
+
+    /// Creates keyspace if it doesn't exist yet, e.g. before migration lock, whose table lives in the keyspace,
+    /// can be acquired. Returns whether keyspace was created.
+    pub async fn create_if_missing(&self) -> Result<bool, MigrationError> {
+        if self.current().await?.is_some() {
+            return Ok(false);
+        }
+
+        execute_cql(
+            self.session,
+            &self.create_cql(),
+            self.format,
+            self.schema_agreement_timeout,
+            true,
+        )
+        .await?;
+
+        Ok(true)
+    }
+
+    async fn current(&self) -> Result<Option<CurrentKeyspace>, MigrationError> {
+        let cql = "SELECT replication, durable_writes FROM system_schema.keyspaces WHERE keyspace_name = ?";
+
+        let result = self
+            .session
+            .query_unpaged(cql, (self.keyspace,))
+            .await
+            .map_err(|e| MigrationError::ExecutionError(cql.to_string(), e))?
+            .into_rows_result()
+            .map_err(|e| MigrationError::IntoRowsResultError(cql.to_string(), e))?;
+
+        let row = result
+            .maybe_first_row::<(HashMap<String, String>, bool)>()
+            .map_err(|e| MigrationError::MaybeFirstRowError(cql.to_string(), e))?;
+
+        Ok(row.map(|(replication, durable_writes)| CurrentKeyspace {
+            replication,
+            durable_writes,
+        }))
+    }
+
+    fn is_changed(&self, current: &CurrentKeyspace) -> bool {
+        if self.config.durable_writes.unwrap_or(true) != current.durable_writes {
+            return true;
+        }
+
+        match &self.config.replication {
+            ReplicationConfig::SimpleStrategy { replication_factor } => {
+                current.class() != "SimpleStrategy"
+                    || current.replication.get(REPLICATION_FACTOR) != Some(&replication_factor.to_string())
+            }
+            ReplicationConfig::NetworkTopologyStrategy { .. } => {
+                current.class() != "NetworkTopologyStrategy"
+                    || self.expected_datacenters(current) != current.datacenters()
+            }
+        }
+    }
+
+    /// Datacenters with their replication factor expected in existing keyspace. Datacenters that are not configured
+    /// fall back to `replication_factor` if set, otherwise they are expected to be removed.
+    fn expected_datacenters(&self, current: &CurrentKeyspace) -> BTreeMap<String, String> {
+        let datacenters = match &self.config.replication {
+            ReplicationConfig::NetworkTopologyStrategy { datacenters } => datacenters,
+            ReplicationConfig::SimpleStrategy { .. } => return BTreeMap::new(),
+        };
+
+        let mut expected: BTreeMap<String, String> = datacenters
+            .iter()
+            .filter(|(datacenter, _)| datacenter.as_str() != REPLICATION_FACTOR)
+            .map(|(datacenter, factor)| (datacenter.clone(), factor.to_string()))
+            .collect();
+
+        if let Some(replication_factor) = datacenters.get(REPLICATION_FACTOR) {
+            for datacenter in current.datacenters().into_keys() {
+                expected
+                    .entry(datacenter)
+                    .or_insert_with(|| replication_factor.to_string());
+            }
+        }
+
+        expected
+    }
+
+    fn replication_clause(&self, removed_datacenters: &[String]) -> String {
+        let mut options = vec![];
+
+        match &self.config.replication {
+            ReplicationConfig::SimpleStrategy { replication_factor } => {
+                options.push("'class': 'SimpleStrategy'".to_string());
+                options.push(format!("'{}': {}", REPLICATION_FACTOR, replication_factor));
+            }
+            ReplicationConfig::NetworkTopologyStrategy { datacenters } => {
+                options.push("'class': 'NetworkTopologyStrategy'".to_string());
+
+                for (datacenter, factor) in datacenters {
+                    options.push(format!("'{}': {}", datacenter, factor));
+                }
+
+                for datacenter in removed_datacenters {
+                    options.push(format!("'{}': 0", datacenter));
+                }
+            }
+        }
+
+        format!("{{{}}}", options.join(", "))
+    }
+
+    fn create_cql(&self) -> String {
+        let mut cql = format!(
+            "CREATE KEYSPACE IF NOT EXISTS {} WITH replication = {}",
+            self.keyspace,
+            self.replication_clause(&[])
+        );
+
+        if let Some(durable_writes) = self.config.durable_writes {
+            cql.push_str(&format!(" AND durable_writes = {}", durable_writes));
+        }
+
+        if let Some(tablets) = &self.config.tablets {
+            match tablets.initial {
+                Some(initial) => cql.push_str(&format!(
+                    " AND tablets = {{'enabled': {}, 'initial': {}}}",
+                    tablets.enabled, initial
+                )),
+                None => cql.push_str(&format!(" AND tablets = {{'enabled': {}}}", tablets.enabled)),
+            }
+        }
+
+        cql
+    }
+
+    fn alter_cql(&self, current: &CurrentKeyspace) -> String {
+        let removed_datacenters = match &self.config.replication {
+            ReplicationConfig::NetworkTopologyStrategy { datacenters }
+                if !datacenters.contains_key(REPLICATION_FACTOR) && current.class() == "NetworkTopologyStrategy" =>
+            {
+                current
+                    .datacenters()
+                    .into_keys()
+                    .filter(|datacenter| !datacenters.contains_key(datacenter))
+                    .collect()
+            }
+            _ => vec![],
+        };
+
+        format!(
+            "ALTER KEYSPACE {} WITH replication = {} AND durable_writes = {}",
+            self.keyspace,
+            self.replication_clause(&removed_datacenters),
+            self.config.durable_writes.unwrap_or(true)
+        )
+    }
+}
@@ -1,16 +1,48 @@
 #![doc = include_str!("../README.md")]
+#![allow(clippy::result_large_err)]
 
 use crate::args::Args;
+use crate::config::Config;
+use crate::errors::MigrationError;
+use crate::history::{MigrationHistory, MIGRATIONS_TABLE};
+use crate::lock::LOCK_TABLE;
 use crate::migration::Migration;
-use charybdis_parser::schema::code_schema::CodeSchema;
+use crate::modelize::Modelizer;
+use crate::plan::MigrationPlan;
+use crate::report::OutputFormat;
+use charybdis_parser::schema::code_schema::{CodeSchema, ModelDiscovery};
 use charybdis_parser::schema::db_schema::DbSchema;
+use charybdis_parser::schema::diff::SchemaDiff;
+use colored::Colorize;
 use scylla::client::session::Session;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Prints progress message in given output format. It goes to stderr when stdout is reserved for `--format json`
+/// output.
+macro_rules! progress {
+    ($format:expr, $($arg:tt)*) => {
+        $crate::report::OutputFormat::progress($format, format_args!($($arg)*))
+    };
+}
 
 pub mod args;
+pub mod config;
+pub(crate) mod dependency;
+pub mod errors;
+pub mod history;
+pub mod keyspace;
+pub mod lock;
 pub mod migration;
 pub(crate) mod model;
+pub mod modelize;
+pub mod plan;
+pub mod report;
 pub mod session;
 
+/// Tables managed by charybdis-migrate itself. They are never modelized or dropped as removed models.
+pub(crate) const INTERNAL_TABLES: [&str; 2] = [MIGRATIONS_TABLE, LOCK_TABLE];
+
 pub struct MigrationBuilder {
     pub(crate) args: Args,
 }
@@ -26,26 +58,119 @@ impl MigrationBuilder {
         Self { args: Args::default() }
     }
 
-    pub async fn build(mut self, session: &Session) -> Migration {
+    pub async fn build(mut self, session: &Session) -> Result<Migration<'_>, MigrationError> {
+        let config = self.resolve_args(session)?;
+
+        let current_db_schema = DbSchema::new(session, self.args.keyspace.clone()).await?;
+        let current_code_schema: CodeSchema = match &self.args.code_schema_override_json {
+            Some(json) => serde_json::from_str(json)?,
+            None => self.code_schema()?,
+        };
+
+        let mut migration = Migration::new(current_db_schema, current_code_schema, session, self.args);
+        migration.keyspace_config = config.keyspace;
+
+        Ok(migration)
+    }
+
+    /// Builds migration and returns its plan without executing it.
+    pub async fn plan(self, session: &Session) -> Result<MigrationPlan, MigrationError> {
+        self.build(session).await?.plan()
+    }
+
+    /// Versioned migration files and their history, without computing schema diff.
+    pub fn history(mut self, session: &Session) -> Result<MigrationHistory<'_>, MigrationError> {
+        self.resolve_args(session)?;
+
+        Ok(
+            MigrationHistory::new(session, self.args.keyspace.clone(), self.args.migrations_path())
+                .lock_ttl(Duration::from_secs(self.args.lock_ttl))
+                .lock_timeout(Duration::from_secs(self.args.lock_timeout))
+                .format(self.args.format)
+                .schema_agreement_timeout(self.args.schema_agreement_timeout()),
+        )
+    }
+
+    /// Generates model files from existing keyspace into `models_dir`, relative to the current directory.
+    pub async fn modelize(
+        mut self,
+        session: &Session,
+        models_dir: &str,
+        overwrite: bool,
+    ) -> Result<Vec<PathBuf>, MigrationError> {
+        self.resolve_args(session)?;
+
+        let db_schema = DbSchema::new(session, self.args.keyspace.clone()).await?;
+        let models_dir = Path::new(&self.args.current_dir).join(models_dir);
+
+        Modelizer::new(&db_schema)
+            .format(self.args.format)
+            .write(&models_dir, overwrite)
+    }
+
+    /// Compares schema JSON snapshot `from` with schema JSON `to`, or with schema parsed from models in the current
+    /// directory if `to` is not provided. Doesn't require database connection.
+    pub fn diff(&self, from: &Path, to: Option<&Path>) -> Result<SchemaDiff, MigrationError> {
+        let from = read_schema_json(from)?;
+
+        match to {
+            Some(to) => Ok(SchemaDiff::new(&from, &read_schema_json(to)?)),
+            None => Ok(SchemaDiff::new(&from, &self.code_schema()?)),
+        }
+    }
+
+    /// Parses models in scope given by arguments and `models` section of the configuration file. Paths and
+    /// features given by arguments take precedence, while excluded paths are combined. Files that couldn't be parsed
+    /// are reported in requested output format.
+    fn code_schema(&self) -> Result<CodeSchema, MigrationError> {
+        let models_config = match &self.args.resolved_config {
+            Some(config) => config.models.clone(),
+            None => Config::load_profile(&self.args.config_path(), self.args.profile().as_deref())?.models,
+        }
+        .unwrap_or_default();
+
+        let models_paths = if self.args.models_path.is_empty() {
+            models_config.paths
+        } else {
+            self.args.models_path.clone()
+        };
+        let mut exclude = models_config.exclude;
+        exclude.extend(self.args.exclude.iter().cloned());
+
+        let discovery = ModelDiscovery {
+            models_paths,
+            exclude,
+            features: self.args.features.clone().or(models_config.features),
+        };
+
+        let code_schema = CodeSchema::discover(&self.args.current_dir, discovery)?;
+
+        for skipped_file in code_schema.skipped_files() {
+            progress!(
+                self.args.format,
+                "{} {}",
+                "Skipping file.".bright_yellow().bold(),
+                skipped_file.error.to_string().bright_yellow()
+            );
+        }
+
+        Ok(code_schema)
+    }
+
+    /// Fills arguments that are not set from environment variables and configuration file and returns the
+    /// configuration. If keyspace is not configured either, keyspace of the session is used.
+    fn resolve_args(&mut self, session: &Session) -> Result<Config, MigrationError> {
+        let config = self.args.apply_config()?;
+
         if self.args.keyspace.is_empty() {
             // try to get the keyspace from the session
             self.args.keyspace = session
                 .get_keyspace()
-                .expect("No keyspace provided and no default keyspace set")
+                .ok_or(MigrationError::KeyspaceNotSet)?
                 .to_string();
         }
 
-        let current_db_schema = DbSchema::new(session, self.args.keyspace.clone()).await;
-        let current_code_schema: CodeSchema = self
-            .args
-            .code_schema_override_json
-            .as_ref()
-            .map(|json| serde_json::from_str(json).unwrap())
-            .unwrap_or_else(|| CodeSchema::new(&self.args.current_dir));
-
-        let migration = Migration::new(current_db_schema, current_code_schema, session, self.args);
-
-        migration
+        Ok(config)
     }
 
     pub fn keyspace(mut self, keyspace: String) -> Self {
@@ -58,11 +183,120 @@ impl MigrationBuilder {
         self
     }
 
+    /// Configuration file relative to the current directory, `charybdis.toml` by default.
+    pub fn config(mut self, config: String) -> Self {
+        self.args.config = config;
+        self
+    }
+
+    /// Configuration profile, e.g. `prod`, that overrides top level configuration.
+    pub fn profile(mut self, profile: String) -> Self {
+        self.args.profile = Some(profile);
+        self
+    }
+
     pub fn drop_and_replace(mut self, drop_and_replace: bool) -> Self {
         self.args.drop_and_replace = drop_and_replace;
         self
     }
 
+    pub fn recreate_views(mut self, recreate_views: bool) -> Self {
+        self.args.recreate_views = recreate_views;
+        self
+    }
+
+    pub fn rebuild_tables(mut self, rebuild_tables: bool) -> Self {
+        self.args.rebuild_tables = rebuild_tables;
+        self
+    }
+
+    pub fn swap_rebuilt_tables(mut self, swap_rebuilt_tables: bool) -> Self {
+        self.args.swap_rebuilt_tables = swap_rebuilt_tables;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.args.dry_run = dry_run;
+        self
+    }
+
+    pub fn migrations_dir(mut self, migrations_dir: String) -> Self {
+        self.args.migrations_dir = migrations_dir;
+        self
+    }
+
+    /// Drops tables, UDTs and materialized views that are present in database but not in code. Only objects
+    /// listed in `allow_drop` are dropped, others are reported and skipped.
+    pub fn drop_removed_models(mut self, drop_removed_models: bool) -> Self {
+        self.args.drop_removed_models = drop_removed_models;
+        self
+    }
+
+    /// Names of removed models, and `table.column` or `table.index` names of removed columns and indexes that can
+    /// be dropped.
+    pub fn allow_drop(mut self, allow_drop: Vec<String>) -> Self {
+        self.args.allow_drop = allow_drop;
+        self
+    }
+
+    /// Drops all columns and indexes that are no longer defined in code. Otherwise, only drops listed in
+    /// `allow_drop` are executed, while others are reported as blocked steps.
+    pub fn allow_destructive(mut self, allow_destructive: bool) -> Self {
+        self.args.allow_destructive = allow_destructive;
+        self
+    }
+
+    /// Time after which migration lock expires if it's not renewed, e.g. because migrator crashed.
+    pub fn lock_ttl(mut self, lock_ttl: Duration) -> Self {
+        self.args.lock_ttl = lock_ttl.as_secs();
+        self
+    }
+
+    /// Time to wait for migration lock held by another migrator.
+    pub fn lock_timeout(mut self, lock_timeout: Duration) -> Self {
+        self.args.lock_timeout = lock_timeout.as_secs();
+        self
+    }
+
+    /// Time to wait for all nodes to agree on schema after each schema altering statement, 60 seconds by default.
+    /// It applies to given session as well, as agreement is awaited by migration itself.
+    pub fn schema_agreement_timeout(mut self, schema_agreement_timeout: Duration) -> Self {
+        self.args.schema_agreement_timeout = Some(schema_agreement_timeout.as_secs());
+        self
+    }
+
+    /// Index class used to create vector indexes, `StorageAttachedIndex` by default.
+    pub fn vector_index_class(mut self, vector_index_class: &str) -> Self {
+        self.args.vector_index_class = vector_index_class.to_string();
+        self
+    }
+
+    /// Files or directories to parse models from, as globs relative to the current directory. All `.rs` files are
+    /// parsed if empty.
+    pub fn models_path(mut self, models_path: Vec<String>) -> Self {
+        self.args.models_path = models_path;
+        self
+    }
+
+    /// Files or directories to skip when parsing models, as globs relative to the current directory.
+    pub fn exclude(mut self, exclude: Vec<String>) -> Self {
+        self.args.exclude = exclude;
+        self
+    }
+
+    /// Enabled features that `cfg(feature = "...")` gates of models are evaluated with. Without it, gated models are
+    /// always included.
+    pub fn features(mut self, features: Vec<String>) -> Self {
+        self.args.features = Some(features);
+        self
+    }
+
+    /// Output format of progress messages. With `OutputFormat::Json`, they are printed to stderr.
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.args.format = format;
+        self
+    }
+
     pub fn verbose(mut self, verbose: bool) -> Self {
         self.args.verbose = verbose;
         self
@@ -74,8 +308,101 @@ impl MigrationBuilder {
     }
 }
 
+/// Reads schema JSON written by `DbSchema::write_schema_to_json`. Serialized `CodeSchema` is read as well, as it
+/// only lacks keyspace name. Internal tables are skipped, as they are not defined in code.
+fn read_schema_json(path: &Path) -> Result<DbSchema, MigrationError> {
+    let json =
+        std::fs::read_to_string(path).map_err(|e| MigrationError::SchemaFileError(path.display().to_string(), e))?;
+    let mut schema: DbSchema = serde_json::from_str(&json)?;
+
+    schema
+        .tables
+        .retain(|name, _| !INTERNAL_TABLES.contains(&name.as_str()));
+
+    Ok(schema)
+}
+
 impl From<Args> for MigrationBuilder {
     fn from(args: Args) -> Self {
         Self { args }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use charybdis_parser::schema::SchemaObject;
+    use std::collections::HashMap;
+
+    fn write_json(dir: &Path, file_name: &str, json: String) -> PathBuf {
+        let path = dir.join(file_name);
+        std::fs::write(&path, json).unwrap();
+
+        path
+    }
+
+    fn table(fields: &[&str]) -> SchemaObject {
+        SchemaObject {
+            fields: fields
+                .iter()
+                .map(|name| (name.to_string(), "text".to_string(), false))
+                .collect(),
+            field_names: fields.iter().map(|name| name.to_string()).collect(),
+            types_by_name: fields
+                .iter()
+                .map(|name| (name.to_string(), "text".to_string()))
+                .collect(),
+            partition_keys: vec!["id".to_string()],
+            ..SchemaObject::default()
+        }
+    }
+
+    fn db_schema(tables: HashMap<String, SchemaObject>) -> DbSchema {
+        DbSchema {
+            tables,
+            udts: HashMap::new(),
+            materialized_views: HashMap::new(),
+            keyspace_name: "test_keyspace".to_string(),
+        }
+    }
+
+    #[test]
+    fn diff_of_db_schema_snapshots() {
+        let dir = std::env::temp_dir().join(format!("charybdis_schema_json_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let from = db_schema(HashMap::from([
+            ("posts".to_string(), table(&["id", "title"])),
+            (MIGRATIONS_TABLE.to_string(), table(&["id"])),
+        ]));
+        let to = db_schema(HashMap::from([("posts".to_string(), table(&["id", "title", "bio"]))]));
+        let from = write_json(&dir, "from.json", from.get_current_schema_as_json().unwrap());
+        let to = write_json(&dir, "to.json", to.get_current_schema_as_json().unwrap());
+
+        let diff = MigrationBuilder::new().diff(&from, Some(&to)).unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // internal table is not reported as dropped
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].object_name, "posts");
+    }
+
+    #[test]
+    fn read_code_schema_json() {
+        let dir = std::env::temp_dir().join(format!("charybdis_code_schema_json_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut code_schema = CodeSchema::default();
+        code_schema.tables.insert("posts".to_string(), table(&["id"]));
+        let path = write_json(&dir, "code.json", serde_json::to_string(&code_schema).unwrap());
+
+        let schema = read_schema_json(&path);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let schema = schema.unwrap();
+        assert!(schema.tables.contains_key("posts"));
+        assert!(schema.keyspace_name.is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use colored::Colorize;
+use scylla::client::session::Session;
+use scylla::serialize::row::SerializeRow;
+use scylla::value::{CqlValue, Row};
+
+use crate::args::DEFAULT_SCHEMA_AGREEMENT_TIMEOUT;
+use crate::errors::MigrationError;
+use crate::history::applied_by;
+use crate::migration::execute_cql;
+use crate::report::OutputFormat;
+
+/// Table in the migrated keyspace that holds the lock row while migration is running.
+pub const LOCK_TABLE: &str = "charybdis_migration_lock";
+
+const LOCK_ID: &str = "migration";
+const ACQUIRE_INTERVAL: Duration = Duration::from_secs(1);
+
+pub const DEFAULT_LOCK_TTL: Duration = Duration::from_secs(60);
+pub const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Distributed lock that prevents concurrent migrators from racing to issue the same statements, e.g. when
+/// migrations run at application startup of multiple pods.
+///
+/// Lock is a single LWT row with TTL, so lock of crashed migrator expires on its own. While lock is held, it's
+/// renewed every third of its TTL.
+pub struct MigrationLock<'a> {
+    session: &'a Session,
+    keyspace: String,
+    owner: String,
+    ttl: Duration,
+    timeout: Duration,
+    format: OutputFormat,
+    schema_agreement_timeout: Duration,
+}
+
+impl<'a> MigrationLock<'a> {
+    pub fn new(session: &'a Session, keyspace: &str) -> Self {
+        Self {
+            session,
+            keyspace: keyspace.to_string(),
+            owner: owner_id(),
+            ttl: DEFAULT_LOCK_TTL,
+            timeout: DEFAULT_LOCK_TIMEOUT,
+            format: OutputFormat::default(),
+            schema_agreement_timeout: Duration::from_secs(DEFAULT_SCHEMA_AGREEMENT_TIMEOUT),
+        }
+    }
+
+    /// Time after which lock expires if it's not renewed, e.g. because migrator crashed.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Time to wait for lock held by another migrator to be released.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Output format of progress messages.
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Time to wait for all nodes to agree on schema after each schema altering statement.
+    pub fn schema_agreement_timeout(mut self, schema_agreement_timeout: Duration) -> Self {
+        self.schema_agreement_timeout = schema_agreement_timeout;
+        self
+    }
+
+    /// Acquires lock, runs given future while renewing the lock and releases it afterwards. If lock is lost, e.g.
+    /// it expired and another migrator acquired it, future is dropped and `MigrationError::LockLost` is returned.
+    pub async fn hold<T>(&self, future: impl Future<Output = Result<T, MigrationError>>) -> Result<T, MigrationError> {
+        self.acquire().await?;
+
+        let result = self.renew_while(future).await;
+
+        if let Err(e) = self.release().await {
+            progress!(
+                self.format,
+                "{} {}",
+                "Failed to release migration lock, it will expire on its own:".bright_yellow(),
+                e
+            );
+        }
+
+        result
+    }
+
+    /// Removes lock regardless of its owner, e.g. after migrator was killed and lock shouldn't wait for TTL.
+    pub async fn force_unlock(&self) -> Result<(), MigrationError> {
+        self.create_table_if_not_exists().await?;
+
+        let cql = format!("DELETE FROM {}.{} WHERE id = ?", self.keyspace, LOCK_TABLE);
+
+        self.session
+            .query_unpaged(cql.clone(), (LOCK_ID,))
+            .await
+            .map_err(|e| MigrationError::ExecutionError(cql, e))?;
+
+        Ok(())
+    }
+
+    async fn acquire(&self) -> Result<(), MigrationError> {
+        self.create_table_if_not_exists().await?;
+
+        let cql = format!(
+            "INSERT INTO {}.{} (id, owner, renewed_at) VALUES (?, ?, ?) IF NOT EXISTS USING TTL ?",
+            self.keyspace, LOCK_TABLE
+        );
+        let started = Instant::now();
+        let mut reported_owner = None;
+
+        loop {
+            let (applied, owner) = self
+                .conditional(&cql, (LOCK_ID, &self.owner, Utc::now(), self.ttl_seconds()))
+                .await?;
+
+            if applied {
+                return Ok(());
+            }
+
+            let owner = owner.unwrap_or_default();
+
+            if started.elapsed() >= self.timeout {
+                return Err(MigrationError::LockTimeout(owner));
+            }
+
+            if reported_owner.as_ref() != Some(&owner) {
+                progress!(
+                    self.format,
+                    "{} {}",
+                    "Waiting for migration lock held by".bright_cyan(),
+                    owner.bright_yellow()
+                );
+
+                reported_owner = Some(owner);
+            }
+
+            tokio::time::sleep(ACQUIRE_INTERVAL).await;
+        }
+    }
+
+    async fn renew_while<T>(
+        &self,
+        future: impl Future<Output = Result<T, MigrationError>>,
+    ) -> Result<T, MigrationError> {
+        let mut heartbeat = tokio::time::interval((self.ttl / 3).max(Duration::from_secs(1)));
+        heartbeat.tick().await;
+
+        tokio::pin!(future);
+
+        loop {
+            tokio::select! {
+                result = &mut future => return result,
+                _ = heartbeat.tick() => self.renew().await?,
+            }
+        }
+    }
+
+    async fn renew(&self) -> Result<(), MigrationError> {
+        let cql = format!(
+            "UPDATE {}.{} USING TTL ? SET owner = ?, renewed_at = ? WHERE id = ? IF owner = ?",
+            self.keyspace, LOCK_TABLE
+        );
+
+        match self
+            .conditional(
+                &cql,
+                (self.ttl_seconds(), &self.owner, Utc::now(), LOCK_ID, &self.owner),
+            )
+            .await
+        {
+            Ok((true, _)) => Ok(()),
+            Ok((false, owner)) => Err(MigrationError::LockLost(owner.unwrap_or_default())),
+            // lock is still valid until its TTL expires, so renewal is retried on next heartbeat
+            Err(e) => {
+                progress!(
+                    self.format,
+                    "{} {}",
+                    "Failed to renew migration lock:".bright_yellow(),
+                    e
+                );
+
+                Ok(())
+            }
+        }
+    }
+
+    async fn release(&self) -> Result<(), MigrationError> {
+        let cql = format!("DELETE FROM {}.{} WHERE id = ? IF owner = ?", self.keyspace, LOCK_TABLE);
+
+        self.conditional(&cql, (LOCK_ID, &self.owner)).await?;
+
+        Ok(())
+    }
+
+    /// Executes LWT statement, returning whether it was applied and current lock owner if it wasn't.
+    async fn conditional(
+        &self,
+        cql: &str,
+        values: impl SerializeRow,
+    ) -> Result<(bool, Option<String>), MigrationError> {
+        let result = self
+            .session
+            .query_unpaged(cql, values)
+            .await
+            .map_err(|e| MigrationError::ExecutionError(cql.to_string(), e))?
+            .into_rows_result()
+            .map_err(|e| MigrationError::IntoRowsResultError(cql.to_string(), e))?;
+
+        let owner_index = result.column_specs().iter().position(|spec| spec.name() == "owner");
+        let row = result
+            .maybe_first_row::<Row>()
+            .map_err(|e| MigrationError::MaybeFirstRowError(cql.to_string(), e))?;
+
+        let columns = row.map(|row| row.columns).unwrap_or_default();
+        let applied = matches!(columns.first(), Some(Some(CqlValue::Boolean(true))));
+        let owner = owner_index
+            .and_then(|index| columns.get(index).cloned().flatten())
+            .and_then(|owner| owner.into_string());
+
+        Ok((applied, owner))
+    }
+
+    async fn create_table_if_not_exists(&self) -> Result<(), MigrationError> {
+        let cql = format!(
+            "CREATE TABLE IF NOT EXISTS {}.{} (
+                id text PRIMARY KEY,
+                owner text,
+                renewed_at timestamp
+            )",
+            self.keyspace, LOCK_TABLE
+        );
+
+        execute_cql(self.session, &cql, self.format, self.schema_agreement_timeout, false).await
+    }
+
+    fn ttl_seconds(&self) -> i32 {
+        self.ttl.as_secs().max(1) as i32
+    }
+}
+
+/// Identifies migrator process, e.g. `deploy@api-7d9f8c-x2k4p:1:1718000000000000000`.
+fn owner_id() -> String {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "unknown".to_string());
+    let started_at = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+
+    format!("{}@{}:{}:{}", applied_by(), host, std::process::id(), started_at)
+}
@@ -1,9 +1,19 @@
+#![allow(clippy::result_large_err)]
+
 use clap::Parser;
-use migrate::args::Args;
+use colored::Colorize;
+use migrate::args::{Args, Command};
+use migrate::errors::MigrationError;
+use migrate::migration::Migration;
+use migrate::plan::StepKind;
+use migrate::report::{ErrorReport, MigrationFileReport, OutputFormat};
 use migrate::session::initialize_session;
 use migrate::MigrationBuilder;
 use scylla::client::session::Session;
+use serde::Serialize;
 use std::env;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
 
 /// Automatic Migration Tool
 #[tokio::main]
@@ -12,10 +22,253 @@ async fn main() {
         colored::control::set_override(true);
     }
 
-    let args = Args::parse();
-    let session: Session = initialize_session(&args).await;
-    let migration = MigrationBuilder::from(args).build(&session).await;
+    let mut args = Args::parse();
+    let format = args.format;
+
+    if let Err(e) = args.apply_config() {
+        exit_with_error(e, format);
+    }
+
+    if let Some(command) = &args.command {
+        if !command.requires_session() {
+            if let Err(e) = run_offline(args) {
+                exit_with_error(e, format);
+            }
+
+            return;
+        }
+    }
+
+    if args.host.is_empty() || args.keyspace.is_empty() {
+        exit_with_error(MigrationError::ConnectionNotConfigured, format);
+    }
+
+    let session: Session = match initialize_session(&args).await {
+        Ok(session) => session,
+        Err(e) => exit_with_error(e, format),
+    };
+
+    if let Err(e) = run(args, &session).await {
+        exit_with_error(e, format);
+    }
+}
+
+/// Prints error and exits. With `--format json`, error is printed to stdout as JSON as well, unless report of failed
+/// migration was already printed.
+fn exit_with_error(e: MigrationError, format: OutputFormat) -> ! {
+    if format == OutputFormat::Json && !matches!(e, MigrationError::MigrationFailed(_, _)) {
+        let _ = print_json(&ErrorReport::new(&e));
+    }
+
+    eprintln!("\n{}\n", e);
+    std::process::exit(1);
+}
+
+fn print_json(value: &impl Serialize) -> Result<(), MigrationError> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+
+    Ok(())
+}
+
+/// Commands that don't require database connection.
+fn run_offline(args: Args) -> Result<(), MigrationError> {
+    if let Some(Command::Diff { from, to, allow_unsafe }) = args.command.clone() {
+        let format = args.format;
+        let diff = MigrationBuilder::from(args).diff(Path::new(&from), to.as_deref().map(Path::new))?;
+
+        match format {
+            OutputFormat::Text => print!("{}", diff),
+            OutputFormat::Json => print_json(&diff)?,
+        }
+
+        let unsafe_changes = diff.unsafe_changes().len();
+
+        if unsafe_changes > 0 && !allow_unsafe {
+            return Err(MigrationError::UnsafeSchemaChanges(unsafe_changes));
+        }
+    }
+
+    Ok(())
+}
+
+async fn run(args: Args, session: &Session) -> Result<(), MigrationError> {
+    let format = args.format;
+
+    match args.command.clone() {
+        None => {
+            let dry_run = args.dry_run;
+            let drop_removed_models = args.drop_removed_models;
+            let allow_destructive = args.allow_destructive;
+            let mut migration = MigrationBuilder::from(args).build(session).await?;
+
+            if drop_removed_models && !dry_run {
+                confirm_drops(&mut migration, format)?;
+            }
+
+            if !allow_destructive && !dry_run {
+                confirm_destructive_drops(&mut migration, format)?;
+            }
+
+            let report = match migration.run().await {
+                Ok(report) => report,
+                Err(MigrationError::MigrationFailed(report, e)) if format == OutputFormat::Json => {
+                    print_json(&report)?;
+
+                    return Err(MigrationError::MigrationFailed(report, e));
+                }
+                Err(e) => return Err(e),
+            };
+
+            if format == OutputFormat::Json {
+                print_json(&report)?;
+            }
+
+            if !dry_run {
+                migration.write_schema_to_json().await?;
+            }
+        }
+        Some(Command::Generate { name }) => {
+            let migration = MigrationBuilder::from(args).build(session).await?;
+            let file = migration.generate(&name).await?;
+
+            match (format, file) {
+                (OutputFormat::Json, file) => {
+                    print_json(&file.map(|file| file.path.display().to_string()))?;
+                }
+                (OutputFormat::Text, Some(file)) => println!(
+                    "\n{} {}",
+                    "Generated migration file:".bright_green(),
+                    file.path.display().to_string().bright_yellow()
+                ),
+                (OutputFormat::Text, None) => println!("\n{}", "No changes detected.".green()),
+            }
+        }
+        Some(Command::Up) => {
+            let history = MigrationBuilder::from(args).history(session)?;
+            let applied = history.up().await?;
+
+            if format == OutputFormat::Json {
+                print_json(&applied.iter().map(MigrationFileReport::applied).collect::<Vec<_>>())?;
+            } else if applied.is_empty() {
+                println!("\n{}", "No pending migrations.".green());
+            } else {
+                println!(
+                    "\n{} {}",
+                    "Applied migrations:".bright_green(),
+                    applied.len().to_string().bright_yellow()
+                );
+            }
+        }
+        Some(Command::Modelize { models_dir, overwrite }) => {
+            let written = MigrationBuilder::from(args)
+                .modelize(session, &models_dir, overwrite)
+                .await?;
+
+            if format == OutputFormat::Json {
+                print_json(&written)?;
+            } else {
+                for path in written {
+                    println!(
+                        "{} {}",
+                        "Generated model file:".bright_green(),
+                        path.display().to_string().bright_yellow()
+                    );
+                }
+            }
+        }
+        Some(Command::Diff { .. }) => run_offline(args)?,
+        Some(Command::Unlock) => {
+            MigrationBuilder::from(args).history(session)?.unlock().await?;
+
+            if format == OutputFormat::Json {
+                print_json(&serde_json::json!({ "unlocked": true }))?;
+            } else {
+                println!("\n{}", "Migration lock removed.".bright_green());
+            }
+        }
+        Some(Command::Status) => {
+            let history = MigrationBuilder::from(args).history(session)?;
+            let statuses = history.status().await?;
+
+            if format == OutputFormat::Json {
+                print_json(&statuses.iter().map(MigrationFileReport::from).collect::<Vec<_>>())?;
+            } else {
+                for status in statuses {
+                    println!("{}", status);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Asks user to confirm drop of each removed column and index that is not allowed with `--allow-destructive` or
+/// `--allow-drop` by typing its `table.name`. UDT fields can't be dropped, so their removal fails planning instead.
+fn confirm_destructive_drops(migration: &mut Migration, format: OutputFormat) -> Result<(), MigrationError> {
+    for (name, kind) in migration.blocked_drops()? {
+        // dropped index can be rebuilt, while dropped column data is lost
+        let (dropped, warning) = match kind {
+            StepKind::RemoveFields => ("Column", "⚠️ DATA WILL BE LOST"),
+            _ => ("Index", "⚠️ QUERIES USING IT WILL FAIL"),
+        };
+
+        let prompt = format!(
+            "\n{} {} {} {}: ",
+            dropped.bright_magenta(),
+            name.bright_yellow(),
+            "is no longer defined in code. Type its name to drop it, or press enter to skip".bright_red(),
+            warning.bright_red(),
+        );
+
+        if read_confirmation(&prompt, &name, format) {
+            migration.allow_drop(name);
+        }
+    }
+
+    Ok(())
+}
+
+/// Asks user to confirm drop of each removed model that is not allowed with `--allow-drop` by typing its name.
+fn confirm_drops(migration: &mut Migration, format: OutputFormat) -> Result<(), MigrationError> {
+    let not_allowed: Vec<_> = migration
+        .removed_models()?
+        .into_iter()
+        .filter(|(name, _)| !migration.is_drop_allowed(name))
+        .collect();
+
+    for (name, model_type) in not_allowed {
+        let prompt = format!(
+            "\n{} {} {} {}: ",
+            model_type.to_string().bright_magenta(),
+            name.bright_yellow(),
+            "is no longer defined in code. Type its name to drop it, or press enter to skip".bright_red(),
+            "⚠️ DATA WILL BE LOST".bright_red(),
+        );
+
+        if read_confirmation(&prompt, &name, format) {
+            migration.allow_drop(name);
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints prompt and checks if user confirmed it by typing expected name.
+fn read_confirmation(prompt: &str, name: &str, format: OutputFormat) -> bool {
+    // stdout is reserved for json report
+    match format {
+        OutputFormat::Text => {
+            print!("{}", prompt);
+            let _ = io::stdout().flush();
+        }
+        OutputFormat::Json => {
+            eprint!("{}", prompt);
+            let _ = io::stderr().flush();
+        }
+    }
+
+    let mut input = String::new();
 
-    migration.run().await;
-    migration.write_schema_to_json().await;
+    io::stdin().lock().read_line(&mut input).is_ok() && input.trim() == name
 }
@@ -1,18 +1,40 @@
 use crate::args::Args;
+use crate::config::KeyspaceConfig;
+use crate::dependency::{creation_order, dependency_order, sorted_objects, SortedObjects};
+use crate::errors::MigrationError;
+use crate::history::{MigrationFile, MigrationHistory};
+use crate::keyspace::KeyspaceMigration;
+use crate::lock::MigrationLock;
 use crate::model::data::ModelData;
+use crate::model::REBUILD_SUFFIX;
 use crate::model::{ModelMigration, ModelType};
+use crate::plan::{strip_colors, MigrationPlan, PlanStep, StepKind};
+use crate::report::{MigrationReport, OutputFormat, StepStatus};
+use crate::INTERNAL_TABLES;
 use colored::Colorize;
+use futures::StreamExt;
+use scylla::client::pager::TypedRowStream;
 use scylla::client::session::Session;
+use scylla::errors::{DbError, ExecutionError, RequestAttemptError, SchemaAgreementError};
+use scylla::value::{CqlValue, MaybeUnset, Row};
+use std::time::{Duration, Instant};
 
 use charybdis_parser::schema::code_schema::CodeSchema;
 use charybdis_parser::schema::db_schema::DbSchema;
 use charybdis_parser::schema::SchemaObject;
 
+const COPY_PAGE_SIZE: i32 = 1000;
+const DDL_RETRIES: u32 = 3;
+const DDL_RETRY_INTERVAL: Duration = Duration::from_millis(500);
+const SCHEMA_AGREEMENT_INTERVAL: Duration = Duration::from_millis(200);
+
 pub struct Migration<'a> {
     current_db_schema: DbSchema,
     current_code_schema: CodeSchema,
     session: &'a Session,
     args: Args,
+    /// `[keyspace]` section of configuration file, applied before models are migrated.
+    pub(crate) keyspace_config: Option<KeyspaceConfig>,
 }
 
 impl<'a> Migration<'a> {
@@ -22,21 +44,398 @@ impl<'a> Migration<'a> {
             current_code_schema,
             session,
             args,
+            keyspace_config: None,
         }
     }
 
-    pub async fn run(&self) {
-        self.run_udts().await;
-        self.run_tables().await;
-        self.run_materialized_views().await;
+    /// Computes steps required to migrate database schema to code schema without touching the database.
+    pub fn plan(&self) -> Result<MigrationPlan, MigrationError> {
+        let mut plan = MigrationPlan::new();
+        let removed_models = self.checked_removed_models(&mut plan, self.removed_models()?)?;
+        let creation_order = creation_order(&self.current_code_schema)?;
+
+        let mut views_plan = MigrationPlan::new();
+        self.plan_materialized_views(&mut views_plan, &creation_order)?;
+
+        // views are dropped before their base tables are changed and UDTs after tables that use them are changed
+        self.plan_removed_models(&mut plan, &removed_models, ModelType::MaterializedView);
+        self.plan_removed_models(&mut plan, &removed_models, ModelType::Table);
+
+        let (view_drops, view_changes): (Vec<PlanStep>, Vec<PlanStep>) = views_plan
+            .steps
+            .into_iter()
+            .partition(|step| step.kind == StepKind::Drop);
+        plan.steps.extend(view_drops);
+
+        self.plan_udts(&mut plan, &creation_order)?;
+        self.plan_tables(&mut plan, &creation_order)?;
+        plan.steps.extend(view_changes);
+
+        self.plan_removed_models(&mut plan, &removed_models, ModelType::Udt);
+
+        Ok(plan)
+    }
+
+    /// Models present in database, but not in code, in reverse of order in which they could be created, so views
+    /// are dropped before their base tables and UDTs are dropped after tables and UDTs that reference them.
+    pub fn removed_models(&self) -> Result<Vec<(String, ModelType)>, MigrationError> {
+        let db_schema = &self.current_db_schema;
+        let code_schema = &self.current_code_schema;
+
+        let udts: SortedObjects = sorted_objects(&db_schema.udts)
+            .into_iter()
+            .filter(|(name, _)| !code_schema.udts.contains_key(*name))
+            .collect();
+        let tables: SortedObjects = sorted_objects(&db_schema.tables)
+            .into_iter()
+            .filter(|(name, _)| !code_schema.tables.contains_key(*name))
+            .filter(|(name, _)| !INTERNAL_TABLES.contains(&name.as_str()))
+            .filter(|(name, _)| !self.is_rebuild_table(name))
+            .collect();
+        let materialized_views: SortedObjects = sorted_objects(&db_schema.materialized_views)
+            .into_iter()
+            .filter(|(name, _)| !code_schema.materialized_views.contains_key(*name))
+            .collect();
+
+        let removed_models: Vec<(String, ModelType)> = dependency_order(udts, tables, materialized_views)?
+            .into_iter()
+            .rev()
+            .map(|(model_type, name)| (name.clone(), model_type))
+            .collect();
+
+        Ok(removed_models)
+    }
+
+    /// Removed models that can be planned for drop. If any model file couldn't be parsed, its models would look
+    /// removed, so migration fails when they could be dropped, i.e. with `--drop-removed-models` or `--allow-drop`.
+    /// Otherwise drops are skipped with a warning.
+    fn checked_removed_models(
+        &self,
+        plan: &mut MigrationPlan,
+        removed_models: Vec<(String, ModelType)>,
+    ) -> Result<Vec<(String, ModelType)>, MigrationError> {
+        let skipped_files: Vec<String> = self
+            .current_code_schema
+            .skipped_files()
+            .iter()
+            .map(|skipped_file| skipped_file.path.clone())
+            .collect();
+
+        if removed_models.is_empty() || skipped_files.is_empty() {
+            return Ok(removed_models);
+        }
+
+        if self.args.drop_removed_models || removed_models.iter().any(|(name, _)| self.is_drop_allowed(name)) {
+            return Err(MigrationError::SkippedModelFiles(skipped_files));
+        }
+
+        plan.warn(format!(
+            "Skipping drops of models that are not defined in code, as some model files couldn't be parsed: {}",
+            skipped_files.join(", ")
+        ));
+
+        Ok(vec![])
+    }
 
-        println!("\n{}", "Migration plan ran successfully!".bright_green());
+    /// `<table>_rebuild` tables left by `--rebuild-tables` without `--swap-rebuilt-tables`.
+    fn is_rebuild_table(&self, name: &str) -> bool {
+        name.strip_suffix(REBUILD_SUFFIX)
+            .and_then(|table_name| table_name.strip_suffix('_'))
+            .is_some_and(|table_name| self.current_code_schema.tables.contains_key(table_name))
     }
 
-    pub async fn write_schema_to_json(&self) {
+    pub fn is_drop_allowed(&self, name: &str) -> bool {
+        self.args.allow_drop.iter().any(|allowed| allowed == name)
+    }
+
+    /// Allows dropping of removed model, e.g. after it was confirmed by user.
+    pub fn allow_drop(&mut self, name: String) {
+        self.args.allow_drop.push(name);
+    }
+
+    /// Drops of columns and indexes that are blocked in plan, as they are not allowed with `--allow-destructive` or
+    /// `--allow-drop`. This includes old columns of renamed fields and columns discarded by swap of rebuilt table.
+    /// Returns their `--allow-drop` names, e.g. `users.bio`, along with kind of the blocked step.
+    pub fn blocked_drops(&self) -> Result<Vec<(String, StepKind)>, MigrationError> {
+        Ok(self.plan()?.blocked_drops())
+    }
+
+    fn plan_removed_models(
+        &self,
+        plan: &mut MigrationPlan,
+        removed_models: &[(String, ModelType)],
+        model_type: ModelType,
+    ) {
+        for (name, _) in removed_models
+            .iter()
+            .filter(|(_, removed_type)| *removed_type == model_type)
+        {
+            if !self.args.drop_removed_models {
+                plan.warn(format!(
+                    "{} {} is not defined in code. Use `--drop-removed-models` to drop it",
+                    model_type, name
+                ));
+
+                continue;
+            }
+
+            if !self.is_drop_allowed(name) {
+                plan.warn(format!(
+                    "Skipping drop of removed {} {} as it is not allowed with `--allow-drop`",
+                    model_type, name
+                ));
+
+                continue;
+            }
+
+            plan.push(name, model_type, StepKind::Drop, &drop_cql(name, model_type));
+        }
+    }
+
+    /// Executes migration plan and reports outcome of each step. If any step fails, remaining steps are skipped and
+    /// report is returned within `MigrationError::MigrationFailed`.
+    ///
+    /// Keyspace configured by `[keyspace]` section is created or altered first. Unless it's a dry run, migration
+    /// lock is held while keyspace is altered and plan is computed and executed, so concurrent migrators don't run
+    /// the same statements. Missing keyspace is created before the lock is acquired, as lock table lives in it.
+    /// Database schema is reloaded once lock is acquired, as previous lock holder could have migrated it already.
+    pub async fn run(&mut self) -> Result<MigrationReport, MigrationError> {
+        if self.args.dry_run {
+            if let Some(keyspace_config) = &self.keyspace_config {
+                KeyspaceMigration::new(self.session, &self.args.keyspace, keyspace_config)
+                    .format(self.args.format)
+                    .schema_agreement_timeout(self.args.schema_agreement_timeout())
+                    .run(true)
+                    .await?;
+            }
+
+            return self.execute_plan().await;
+        }
+
+        if let Some(keyspace_config) = &self.keyspace_config {
+            KeyspaceMigration::new(self.session, &self.args.keyspace, keyspace_config)
+                .format(self.args.format)
+                .schema_agreement_timeout(self.args.schema_agreement_timeout())
+                .create_if_missing()
+                .await?;
+
+            // session couldn't select keyspace that didn't exist
+            if self.session.get_keyspace().is_none() {
+                self.session
+                    .use_keyspace(&self.args.keyspace, false)
+                    .await
+                    .map_err(|e| MigrationError::UseKeyspaceError(self.args.keyspace.clone(), e))?;
+            }
+        }
+
+        let lock = self.migration_lock();
+
+        lock.hold(async {
+            if let Some(keyspace_config) = &self.keyspace_config {
+                KeyspaceMigration::new(self.session, &self.args.keyspace, keyspace_config)
+                    .format(self.args.format)
+                    .schema_agreement_timeout(self.args.schema_agreement_timeout())
+                    .run(false)
+                    .await?;
+            }
+
+            self.current_db_schema = DbSchema::new(self.session, self.args.keyspace.clone()).await?;
+
+            self.execute_plan().await
+        })
+        .await
+    }
+
+    async fn execute_plan(&self) -> Result<MigrationReport, MigrationError> {
+        let started = Instant::now();
+        let plan = self.plan()?;
+        let removed_models = self.removed_models()?;
+        let mut report = MigrationReport::new(&self.args.keyspace, self.args.dry_run);
+        report.warnings = plan.warnings.clone();
+
+        if self.args.dry_run {
+            progress!(
+                self.args.format,
+                "\n{}\n",
+                "Migration plan (dry run):".on_bright_green().black()
+            );
+            progress!(self.args.format, "{}", plan.to_string().trim_end());
+
+            for step in plan.steps.iter() {
+                report.push(step, StepStatus::Planned);
+            }
+
+            for blocked in plan.blocked.iter() {
+                report.push(&blocked.step, StepStatus::Blocked);
+            }
+        } else {
+            for blocked in plan.blocked.iter() {
+                progress!(
+                    self.args.format,
+                    "{} {} {}",
+                    "Blocked destructive step:".bright_red(),
+                    blocked.step.cql.bright_purple(),
+                    format!(
+                        "Use `--allow-destructive` or `--allow-drop {}` to run it.",
+                        blocked.allow_drop
+                    )
+                    .bright_yellow(),
+                );
+            }
+
+            for warning in plan.warnings.iter() {
+                progress!(self.args.format, "{}", warning.bright_yellow());
+            }
+
+            let mut error = None;
+            let mut rows_copied = None;
+
+            for step in plan.steps.iter() {
+                if error.is_some() {
+                    report.push(step, StepStatus::Skipped);
+                    continue;
+                }
+
+                let step_started = Instant::now();
+                let result = self.execute(step, rows_copied).await;
+                let step_report = report.push(step, StepStatus::Executed);
+                step_report.duration_ms = step_started.elapsed().as_millis() as u64;
+
+                match result {
+                    Ok(copied) => {
+                        if step.kind == StepKind::CopyRows {
+                            rows_copied = copied;
+                            step_report.rows_copied = copied;
+                        }
+                    }
+                    Err(e) => {
+                        step_report.status = StepStatus::Failed;
+                        step_report.error = Some(strip_colors(&e.to_string()));
+                        error = Some(e);
+                    }
+                }
+            }
+
+            for blocked in plan.blocked.iter() {
+                report.push(&blocked.step, StepStatus::Blocked);
+            }
+
+            if let Some(e) = error {
+                self.push_skipped_drops(&mut report, &plan, &removed_models);
+                report.set_duration(started.elapsed());
+
+                return Err(MigrationError::MigrationFailed(Box::new(report), Box::new(e)));
+            }
+
+            progress!(
+                self.args.format,
+                "\n{}",
+                "Migration plan ran successfully!".bright_green()
+            );
+        }
+
+        self.push_skipped_drops(&mut report, &plan, &removed_models);
+        report.set_duration(started.elapsed());
+
+        Ok(report)
+    }
+
+    /// Executes plan step, returning number of copied rows for `CopyRows` steps. `VerifyCopy` step checks rows
+    /// copied by the preceding `CopyRows` step.
+    async fn execute(&self, step: &PlanStep, rows_copied: Option<u64>) -> Result<Option<u64>, MigrationError> {
+        if let Some(insert_cql) = &step.insert_cql {
+            let copied = copy_rows(self.session, step, insert_cql, self.args.format).await?;
+
+            return Ok(Some(copied));
+        }
+
+        if step.kind == StepKind::VerifyCopy {
+            let copied = rows_copied.unwrap_or_default();
+            let found = count_rows(self.session, &step.cql).await?;
+
+            if found != copied {
+                return Err(MigrationError::CopyVerificationFailed(
+                    step.object_name.clone(),
+                    copied,
+                    found,
+                ));
+            }
+
+            return Ok(None);
+        }
+
+        let print = step.kind != StepKind::ChangeTableOptions || self.args.verbose;
+
+        execute_cql(
+            self.session,
+            &step.cql,
+            self.args.format,
+            self.args.schema_agreement_timeout(),
+            print,
+        )
+        .await?;
+
+        Ok(None)
+    }
+
+    /// Reports removed models that were not dropped as skipped drop steps.
+    fn push_skipped_drops(
+        &self,
+        report: &mut MigrationReport,
+        plan: &MigrationPlan,
+        removed_models: &[(String, ModelType)],
+    ) {
+        for (name, model_type) in removed_models {
+            let is_planned = plan.steps.iter().any(|step| {
+                step.kind == StepKind::Drop && step.object_name == *name && step.object_type == *model_type
+            });
+
+            if !is_planned {
+                let step = PlanStep::new(name, *model_type, StepKind::Drop, &drop_cql(name, *model_type));
+                report.push(&step, StepStatus::Skipped);
+            }
+        }
+    }
+
+    /// Writes current plan as the next numbered migration file instead of executing it.
+    pub async fn generate(&self, name: &str) -> Result<Option<MigrationFile>, MigrationError> {
+        let history = MigrationHistory::new(self.session, self.args.keyspace.clone(), self.args.migrations_path())
+            .format(self.args.format)
+            .schema_agreement_timeout(self.args.schema_agreement_timeout());
+        let pending = history.pending().await?;
+
+        if !pending.is_empty() {
+            return Err(MigrationError::PendingMigrations(
+                pending.iter().map(|file| file.file_name()).collect(),
+            ));
+        }
+
+        let plan = self.plan()?;
+
+        if let Some(step) = plan.steps.iter().find(|step| step.kind == StepKind::CopyRows) {
+            return Err(MigrationError::CopyRowsInMigrationFile(step.object_name.clone()));
+        }
+
+        history.generate(&plan, name)
+    }
+
+    pub async fn write_schema_to_json(&self) -> Result<(), MigrationError> {
         DbSchema::new(self.session, self.args.keyspace.clone())
-            .await
-            .write_schema_to_json(&self.args.current_dir);
+            .await?
+            .write_schema_to_json(&self.args.current_dir)
+            .map_err(|e| {
+                MigrationError::SchemaFileError(format!("{}/current_schema.json", self.args.current_dir), e)
+            })?;
+
+        Ok(())
+    }
+
+    fn migration_lock(&self) -> MigrationLock<'a> {
+        MigrationLock::new(self.session, &self.args.keyspace)
+            .ttl(Duration::from_secs(self.args.lock_ttl))
+            .timeout(Duration::from_secs(self.args.lock_timeout))
+            .format(self.args.format)
+            .schema_agreement_timeout(self.args.schema_agreement_timeout())
     }
 
     pub fn get_code_schema(&self) -> &CodeSchema {
@@ -47,10 +446,11 @@ impl<'a> Migration<'a> {
         &self.current_db_schema
     }
 
-    async fn run_udts(&self) {
+    fn plan_udts(&self, plan: &mut MigrationPlan, order: &[(ModelType, &String)]) -> Result<(), MigrationError> {
         let empty_udt = SchemaObject::default();
 
-        for (name, code_udt_schema) in self.current_code_schema.udts.iter() {
+        for name in models_of_type(order, ModelType::Udt) {
+            let code_udt_schema = &self.current_code_schema.udts[name];
             let model_data = ModelData::new(
                 name,
                 ModelType::Udt,
@@ -58,33 +458,68 @@ impl<'a> Migration<'a> {
                 self.current_db_schema.udts.get(name).unwrap_or(&empty_udt),
             );
 
-            let migration = ModelMigration::new(&model_data, self.session, &self.args);
+            let migration = ModelMigration::new(&model_data, &self.args);
 
-            migration.run().await;
+            migration.plan(plan)?;
         }
+
+        Ok(())
     }
 
-    async fn run_tables(&self) {
+    fn plan_tables(&self, plan: &mut MigrationPlan, order: &[(ModelType, &String)]) -> Result<(), MigrationError> {
         let empty_table = SchemaObject::default();
 
-        for (name, code_table_schema) in self.current_code_schema.tables.iter() {
+        for name in models_of_type(order, ModelType::Table) {
+            let code_table_schema = &self.current_code_schema.tables[name];
+            let rebuild_table_name = format!("{}_{}", name, REBUILD_SUFFIX);
             let model_data = ModelData::new(
                 name,
                 ModelType::Table,
                 code_table_schema,
                 self.current_db_schema.tables.get(name).unwrap_or(&empty_table),
-            );
+            )
+            .with_rebuild_table(self.current_db_schema.tables.get(&rebuild_table_name));
+
+            if self.args.rebuild_tables && self.args.swap_rebuilt_tables && model_data.keys_changed() {
+                self.check_table_without_views(name)?;
+            }
+
+            let migration = ModelMigration::new(&model_data, &self.args);
+
+            migration.plan(plan)?;
+        }
+
+        Ok(())
+    }
 
-            let migration = ModelMigration::new(&model_data, self.session, &self.args);
+    /// Table can't be dropped while it has materialized views, so it can't be swapped with its rebuilt table.
+    fn check_table_without_views(&self, table_name: &str) -> Result<(), MigrationError> {
+        let mut views: Vec<String> = self
+            .current_db_schema
+            .materialized_views
+            .iter()
+            .filter(|(_, view)| view.base_table == table_name)
+            .map(|(name, _)| name.clone())
+            .collect();
 
-            migration.run().await;
+        if views.is_empty() {
+            return Ok(());
         }
+
+        views.sort();
+
+        Err(MigrationError::TableRebuildWithViews(table_name.to_string(), views))
     }
 
-    async fn run_materialized_views(&self) {
+    fn plan_materialized_views(
+        &self,
+        plan: &mut MigrationPlan,
+        order: &[(ModelType, &String)],
+    ) -> Result<(), MigrationError> {
         let empty_mv = SchemaObject::default();
 
-        for (name, code_mv_schema) in self.current_code_schema.materialized_views.iter() {
+        for name in models_of_type(order, ModelType::MaterializedView) {
+            let code_mv_schema = &self.current_code_schema.materialized_views[name];
             let model_data = ModelData::new(
                 name,
                 ModelType::MaterializedView,
@@ -92,9 +527,255 @@ impl<'a> Migration<'a> {
                 self.current_db_schema.materialized_views.get(name).unwrap_or(&empty_mv),
             );
 
-            let migration = ModelMigration::new(&model_data, self.session, &self.args);
+            let migration = ModelMigration::new(&model_data, &self.args);
+
+            migration.plan(plan)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Executes schema altering statement and awaits schema agreement, so that following statements don't race schema
+/// propagation on multi-node clusters. Transient errors are retried with backoff, unless schema version changed in
+/// the meantime, i.e. statement was applied even though its response was lost.
+pub(crate) async fn execute_cql(
+    session: &Session,
+    cql: &str,
+    format: OutputFormat,
+    schema_agreement_timeout: Duration,
+    print: bool,
+) -> Result<(), MigrationError> {
+    if print {
+        progress!(
+            format,
+            "{} {}",
+            "Running CQL:".on_bright_green().black(),
+            cql.bright_purple()
+        );
+    }
+
+    let schema_version = await_schema_agreement(session, cql, schema_agreement_timeout).await?;
+    let mut attempt = 0;
+
+    loop {
+        match session.query_unpaged(cql, ()).await {
+            Ok(_) => break,
+            // statement was applied by previous attempt that timed out
+            Err(ExecutionError::LastAttemptError(RequestAttemptError::DbError(DbError::AlreadyExists { .. }, _)))
+                if attempt > 0 =>
+            {
+                break
+            }
+            // session that awaits schema agreement on its own applied the statement, but its wait timed out
+            Err(ExecutionError::SchemaAgreementError(_)) => break,
+            Err(e) if attempt < DDL_RETRIES && is_transient(&e) => {
+                attempt += 1;
+
+                tokio::time::sleep(DDL_RETRY_INTERVAL * 2u32.pow(attempt - 1)).await;
+
+                // retrying applied statement would fail, e.g. `ALTER TABLE ADD` of existing column
+                if await_schema_agreement(session, cql, schema_agreement_timeout).await? != schema_version {
+                    progress!(
+                        format,
+                        "{} {}",
+                        "Transient error, but schema changed, so statement was applied:".bright_yellow(),
+                        e
+                    );
+
+                    break;
+                }
+
+                progress!(
+                    format,
+                    "{} {} {}",
+                    "Transient error, retrying".bright_yellow(),
+                    format!("{}/{}:", attempt, DDL_RETRIES).bright_yellow(),
+                    e
+                );
+            }
+            Err(e) => return Err(MigrationError::ExecutionError(cql.to_string(), e)),
+        }
+    }
+
+    await_schema_agreement(session, cql, schema_agreement_timeout).await?;
+
+    if print {
+        progress!(format, "{}\n", "CQL executed successfully! ✅".bright_green());
+    }
+
+    Ok(())
+}
+
+/// Waits until all nodes agree on the schema and returns agreed schema version. Agreement is awaited here rather
+/// than by the session, so timeout applies to sessions created by the application as well. It's checked right away,
+/// so sessions that already awaited it on their own don't wait again.
+async fn await_schema_agreement(session: &Session, cql: &str, timeout: Duration) -> Result<String, MigrationError> {
+    let started_at = Instant::now();
+
+    loop {
+        let version = session
+            .check_schema_agreement()
+            .await
+            .map_err(|e| MigrationError::SchemaAgreementError(cql.to_string(), e))?;
+
+        if let Some(version) = version {
+            return Ok(version.to_string());
+        }
+
+        if started_at.elapsed() >= timeout {
+            return Err(MigrationError::SchemaAgreementError(
+                cql.to_string(),
+                SchemaAgreementError::Timeout(timeout),
+            ));
+        }
+
+        tokio::time::sleep(SCHEMA_AGREEMENT_INTERVAL).await;
+    }
+}
+
+/// Errors after which statement can be safely retried, e.g. node restart or overload.
+fn is_transient(error: &ExecutionError) -> bool {
+    match error {
+        ExecutionError::ConnectionPoolError(_) | ExecutionError::RequestTimeout(_) => true,
+        ExecutionError::LastAttemptError(error) => match error {
+            RequestAttemptError::BrokenConnectionError(_) | RequestAttemptError::UnableToAllocStreamId => true,
+            RequestAttemptError::DbError(error, _) => matches!(
+                error,
+                DbError::Overloaded
+                    | DbError::IsBootstrapping
+                    | DbError::Unavailable { .. }
+                    | DbError::ReadTimeout { .. }
+                    | DbError::WriteTimeout { .. }
+            ),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Copies rows page by page, reporting progress after each page. Values are inserted as they are, so copied rows get
+/// new write time and no TTL.
+pub(crate) async fn copy_rows(
+    session: &Session,
+    step: &PlanStep,
+    insert_cql: &str,
+    format: OutputFormat,
+) -> Result<u64, MigrationError> {
+    let select_cql = step.cql.as_str();
+    let target_table = step.object_name.as_str();
+
+    progress!(
+        format,
+        "{} {}",
+        "Copying rows:".on_bright_green().black(),
+        select_cql.bright_purple()
+    );
+
+    let insert = session
+        .prepare(insert_cql)
+        .await
+        .map_err(|e| MigrationError::PrepareError(insert_cql.to_string(), e))?;
+
+    let mut rows = rows_stream(session, select_cql).await?;
+    let required_column = step
+        .required_column
+        .as_deref()
+        .and_then(|column| rows.column_specs().get_by_name(column).map(|(index, _)| index));
+    let mut copied: u64 = 0;
+
+    while let Some(row) = rows.next().await {
+        let row = row.map_err(|e| MigrationError::NextRowError(select_cql.to_string(), e))?;
+
+        if required_column.is_some_and(|index| row.columns[index].is_none()) {
+            continue;
+        }
+
+        // unset values don't create tombstones for missing columns
+        let values = row
+            .columns
+            .into_iter()
+            .map(|value| value.map_or(MaybeUnset::Unset, MaybeUnset::Set))
+            .collect::<Vec<MaybeUnset<CqlValue>>>();
+
+        session
+            .execute_unpaged(&insert, values)
+            .await
+            .map_err(|e| MigrationError::ExecutionError(insert_cql.to_string(), e))?;
 
-            migration.run().await;
+        copied += 1;
+
+        if copied % COPY_PAGE_SIZE as u64 == 0 {
+            progress!(
+                format,
+                "{} {} {} {}",
+                "Copied".bright_cyan(),
+                copied.to_string().bright_yellow(),
+                "rows into".bright_cyan(),
+                target_table.bright_yellow()
+            );
         }
     }
+
+    progress!(
+        format,
+        "{} {} {} {}",
+        "Copied".bright_cyan(),
+        copied.to_string().bright_yellow(),
+        "rows into".bright_cyan(),
+        target_table.bright_yellow()
+    );
+    progress!(format, "{}\n", "Rows copied successfully! ✅".bright_green());
+
+    Ok(copied)
+}
+
+/// Counts rows selected by `select_cql` page by page, as `COUNT(*)` of large table could time out.
+async fn count_rows(session: &Session, select_cql: &str) -> Result<u64, MigrationError> {
+    let mut rows = rows_stream(session, select_cql).await?;
+    let mut count: u64 = 0;
+
+    while let Some(row) = rows.next().await {
+        row.map_err(|e| MigrationError::NextRowError(select_cql.to_string(), e))?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Pages through rows the same way as `find_all` of models.
+async fn rows_stream(session: &Session, select_cql: &str) -> Result<TypedRowStream<Row>, MigrationError> {
+    let mut select = session
+        .prepare(select_cql)
+        .await
+        .map_err(|e| MigrationError::PrepareError(select_cql.to_string(), e))?;
+    select.set_page_size(COPY_PAGE_SIZE);
+
+    session
+        .execute_iter(select, ())
+        .await
+        .map_err(|e| MigrationError::PagerExecutionError(select_cql.to_string(), e))?
+        .rows_stream::<Row>()
+        .map_err(|e| MigrationError::TypeCheckError(select_cql.to_string(), e))
+}
+
+fn drop_cql(name: &str, model_type: ModelType) -> String {
+    let object = match model_type {
+        ModelType::Udt => "TYPE",
+        ModelType::Table => "TABLE",
+        ModelType::MaterializedView => "MATERIALIZED VIEW",
+    };
+
+    format!("DROP {} IF EXISTS {}", object, name)
+}
+
+/// Names of models of given type in creation order.
+fn models_of_type<'a>(
+    order: &'a [(ModelType, &'a String)],
+    model_type: ModelType,
+) -> impl Iterator<Item = &'a String> + 'a {
+    order
+        .iter()
+        .filter(move |(order_type, _)| *order_type == model_type)
+        .map(|(_, name)| *name)
 }
@@ -1,16 +1,20 @@
 use std::fmt::Display;
 
 use crate::args::Args;
+use crate::errors::MigrationError;
 use crate::model::data::ModelData;
-use crate::model::runner::ModelRunner;
-use colored::Colorize;
-use scylla::client::session::Session;
+use crate::model::planner::ModelPlanner;
+pub(crate) use crate::model::planner::REBUILD_SUFFIX;
+use crate::plan::MigrationPlan;
+use charybdis_parser::schema::diff::SchemaObjectType;
+use serde::Serialize;
 
 pub(crate) mod data;
-mod runner;
+mod planner;
 
-#[derive(Debug, Eq, PartialEq, Clone, Copy)]
-pub(crate) enum ModelType {
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelType {
     Udt,
     Table,
     MaterializedView,
@@ -26,26 +30,46 @@ impl Display for ModelType {
     }
 }
 
+impl From<ModelType> for SchemaObjectType {
+    fn from(model_type: ModelType) -> Self {
+        match model_type {
+            ModelType::Udt => SchemaObjectType::Udt,
+            ModelType::Table => SchemaObjectType::Table,
+            ModelType::MaterializedView => SchemaObjectType::MaterializedView,
+        }
+    }
+}
+
 /// Migration steps in non-conflicting order
 enum MigrationStep {
     ChangeTableOptions,
     ChangeFieldTypes,
+    RenameFields,
     AddFields,
+    RemoveIndexes,
+    AddIndexes,
     AddGlobalIndexes,
     AddLocalIndexes,
+    AddVectorIndexes,
+    RemoveVectorIndexes,
     RemoveLocalIndexes,
     RemoveGlobalIndexes,
     RemoveFields,
 }
 
 impl MigrationStep {
-    fn array() -> [MigrationStep; 8] {
+    fn array() -> [MigrationStep; 13] {
         [
             MigrationStep::ChangeTableOptions,
             MigrationStep::ChangeFieldTypes,
+            MigrationStep::RenameFields,
             MigrationStep::AddFields,
+            MigrationStep::RemoveIndexes,
+            MigrationStep::AddIndexes,
             MigrationStep::AddGlobalIndexes,
             MigrationStep::AddLocalIndexes,
+            MigrationStep::AddVectorIndexes,
+            MigrationStep::RemoveVectorIndexes,
             MigrationStep::RemoveLocalIndexes,
             MigrationStep::RemoveGlobalIndexes,
             MigrationStep::RemoveFields,
@@ -55,179 +79,213 @@ impl MigrationStep {
 
 pub(crate) struct ModelMigration<'a> {
     data: &'a ModelData<'a>,
-    runner: ModelRunner<'a>,
+    planner: ModelPlanner<'a>,
     args: &'a Args,
 }
 
 impl<'a> ModelMigration<'a> {
-    pub(crate) fn new(data: &'a ModelData, session: &'a Session, args: &'a Args) -> Self {
-        let runner = ModelRunner::new(session, data, args);
+    pub(crate) fn new(data: &'a ModelData, args: &'a Args) -> Self {
+        let planner = ModelPlanner::new(data, args);
 
-        Self { data, runner, args }
+        Self { data, planner, args }
     }
 
-    pub(crate) async fn run(&self) {
+    /// Appends steps required to migrate the model to the given plan.
+    pub(crate) fn plan(&self, plan: &mut MigrationPlan) -> Result<(), MigrationError> {
         if self.data.is_first_migration() {
-            self.handle_first_migration().await;
-            return;
+            self.handle_first_migration(plan);
+            return Ok(());
         }
 
-        self.panic_on_partition_key_change();
-        self.panic_on_clustering_key_change();
+        if self.data.migration_object_type == ModelType::MaterializedView {
+            return self.handle_view_migration(plan);
+        }
 
-        let mut is_any_field_changed = false;
+        if self.data.migration_object_type == ModelType::Table && self.args.rebuild_tables && self.data.keys_changed() {
+            return self.planner.plan_table_rebuild_migration(
+                plan,
+                self.args.swap_rebuilt_tables,
+                &self.args.vector_index_class,
+            );
+        }
+
+        self.check_partition_key_change()?;
+        self.check_clustering_key_change()?;
 
         for step in MigrationStep::array().iter() {
             match step {
-                MigrationStep::ChangeTableOptions => self.runner.run_table_options_change_migration().await,
+                MigrationStep::ChangeTableOptions => self.planner.plan_table_options_change_migration(plan),
                 MigrationStep::ChangeFieldTypes => {
                     if self.data.has_changed_type_fields() {
-                        is_any_field_changed = true;
-                        self.handle_fields_type_change().await;
+                        self.handle_fields_type_change(plan)?;
+                    }
+                }
+                MigrationStep::RenameFields => {
+                    if self.data.has_renamed_fields() {
+                        self.planner.plan_field_renamed_migration(plan)?;
                     }
                 }
                 MigrationStep::AddFields => {
                     if self.data.has_new_fields() {
-                        is_any_field_changed = true;
-                        self.handle_new_fields().await;
+                        self.handle_new_fields(plan)?;
+                    }
+                }
+                // changed indexes are dropped before they are created again with the same name
+                MigrationStep::RemoveIndexes => {
+                    if self.data.has_removed_indexes() {
+                        self.planner.plan_index_removed_migration(plan);
+                    }
+                }
+                MigrationStep::AddIndexes => {
+                    if self.data.has_new_indexes() {
+                        self.planner.plan_index_added_migration(plan);
                     }
                 }
                 MigrationStep::AddGlobalIndexes => {
                     if self.data.has_new_global_secondary_indexes() {
-                        is_any_field_changed = true;
-                        self.runner.run_global_index_added_migration().await;
+                        self.planner.plan_global_index_added_migration(plan);
                     }
                 }
                 MigrationStep::AddLocalIndexes => {
                     if self.data.has_new_local_secondary_indexes() {
-                        is_any_field_changed = true;
-                        self.runner.run_local_index_added_migration().await;
+                        self.planner.plan_local_index_added_migration(plan);
+                    }
+                }
+                MigrationStep::AddVectorIndexes => {
+                    if self.data.has_new_vector_indexes() {
+                        self.planner
+                            .plan_vector_index_added_migration(plan, &self.args.vector_index_class);
+                    }
+                }
+                MigrationStep::RemoveVectorIndexes => {
+                    if self.data.has_removed_vector_indexes() {
+                        self.planner.plan_vector_index_removed_migration(plan);
                     }
                 }
                 MigrationStep::RemoveGlobalIndexes => {
                     if self.data.has_removed_global_secondary_indexes() {
-                        is_any_field_changed = true;
-                        self.runner.run_global_index_removed_migration().await;
+                        self.planner.plan_global_index_removed_migration(plan);
                     }
                 }
                 MigrationStep::RemoveLocalIndexes => {
                     if self.data.has_removed_local_secondary_indexes() {
-                        is_any_field_changed = true;
-                        self.runner.run_local_index_removed_migration().await;
+                        self.planner.plan_local_index_removed_migration(plan);
                     }
                 }
                 MigrationStep::RemoveFields => {
                     if self.data.has_removed_fields() {
-                        is_any_field_changed = true;
-                        self.handle_removed_fields().await;
+                        self.handle_removed_fields(plan)?;
                     }
                 }
             }
         }
 
-        if !is_any_field_changed {
-            println!(
-                "{} {} {}",
-                "No field changes in".green(),
-                self.data.migration_object_name.bright_yellow(),
-                self.data.migration_object_type.to_string().bright_magenta()
-            );
-        }
+        Ok(())
     }
 
-    async fn handle_first_migration(&self) {
-        self.runner.run_first_migration().await;
+    fn handle_first_migration(&self, plan: &mut MigrationPlan) {
+        self.planner.plan_first_migration(plan);
 
         if self.data.has_new_global_secondary_indexes() {
-            self.runner.run_global_index_added_migration().await;
+            self.planner.plan_global_index_added_migration(plan);
         }
 
         if self.data.has_new_local_secondary_indexes() {
-            self.runner.run_local_index_added_migration().await;
+            self.planner.plan_local_index_added_migration(plan);
+        }
+
+        if self.data.has_new_vector_indexes() {
+            self.planner
+                .plan_vector_index_added_migration(plan, &self.args.vector_index_class);
+        }
+
+        if self.data.has_new_indexes() {
+            self.planner.plan_index_added_migration(plan);
         }
     }
 
-    async fn handle_fields_type_change(&self) {
-        if self.args.drop_and_replace {
-            self.panic_on_mv_fields_change();
-            self.panic_on_udt_fields_removal();
+    /// Materialized views can't be altered apart from their table options, so any change of their definition
+    /// requires dropping and recreating the view.
+    fn handle_view_migration(&self, plan: &mut MigrationPlan) -> Result<(), MigrationError> {
+        if !self.data.view_definition_changed() {
+            self.planner.plan_table_options_change_migration(plan);
 
-            self.runner.run_field_type_changed_migration().await;
-        } else {
-            self.panic_on_field_type_change();
+            return Ok(());
         }
+
+        if !self.args.recreate_views && !self.args.drop_and_replace {
+            return Err(MigrationError::MaterializedViewChange(
+                self.data.migration_object_name.clone(),
+                self.data.migration_object_type,
+            ));
+        }
+
+        self.planner.plan_view_recreate_migration(plan);
+
+        Ok(())
     }
 
-    async fn handle_new_fields(&self) {
-        self.panic_on_mv_fields_change();
+    fn handle_fields_type_change(&self, plan: &mut MigrationPlan) -> Result<(), MigrationError> {
+        if !self.args.drop_and_replace {
+            return Err(MigrationError::FieldTypeChange(
+                self.data.migration_object_name.clone(),
+                self.data.migration_object_type,
+            ));
+        }
+
+        self.check_udt_fields_removal()?;
 
-        self.runner.run_field_added_migration().await;
+        self.planner.plan_field_type_changed_migration(plan);
+
+        Ok(())
     }
 
-    async fn handle_removed_fields(&self) {
-        self.panic_on_mv_fields_change();
-        self.panic_on_udt_fields_removal();
+    fn handle_new_fields(&self, plan: &mut MigrationPlan) -> Result<(), MigrationError> {
+        self.planner.plan_field_added_migration(plan);
 
-        self.runner.run_field_removed_migration().await;
+        Ok(())
     }
 
-    fn panic_on_field_type_change(&self) {
-        panic!(
-            "\n\n{} {} {}\n{}\n\n",
-            "Illegal change in".bright_red(),
-            self.data.migration_object_name.bright_yellow(),
-            self.data.migration_object_type.to_string().bright_magenta(),
-            "Field type change is not allowed. Use `-d` flag to drop and recreate field with new type!".bright_red(),
-        );
+    fn handle_removed_fields(&self, plan: &mut MigrationPlan) -> Result<(), MigrationError> {
+        self.check_udt_fields_removal()?;
+
+        self.planner.plan_field_removed_migration(plan);
+
+        Ok(())
     }
 
-    fn panic_on_partition_key_change(&self) {
+    fn check_partition_key_change(&self) -> Result<(), MigrationError> {
         if self.data.migration_object_type != ModelType::Udt && self.data.partition_key_changed() {
-            panic!(
-                "\n\n{} {} {}\n{}\n\n",
-                "Illegal change in".bright_red(),
-                self.data.migration_object_name.bright_yellow(),
-                self.data.migration_object_type.to_string().bright_magenta(),
-                "Partition key change is not allowed!".bright_red(),
-            );
+            return Err(MigrationError::PartitionKeyChange(
+                self.data.migration_object_name.clone(),
+                self.data.migration_object_type,
+            ));
         }
+
+        Ok(())
     }
 
-    fn panic_on_clustering_key_change(&self) {
+    fn check_clustering_key_change(&self) -> Result<(), MigrationError> {
         if self.data.migration_object_type != ModelType::Udt && self.data.clustering_key_changed() {
-            panic!(
-                "\n\n{} {} {}\n{}\n\n",
-                "Illegal change in".bright_red(),
-                self.data.migration_object_name.bright_yellow(),
-                self.data.migration_object_type.to_string().bright_magenta(),
-                "Clustering key change is not allowed!".bright_red(),
-            );
+            return Err(MigrationError::ClusteringKeyChange(
+                self.data.migration_object_name.clone(),
+                self.data.migration_object_type,
+            ));
         }
+
+        Ok(())
     }
 
-    fn panic_on_udt_fields_removal(&self) {
+    fn check_udt_fields_removal(&self) -> Result<(), MigrationError> {
         if self.data.migration_object_type == ModelType::Udt
             && (self.data.has_removed_fields() || self.data.has_changed_type_fields())
         {
-            panic!(
-                "\n\n{} {} {}\n{}\n\n",
-                "Illegal change in".bright_red(),
-                self.data.migration_object_name.bright_yellow(),
-                self.data.migration_object_type.to_string().bright_magenta(),
-                "UDT fields removal is not allowed!".bright_red(),
-            );
+            return Err(MigrationError::UdtFieldsRemoval(
+                self.data.migration_object_name.clone(),
+                self.data.migration_object_type,
+            ));
         }
-    }
 
-    fn panic_on_mv_fields_change(&self) {
-        if self.data.migration_object_type == ModelType::MaterializedView {
-            panic!(
-                "\n\n{} {} {}\n{}\n\n",
-                "Illegal change in".bright_red(),
-                self.data.migration_object_name.bright_yellow(),
-                self.data.migration_object_type.to_string().bright_magenta(),
-                "Materialized view fields change is not allowed!".bright_red(),
-            );
-        }
+        Ok(())
     }
 }
@@ -1,25 +1,21 @@
-use charybdis_parser::schema::{IndexName, SchemaObject};
+use charybdis_parser::schema::diff::ObjectDiff;
+use charybdis_parser::schema::table_options::TableOptions;
+use charybdis_parser::schema::SchemaObject;
 
-use crate::model::runner::INDEX_SUFFIX;
+use crate::model::planner::INDEX_SUFFIX;
 use crate::model::ModelType;
 
 type FieldName = String;
-type FieldType = String;
-type OldFieldType = FieldType;
-type NewFieldType = FieldType;
 
 pub struct ModelData<'a> {
     pub(crate) migration_object_name: &'a String,
     pub(crate) migration_object_type: ModelType,
     pub(crate) current_code_schema: &'a SchemaObject,
     pub(crate) current_db_schema: &'a SchemaObject,
-    pub(crate) new_fields: Vec<(FieldName, FieldType)>,
-    pub(crate) removed_fields: Vec<FieldName>,
-    pub(crate) new_global_secondary_indexes: Vec<FieldName>,
-    pub(crate) new_local_secondary_indexes: Vec<FieldName>,
-    pub(crate) removed_global_secondary_indexes: Vec<IndexName>,
-    pub(crate) removed_local_secondary_indexes: Vec<IndexName>,
-    pub(crate) changed_field_types: Vec<(FieldName, OldFieldType, NewFieldType)>,
+    /// `<table>_rebuild` table left by previous rebuild, if any.
+    pub(crate) rebuild_db_schema: Option<&'a SchemaObject>,
+    /// Changes from db schema to code schema.
+    pub(crate) diff: ObjectDiff<'a>,
 }
 
 impl<'a> ModelData<'a> {
@@ -29,33 +25,30 @@ impl<'a> ModelData<'a> {
         current_code_schema: &'a SchemaObject,
         current_db_schema: &'a SchemaObject,
     ) -> Self {
-        let mut data = Self {
+        let diff = ObjectDiff::new(
+            migration_object_name,
+            migration_object_type.into(),
+            current_db_schema,
+            current_code_schema,
+        );
+
+        Self {
             migration_object_name,
             migration_object_type,
             current_code_schema,
             current_db_schema,
-            new_fields: vec![],
-            removed_fields: vec![],
-            new_global_secondary_indexes: vec![],
-            new_local_secondary_indexes: vec![],
-            removed_global_secondary_indexes: vec![],
-            removed_local_secondary_indexes: vec![],
-            changed_field_types: vec![],
-        };
-
-        data.fetch_new_fields();
-        data.fetch_removed_fields();
-        data.fetch_new_global_secondary_indexes();
-        data.fetch_removed_global_secondary_indexes();
-        data.fetch_new_local_secondary_indexes();
-        data.fetch_removed_local_secondary_indexes();
-        data.fetch_changed_field_types();
-
-        data
+            rebuild_db_schema: None,
+            diff,
+        }
     }
 
-    pub(crate) fn construct_index_name(&self, column_name: &String) -> String {
-        format!("{}_{}_{}", self.migration_object_name, column_name, INDEX_SUFFIX)
+    pub(crate) fn construct_index_name(&self, column_name: &str) -> String {
+        format!(
+            "{}_{}_{}",
+            self.migration_object_name,
+            index_name_part(column_name),
+            INDEX_SUFFIX
+        )
     }
 
     pub(crate) fn is_first_migration(&self) -> bool {
@@ -63,149 +56,175 @@ impl<'a> ModelData<'a> {
     }
 
     pub(crate) fn has_new_global_secondary_indexes(&self) -> bool {
-        !self.new_global_secondary_indexes.is_empty()
+        !self.diff.new_global_secondary_indexes.is_empty()
+    }
+
+    pub(crate) fn has_new_vector_indexes(&self) -> bool {
+        !self.diff.new_vector_indexes.is_empty()
+    }
+
+    pub(crate) fn has_removed_vector_indexes(&self) -> bool {
+        !self.diff.removed_vector_indexes.is_empty()
+    }
+
+    pub(crate) fn has_new_indexes(&self) -> bool {
+        !self.diff.new_indexes.is_empty()
+    }
+
+    pub(crate) fn has_removed_indexes(&self) -> bool {
+        !self.diff.removed_indexes.is_empty()
     }
 
     pub(crate) fn has_removed_global_secondary_indexes(&self) -> bool {
-        !self.removed_global_secondary_indexes.is_empty()
+        !self.diff.removed_global_secondary_indexes.is_empty()
     }
 
     pub(crate) fn has_new_local_secondary_indexes(&self) -> bool {
-        !self.new_local_secondary_indexes.is_empty()
+        !self.diff.new_local_secondary_indexes.is_empty()
     }
 
     pub(crate) fn has_removed_local_secondary_indexes(&self) -> bool {
-        !self.removed_local_secondary_indexes.is_empty()
+        !self.diff.removed_local_secondary_indexes.is_empty()
     }
 
     pub(crate) fn has_new_fields(&self) -> bool {
-        !self.new_fields.is_empty()
+        !self.diff.new_fields.is_empty()
     }
 
     pub(crate) fn has_removed_fields(&self) -> bool {
-        !self.removed_fields.is_empty()
+        !self.diff.removed_fields.is_empty()
+    }
+
+    pub(crate) fn has_renamed_fields(&self) -> bool {
+        !self.diff.renamed_fields.is_empty()
     }
 
     // Checks if any field of db schema has changed type in code schema.
     pub(crate) fn has_changed_type_fields(&self) -> bool {
-        !self.changed_field_types.is_empty()
+        !self.diff.changed_field_types.is_empty()
     }
 
     pub(crate) fn partition_key_changed(&self) -> bool {
-        let mut code_partition_keys = self.current_code_schema.partition_keys.clone();
-        let mut db_partition_keys = self.current_db_schema.partition_keys.clone();
+        self.diff.partition_key_changed()
+    }
 
-        code_partition_keys.sort();
-        db_partition_keys.sort();
+    pub(crate) fn clustering_key_changed(&self) -> bool {
+        self.diff.clustering_key_changed()
+    }
 
-        code_partition_keys != db_partition_keys
+    pub(crate) fn base_table_changed(&self) -> bool {
+        self.diff.base_table_changed()
     }
 
-    pub(crate) fn clustering_key_changed(&self) -> bool {
-        let mut code_clustering_keys = self.current_code_schema.clustering_keys.clone();
-        let mut db_clustering_keys = self.current_db_schema.clustering_keys.clone();
+    pub(crate) fn clustering_order_changed(&self) -> bool {
+        self.diff.clustering_order_changed()
+    }
 
-        code_clustering_keys.sort();
-        db_clustering_keys.sort();
+    /// Table options set in code that differ from database.
+    pub(crate) fn changed_table_options(&self) -> TableOptions {
+        self.diff.changed_table_options()
+    }
 
-        code_clustering_keys != db_clustering_keys
+    /// Checks if materialized view changed in a way that can only be applied by dropping and recreating it.
+    pub(crate) fn view_definition_changed(&self) -> bool {
+        self.has_new_fields()
+            || self.has_removed_fields()
+            || self.has_changed_type_fields()
+            || self.partition_key_changed()
+            || self.clustering_key_changed()
+            || self.base_table_changed()
+            || self.clustering_order_changed()
     }
 
-    fn fetch_new_fields(&mut self) {
-        for (field_name, field_type, _) in self.current_code_schema.fields.iter() {
-            if !self.current_db_schema.contains_field(field_name) {
-                self.new_fields.push((field_name.clone(), field_type.clone()));
-            }
-        }
+    pub(crate) fn keys_changed(&self) -> bool {
+        self.diff.keys_changed()
     }
 
-    fn fetch_removed_fields(&mut self) {
-        for (field_name, _, _) in self.current_db_schema.fields.iter() {
-            if !self.current_code_schema.contains_field(field_name) {
-                self.removed_fields.push(field_name.clone());
-            }
-        }
+    pub(crate) fn with_rebuild_table(mut self, rebuild_db_schema: Option<&'a SchemaObject>) -> Self {
+        self.rebuild_db_schema = rebuild_db_schema;
+        self
     }
 
-    fn fetch_new_global_secondary_indexes(&mut self) {
-        let _ = &self
-            .current_code_schema
-            .global_secondary_indexes
-            .iter()
-            .for_each(|(_index_name, index_target)| {
-                if !self
-                    .current_db_schema
-                    .global_secondary_indexes
-                    .iter()
-                    .any(|(_, target)| target == index_target)
-                {
-                    self.new_global_secondary_indexes.push(index_target.clone());
-                }
-            });
+    /// Rebuild table exists and already has the key layout of code schema.
+    pub(crate) fn is_rebuilt(&self) -> bool {
+        self.rebuild_db_schema.is_some_and(|rebuild_db_schema| {
+            rebuild_db_schema.partition_keys == self.current_code_schema.partition_keys
+                && rebuild_db_schema.clustering_keys == self.current_code_schema.clustering_keys
+        })
     }
 
-    fn fetch_removed_global_secondary_indexes(&mut self) {
-        let _ = &self
-            .current_db_schema
-            .global_secondary_indexes
+    pub(crate) fn has_counter_fields(&self) -> bool {
+        self.current_code_schema
+            .fields
             .iter()
-            .for_each(|(index_name, index_target)| {
-                if !self
-                    .current_code_schema
-                    .global_secondary_indexes
-                    .iter()
-                    .any(|(_index_name, target)| target == index_target)
-                {
-                    self.removed_global_secondary_indexes.push(index_name.clone());
-                }
-            });
+            .any(|(_, field_type, _)| field_type.trim().eq_ignore_ascii_case("counter"))
     }
 
-    fn fetch_new_local_secondary_indexes(&mut self) {
-        let _ = &self
-            .current_code_schema
-            .local_secondary_indexes
+    /// Fields that exist with the same type in both code and db schema, so they can be copied into rebuilt table.
+    pub(crate) fn rebuild_copied_fields(&self) -> Vec<FieldName> {
+        self.current_code_schema
+            .fields
             .iter()
-            .for_each(|(_index_name, index_target)| {
-                if !self
-                    .current_db_schema
-                    .local_secondary_indexes
+            .filter(|(field_name, _, _)| self.current_db_schema.contains_field(field_name))
+            .filter(|(field_name, _, _)| {
+                !self
+                    .diff
+                    .changed_field_types
                     .iter()
-                    .any(|(_, target)| target == index_target)
-                {
-                    self.new_local_secondary_indexes.push(index_target.clone());
-                }
-            });
+                    .any(|(changed_field, _, _)| changed_field == field_name)
+            })
+            .map(|(field_name, _, _)| field_name.clone())
+            .collect()
     }
 
-    fn fetch_removed_local_secondary_indexes(&mut self) {
-        let _ = &self
-            .current_db_schema
-            .local_secondary_indexes
+    /// Columns of db table that are no longer in code, including old names of renamed fields. Their values are lost
+    /// once table is swapped with its rebuilt table.
+    pub(crate) fn rebuild_discarded_fields(&self) -> Vec<&FieldName> {
+        self.current_db_schema
+            .fields
             .iter()
-            .for_each(|(index_name, index_target)| {
-                if !self
-                    .current_code_schema
-                    .local_secondary_indexes
-                    .iter()
-                    .any(|(_index_name, target)| target == index_target)
-                {
-                    self.removed_local_secondary_indexes.push(index_name.clone());
-                }
-            });
-    }
-
-    fn fetch_changed_field_types(&mut self) {
-        for (field_name, field_type, _) in self.current_code_schema.fields.iter() {
-            if let Some(db_field_type) = self.current_db_schema.types_by_name.get(field_name) {
-                let code_field_type = field_type.to_lowercase().replace(' ', "");
-                let db_field_type = db_field_type.to_lowercase().replace(' ', "");
-
-                if code_field_type != db_field_type {
-                    self.changed_field_types
-                        .push((field_name.clone(), db_field_type.clone(), code_field_type.clone()));
-                }
+            .map(|(field_name, _, _)| field_name)
+            .filter(|field_name| !self.current_code_schema.contains_field(field_name))
+            .collect()
+    }
+}
+
+/// Index names can't be quoted, so they are lowercased the same way CQL lowercases unquoted names, and characters of
+/// quoted column names that aren't valid in identifiers are replaced by `_`.
+fn index_name_part(column_name: &str) -> String {
+    column_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
             }
-        }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_name_is_lowercased() {
+        let name = "accounts".to_string();
+        let schema = SchemaObject::default();
+        let data = ModelData::new(&name, ModelType::Table, &schema, &schema);
+
+        assert_eq!(data.construct_index_name("email"), "accounts_email_idx");
+        assert_eq!(data.construct_index_name("user_id"), "accounts_user_id_idx");
+        assert_eq!(data.construct_index_name("displayName"), "accounts_displayname_idx");
+    }
+
+    #[test]
+    fn index_name_of_quoted_column_is_identifier() {
+        let name = "accounts".to_string();
+        let schema = SchemaObject::default();
+        let data = ModelData::new(&name, ModelType::Table, &schema, &schema);
+
+        assert_eq!(data.construct_index_name("Email Address"), "accounts_email_address_idx");
     }
 }
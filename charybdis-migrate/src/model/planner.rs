@@ -0,0 +1,1161 @@
+use crate::args::{allow_drop_name, Args};
+use crate::errors::MigrationError;
+use crate::model::{ModelData, ModelType};
+use crate::plan::{MigrationPlan, StepKind};
+use charybdis_parser::fields::{
+    find_all_query, find_column_values_query, insert_query, quote_identifier, update_column_query,
+};
+use charybdis_parser::schema::secondary_indexes::IndexDefinition;
+use charybdis_parser::schema::table_options::TableOptionValue;
+use charybdis_parser::schema::IndexName;
+
+pub(crate) const INDEX_SUFFIX: &str = "idx";
+pub(crate) const REBUILD_SUFFIX: &str = "rebuild";
+
+/// Translates detected model changes into ordered plan steps.
+pub(crate) struct ModelPlanner<'a> {
+    data: &'a ModelData<'a>,
+    args: &'a Args,
+}
+
+impl<'a> ModelPlanner<'a> {
+    pub fn new(data: &'a ModelData, args: &'a Args) -> Self {
+        Self { data, args }
+    }
+
+    fn is_drop_allowed(&self, name: &str) -> bool {
+        self.args.is_destructive_allowed(self.data.migration_object_name, name)
+    }
+
+    /// Pushes drop of removed column or index, or reports it as blocked if it's not allowed.
+    fn push_drop(&self, plan: &mut MigrationPlan, kind: StepKind, name: &str, cql: &str) {
+        if self.is_drop_allowed(name) {
+            self.push(plan, kind, cql);
+            return;
+        }
+
+        let allow_drop = allow_drop_name(self.data.migration_object_name, name);
+
+        plan.push_blocked(
+            self.data.migration_object_name,
+            self.data.migration_object_type,
+            kind,
+            cql,
+            allow_drop,
+        );
+    }
+
+    fn push(&self, plan: &mut MigrationPlan, kind: StepKind, cql: &str) {
+        plan.push(
+            self.data.migration_object_name,
+            self.data.migration_object_type,
+            kind,
+            cql,
+        );
+    }
+
+    pub(crate) fn plan_first_migration(&self, plan: &mut MigrationPlan) {
+        match self.data.migration_object_type {
+            ModelType::Udt => {
+                let cql = format!(
+                    "CREATE TYPE IF NOT EXISTS {}\n(\n{}\n);\n",
+                    self.data.migration_object_name,
+                    self.data.current_code_schema.create_fields_clause()
+                );
+
+                self.push(plan, StepKind::Create, &cql);
+            }
+            ModelType::Table => {
+                let cql = self.create_table_cql(self.data.migration_object_name);
+
+                self.push(plan, StepKind::Create, &cql);
+            }
+            ModelType::MaterializedView => {
+                let cql = self.create_view_cql();
+
+                self.push(plan, StepKind::Create, &cql);
+            }
+        }
+    }
+
+    fn create_table_cql(&self, table_name: &str) -> String {
+        let clustering_keys = quoted(&self.data.current_code_schema.clustering_keys);
+        let clustering_keys_clause = if !clustering_keys.is_empty() {
+            format!(",{}", clustering_keys)
+        } else {
+            "".to_string()
+        };
+
+        let table_options = &self.data.current_code_schema.table_options;
+        let mut table_options_clause = String::new();
+
+        if let Some(table_options) = table_options {
+            table_options_clause = format!("WITH {}", table_options);
+        }
+
+        format!(
+            "CREATE TABLE IF NOT EXISTS {}\n(\n{}, \n    PRIMARY KEY (({}) {})\n) \n {}",
+            table_name,
+            self.data.current_code_schema.create_fields_clause(),
+            quoted(&self.data.current_code_schema.partition_keys),
+            clustering_keys_clause,
+            table_options_clause,
+        )
+    }
+
+    fn create_view_cql(&self) -> String {
+        let mut primary_key = self.data.current_code_schema.partition_keys.clone();
+        primary_key.append(&mut self.data.current_code_schema.clustering_keys.clone());
+
+        let table_options = &self.data.current_code_schema.table_options;
+        let mut table_options_clause = String::new();
+
+        if let Some(table_options) = table_options {
+            table_options_clause = format!("WITH {}", table_options);
+        }
+
+        let materialized_view_where_clause = format!(
+            "WHERE {}",
+            primary_key
+                .iter()
+                .map(|field| format!("{} IS NOT NULL", quote_identifier(field)))
+                .collect::<Vec<String>>()
+                .join(" AND ")
+        );
+
+        let mv_fields_without_types = self
+            .data
+            .current_code_schema
+            .fields
+            .clone()
+            .into_iter()
+            .map(|(field_name, _, _)| field_name)
+            .collect::<Vec<String>>();
+
+        let materialized_view_select_clause = format!(
+            "SELECT {} \nFROM {}\n{}",
+            quoted(&mv_fields_without_types),
+            self.data.current_code_schema.base_table.clone(),
+            materialized_view_where_clause
+        );
+
+        let primary_key_clause = format!(
+            "PRIMARY KEY (({}), {})\n",
+            quoted(&self.data.current_code_schema.partition_keys),
+            quoted(&self.data.current_code_schema.clustering_keys)
+        );
+
+        format!(
+            "CREATE MATERIALIZED VIEW IF NOT EXISTS {}\nAS {}\n{}\n{}",
+            self.data.migration_object_name, materialized_view_select_clause, primary_key_clause, table_options_clause
+        )
+    }
+
+    pub(crate) fn plan_view_recreate_migration(&self, plan: &mut MigrationPlan) {
+        let cql = format!("DROP MATERIALIZED VIEW IF EXISTS {}", self.data.migration_object_name);
+
+        self.push(plan, StepKind::Drop, &cql);
+        self.push(plan, StepKind::Create, &self.create_view_cql());
+    }
+
+    /// Rebuilds table with changed keys into `<table>_rebuild` table. With `swap`, original table is dropped only
+    /// after all rows were copied into rebuild table, and rebuild table is dropped only after they were copied back.
+    pub(crate) fn plan_table_rebuild_migration(
+        &self,
+        plan: &mut MigrationPlan,
+        swap: bool,
+        vector_index_class: &str,
+    ) -> Result<(), MigrationError> {
+        let table_name = self.data.migration_object_name;
+        let rebuild_table_name = format!("{}_{}", table_name, REBUILD_SUFFIX);
+
+        // counter columns can only be incremented, so their values can't be copied
+        if self.data.has_counter_fields() {
+            return Err(MigrationError::CounterTableRebuild(table_name.clone()));
+        }
+
+        if !swap && self.data.is_rebuilt() {
+            plan.warn(format!(
+                "Table {} is already rebuilt into {}. Use `--swap-rebuilt-tables` to swap them",
+                table_name, rebuild_table_name
+            ));
+
+            return Ok(());
+        }
+
+        let copied_fields = self.data.rebuild_copied_fields();
+
+        for (field_name, _, _) in self.data.current_code_schema.fields.iter() {
+            if !copied_fields.contains(field_name) {
+                plan.warn(format!(
+                    "Field {} of {} is new or has changed type and will not be copied",
+                    field_name, table_name
+                ));
+            }
+        }
+
+        plan.warn(format!(
+            "Rows of {} are copied with row markers that don't expire, so rows whose values expire are kept with \
+            their primary key",
+            table_name
+        ));
+
+        // rows of previous rebuild could be stale, so rebuild table is always filled from scratch
+        if self.data.rebuild_db_schema.is_some() {
+            let cql = format!("DROP TABLE IF EXISTS {}", rebuild_table_name);
+            plan.push(&rebuild_table_name, ModelType::Table, StepKind::Drop, &cql);
+        }
+
+        plan.push(
+            &rebuild_table_name,
+            ModelType::Table,
+            StepKind::Create,
+            &self.create_table_cql(&rebuild_table_name),
+        );
+        self.push_copy(plan, table_name, &rebuild_table_name, &copied_fields);
+
+        if !swap {
+            return Ok(());
+        }
+
+        let cql = format!("DROP TABLE IF EXISTS {}", table_name);
+
+        // values of removed columns are lost with original table, so it's dropped only once all their drops are allowed
+        let blocked_fields: Vec<&String> = self
+            .data
+            .rebuild_discarded_fields()
+            .into_iter()
+            .filter(|field_name| !self.is_drop_allowed(field_name))
+            .collect();
+
+        if !blocked_fields.is_empty() {
+            for field_name in blocked_fields {
+                self.push_drop(plan, StepKind::RemoveFields, field_name, &cql);
+            }
+
+            plan.warn(format!(
+                "Table {} is not swapped with {} until drops of its removed columns are allowed",
+                table_name, rebuild_table_name
+            ));
+
+            return Ok(());
+        }
+
+        self.push(plan, StepKind::Drop, &cql);
+        self.push(plan, StepKind::Create, &self.create_table_cql(table_name));
+
+        let code_fields = self
+            .data
+            .current_code_schema
+            .fields
+            .iter()
+            .map(|(field_name, _, _)| field_name.clone())
+            .collect::<Vec<String>>();
+        self.push_copy(plan, &rebuild_table_name, table_name, &code_fields);
+
+        self.push_global_indexes(
+            plan,
+            &index_targets(&self.data.current_code_schema.global_secondary_indexes),
+        );
+        self.push_local_indexes(
+            plan,
+            &index_targets(&self.data.current_code_schema.local_secondary_indexes),
+        );
+        self.push_vector_indexes(
+            plan,
+            &index_targets(&self.data.current_code_schema.vector_indexes),
+            vector_index_class,
+        );
+        self.push_indexes(plan, &self.data.current_code_schema.indexes.iter().collect::<Vec<_>>());
+
+        let cql = format!("DROP TABLE IF EXISTS {}", rebuild_table_name);
+        plan.push(&rebuild_table_name, ModelType::Table, StepKind::Drop, &cql);
+
+        Ok(())
+    }
+
+    /// Pushes copy of rows into `to` table followed by check that all of them are there, so that tables are dropped
+    /// only after copy is verified. Rows are created by their primary key, and values of other `fields` are copied
+    /// column by column with their TTL and write time.
+    fn push_copy(&self, plan: &mut MigrationPlan, from: &str, to: &str, fields: &[String]) {
+        let code_schema = self.data.current_code_schema;
+        let primary_key = [code_schema.partition_keys.clone(), code_schema.clustering_keys.clone()].concat();
+
+        plan.push_copy(
+            to,
+            &find_all_query(from, &primary_key),
+            &insert_query(to, &primary_key),
+            None,
+        );
+        plan.push(
+            to,
+            ModelType::Table,
+            StepKind::VerifyCopy,
+            &find_all_query(to, &primary_key),
+        );
+
+        for field in fields.iter().filter(|field| !primary_key.contains(field)) {
+            // `TTL` and `WRITETIME` can't be selected for non-frozen collections
+            let with_ttl = !is_multi_cell(&code_schema.types_by_name[field]);
+
+            plan.push_copy(
+                to,
+                &find_column_values_query(from, field, &primary_key, with_ttl, false),
+                &update_column_query(to, field, &primary_key, with_ttl),
+                Some(field),
+            );
+        }
+    }
+
+    pub(crate) fn plan_field_added_migration(&self, plan: &mut MigrationPlan) {
+        if self.data.migration_object_type == ModelType::Table {
+            self.plan_table_field_added_migration(plan);
+        } else {
+            self.plan_udt_field_added_migration(plan);
+        }
+    }
+
+    fn plan_table_field_added_migration(&self, plan: &mut MigrationPlan) {
+        let add_fields_clause = self
+            .data
+            .diff
+            .new_fields
+            .iter()
+            .map(|(field_name, field_type)| format!("{} {}", quote_identifier(field_name), field_type))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let cql = format!(
+            "ALTER {} {} ADD ({})",
+            self.data.migration_object_type, self.data.migration_object_name, add_fields_clause,
+        );
+
+        self.push(plan, StepKind::AddFields, &cql);
+    }
+
+    fn plan_udt_field_added_migration(&self, plan: &mut MigrationPlan) {
+        for (field_name, field_type) in self.data.diff.new_fields.iter() {
+            let cql = format!(
+                "ALTER TYPE {} ADD {} {}",
+                self.data.migration_object_name,
+                quote_identifier(field_name),
+                field_type
+            );
+
+            self.push(plan, StepKind::AddFields, &cql);
+        }
+    }
+
+    pub(crate) fn plan_field_removed_migration(&self, plan: &mut MigrationPlan) {
+        let (allowed_fields, blocked_fields): (Vec<String>, Vec<String>) = self
+            .data
+            .diff
+            .removed_fields
+            .iter()
+            .cloned()
+            .partition(|field| self.is_drop_allowed(field));
+
+        if !allowed_fields.is_empty() {
+            let cql = format!(
+                "ALTER {} {} DROP ({})",
+                self.data.migration_object_type,
+                self.data.migration_object_name,
+                quoted(&allowed_fields),
+            );
+
+            self.push(plan, StepKind::RemoveFields, &cql);
+        }
+
+        for field in blocked_fields {
+            let cql = format!(
+                "ALTER {} {} DROP {}",
+                self.data.migration_object_type,
+                self.data.migration_object_name,
+                quote_identifier(&field),
+            );
+
+            self.push_drop(plan, StepKind::RemoveFields, &field, &cql);
+        }
+    }
+
+    /// Primary key and UDT fields are renamed in place. Regular columns can't be renamed, so new column is added,
+    /// values of old column are copied into it with their TTL and write time, and old column is dropped if its drop
+    /// is allowed. Values are copied with `UPDATE`, so rows without value of old column are not touched.
+    pub(crate) fn plan_field_renamed_migration(&self, plan: &mut MigrationPlan) -> Result<(), MigrationError> {
+        let db_schema = self.data.current_db_schema;
+        let code_schema = self.data.current_code_schema;
+        let object_name = self.data.migration_object_name;
+
+        for (old_name, new_name) in self.data.diff.renamed_fields.iter() {
+            let is_primary_key =
+                db_schema.partition_keys.contains(old_name) || db_schema.clustering_keys.contains(old_name);
+
+            if self.data.migration_object_type == ModelType::Udt || is_primary_key {
+                let object = match self.data.migration_object_type {
+                    ModelType::Udt => "TYPE",
+                    _ => "TABLE",
+                };
+                let cql = format!(
+                    "ALTER {} {} RENAME {} TO {}",
+                    object,
+                    object_name,
+                    quote_identifier(old_name),
+                    quote_identifier(new_name)
+                );
+
+                self.push(plan, StepKind::RenameField, &cql);
+
+                continue;
+            }
+
+            let old_type = &db_schema.types_by_name[old_name];
+
+            // counter columns can only be incremented, so their values can't be copied
+            if is_counter(old_type) {
+                return Err(MigrationError::CounterFieldRename(
+                    object_name.clone(),
+                    old_name.clone(),
+                ));
+            }
+
+            let is_static = code_schema.static_columns.contains(new_name);
+
+            // column could be added by previous run that failed before old column was dropped
+            if !db_schema.contains_field(new_name) {
+                let cql = format!(
+                    "ALTER {} {} ADD {} {}{}",
+                    self.data.migration_object_type,
+                    object_name,
+                    quote_identifier(new_name),
+                    code_schema.types_by_name[new_name],
+                    if is_static { " static" } else { "" }
+                );
+
+                self.push(plan, StepKind::AddFields, &cql);
+            }
+
+            // static columns are written with partition key only, so they are selected once per partition
+            let keys = if is_static {
+                db_schema.partition_keys.clone()
+            } else {
+                [db_schema.partition_keys.clone(), db_schema.clustering_keys.clone()].concat()
+            };
+
+            // `TTL` and `WRITETIME` can't be selected for non-frozen collections
+            let with_ttl = !is_multi_cell(old_type);
+
+            plan.push_copy(
+                object_name,
+                &find_column_values_query(object_name, old_name, &keys, with_ttl, is_static),
+                &update_column_query(object_name, new_name, &keys, with_ttl),
+                Some(old_name),
+            );
+
+            let cql = format!(
+                "ALTER {} {} DROP {}",
+                self.data.migration_object_type,
+                object_name,
+                quote_identifier(old_name)
+            );
+
+            self.push_drop(plan, StepKind::RemoveFields, old_name, &cql);
+        }
+
+        Ok(())
+    }
+
+    /// Columns with changed type are dropped and added again with new type. Their values are lost, so each column is
+    /// added only if its drop is allowed, and its drop is reported as blocked otherwise.
+    pub(crate) fn plan_field_type_changed_migration(&self, plan: &mut MigrationPlan) {
+        for (field_name, _, field_type) in self.data.diff.changed_field_types.iter() {
+            let cql = format!(
+                "ALTER {} {} DROP {}",
+                self.data.migration_object_type,
+                self.data.migration_object_name,
+                quote_identifier(field_name),
+            );
+
+            self.push_drop(plan, StepKind::RemoveFields, field_name, &cql);
+
+            if !self.is_drop_allowed(field_name) {
+                continue;
+            }
+
+            let cql = format!(
+                "ALTER {} {} ADD {} {}",
+                self.data.migration_object_type,
+                self.data.migration_object_name,
+                quote_identifier(field_name),
+                field_type
+            );
+
+            self.push(plan, StepKind::ChangeFieldType, &cql);
+        }
+    }
+
+    pub(crate) fn plan_global_index_added_migration(&self, plan: &mut MigrationPlan) {
+        self.push_global_indexes(plan, &self.data.diff.new_global_secondary_indexes);
+    }
+
+    fn push_global_indexes(&self, plan: &mut MigrationPlan, column_names: &[String]) {
+        for column_name in column_names {
+            // index can be named in `indexes` model argument
+            let index_name: String = self
+                .data
+                .current_code_schema
+                .global_secondary_indexes
+                .iter()
+                .find(|(name, target)| target == column_name && !name.is_empty())
+                .map_or_else(|| self.data.construct_index_name(column_name), |(name, _)| name.clone());
+
+            let cql = format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} ({})",
+                index_name,
+                self.data.migration_object_name,
+                quote_identifier(column_name),
+            );
+
+            self.push(plan, StepKind::AddGlobalIndex, &cql);
+        }
+    }
+
+    pub(crate) fn plan_global_index_removed_migration(&self, plan: &mut MigrationPlan) {
+        for (index, _) in &self.data.diff.removed_global_secondary_indexes {
+            let cql = format!("DROP INDEX {}", index);
+
+            self.push_drop(plan, StepKind::RemoveGlobalIndex, index, &cql);
+        }
+    }
+
+    pub(crate) fn plan_local_index_added_migration(&self, plan: &mut MigrationPlan) {
+        self.push_local_indexes(plan, &self.data.diff.new_local_secondary_indexes);
+    }
+
+    fn push_local_indexes(&self, plan: &mut MigrationPlan, column_names: &[String]) {
+        for local_secondary_index in column_names {
+            let partition_keys = self.data.current_code_schema.partition_keys.clone();
+
+            let mut idx_name = partition_keys.join("_");
+            idx_name.push('_');
+            idx_name.push_str(local_secondary_index);
+
+            let index_name: String = self.data.construct_index_name(&idx_name);
+            let pks = quoted(&partition_keys);
+            let cql = format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} (({}), {})",
+                index_name,
+                self.data.migration_object_name,
+                pks,
+                quote_identifier(local_secondary_index),
+            );
+
+            self.push(plan, StepKind::AddLocalIndex, &cql);
+        }
+    }
+
+    pub(crate) fn plan_local_index_removed_migration(&self, plan: &mut MigrationPlan) {
+        for (index, _) in &self.data.diff.removed_local_secondary_indexes {
+            let cql = format!("DROP INDEX {}", index);
+
+            self.push_drop(plan, StepKind::RemoveLocalIndex, index, &cql);
+        }
+    }
+
+    pub(crate) fn plan_vector_index_added_migration(&self, plan: &mut MigrationPlan, vector_index_class: &str) {
+        self.push_vector_indexes(plan, &self.data.diff.new_vector_indexes, vector_index_class);
+    }
+
+    fn push_vector_indexes(&self, plan: &mut MigrationPlan, column_names: &[String], vector_index_class: &str) {
+        for column_name in column_names {
+            let index_name: String = self.data.construct_index_name(column_name);
+
+            let cql = format!(
+                "CREATE CUSTOM INDEX IF NOT EXISTS {} ON {} ({}) USING '{}'",
+                index_name,
+                self.data.migration_object_name,
+                quote_identifier(column_name),
+                vector_index_class,
+            );
+
+            self.push(plan, StepKind::AddVectorIndex, &cql);
+        }
+    }
+
+    pub(crate) fn plan_vector_index_removed_migration(&self, plan: &mut MigrationPlan) {
+        for (index, _) in &self.data.diff.removed_vector_indexes {
+            let cql = format!("DROP INDEX {}", index);
+
+            self.push_drop(plan, StepKind::RemoveVectorIndex, index, &cql);
+        }
+    }
+
+    pub(crate) fn plan_index_added_migration(&self, plan: &mut MigrationPlan) {
+        // changed index is recreated only if its drop is allowed
+        let (indexes, blocked_indexes): (Vec<&IndexDefinition>, Vec<&IndexDefinition>) =
+            self.data.diff.new_indexes.iter().partition(|index| {
+                let index_name = index.index_name(self.data.migration_object_name);
+
+                let is_removed = self
+                    .data
+                    .diff
+                    .removed_indexes
+                    .iter()
+                    .any(|removed| removed.name == index_name);
+
+                !is_removed || self.is_drop_allowed(&index_name)
+            });
+
+        self.push_indexes(plan, &indexes);
+
+        for index in blocked_indexes {
+            let index_name = index.index_name(self.data.migration_object_name);
+
+            plan.push_blocked(
+                self.data.migration_object_name,
+                self.data.migration_object_type,
+                StepKind::AddIndex,
+                &self.create_index_cql(index),
+                allow_drop_name(self.data.migration_object_name, &index_name),
+            );
+        }
+    }
+
+    fn push_indexes(&self, plan: &mut MigrationPlan, indexes: &[&IndexDefinition]) {
+        for index in indexes {
+            self.push(plan, StepKind::AddIndex, &self.create_index_cql(index));
+        }
+    }
+
+    fn create_index_cql(&self, index: &IndexDefinition) -> String {
+        let index_name = index.index_name(self.data.migration_object_name);
+
+        let mut cql = match &index.class_name {
+            Some(class_name) => format!(
+                "CREATE CUSTOM INDEX IF NOT EXISTS {} ON {} ({}) USING '{}'",
+                index_name,
+                self.data.migration_object_name,
+                index.target(),
+                class_name,
+            ),
+            None => format!(
+                "CREATE INDEX IF NOT EXISTS {} ON {} ({})",
+                index_name,
+                self.data.migration_object_name,
+                index.target(),
+            ),
+        };
+
+        if !index.options.is_empty() {
+            let options = TableOptionValue::Map(index.options.clone());
+            cql.push_str(&format!(" WITH OPTIONS = {}", options));
+        }
+
+        cql
+    }
+
+    pub(crate) fn plan_index_removed_migration(&self, plan: &mut MigrationPlan) {
+        for index in &self.data.diff.removed_indexes {
+            let cql = format!("DROP INDEX {}", index.name);
+
+            self.push_drop(plan, StepKind::RemoveIndex, &index.name, &cql);
+        }
+    }
+
+    pub(crate) fn plan_table_options_change_migration(&self, plan: &mut MigrationPlan) {
+        if self.data.migration_object_type == ModelType::Udt {
+            return;
+        }
+
+        if self.data.migration_object_type == ModelType::Table && self.data.clustering_order_changed() {
+            plan.warn(format!(
+                "Clustering order of {} differs from the database, but it can't be altered. Use `--rebuild-tables` \
+                to rebuild the table",
+                self.data.migration_object_name
+            ));
+        }
+
+        if let Some(alter_table_options) = self.data.changed_table_options().alter_clause() {
+            let object = match self.data.migration_object_type {
+                ModelType::MaterializedView => "MATERIALIZED VIEW",
+                _ => "TABLE",
+            };
+
+            let cql = format!(
+                "ALTER {} {} WITH {}",
+                object, self.data.migration_object_name, alter_table_options
+            );
+
+            self.push(plan, StepKind::ChangeTableOptions, &cql);
+        }
+    }
+}
+
+fn index_targets(indexes: &[(IndexName, String)]) -> Vec<String> {
+    indexes.iter().map(|(_, target)| target.clone()).collect()
+}
+
+fn is_counter(field_type: &str) -> bool {
+    field_type.trim().eq_ignore_ascii_case("counter")
+}
+
+/// Non-frozen collections are stored as multiple cells, each with its own TTL and write time.
+fn is_multi_cell(field_type: &str) -> bool {
+    let field_type = field_type.trim().to_lowercase();
+
+    ["list<", "set<", "map<"]
+        .iter()
+        .any(|collection| field_type.starts_with(collection))
+}
+
+/// Comma separated column names, quoted where CQL requires it.
+fn quoted(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|name| quote_identifier(name))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use charybdis_parser::schema::SchemaObject;
+
+    fn table(fields: &[(&str, &str)], partition_keys: &[&str], clustering_keys: &[&str]) -> SchemaObject {
+        SchemaObject {
+            fields: fields
+                .iter()
+                .map(|(name, field_type)| (name.to_string(), field_type.to_string(), false))
+                .collect(),
+            field_names: fields.iter().map(|(name, _)| name.to_string()).collect(),
+            types_by_name: fields
+                .iter()
+                .map(|(name, field_type)| (name.to_string(), field_type.to_string()))
+                .collect(),
+            partition_keys: partition_keys.iter().map(|key| key.to_string()).collect(),
+            clustering_keys: clustering_keys.iter().map(|key| key.to_string()).collect(),
+            ..SchemaObject::default()
+        }
+    }
+
+    fn plan_rebuild(
+        code_table: &SchemaObject,
+        db_table: &SchemaObject,
+        rebuild_table: Option<&SchemaObject>,
+        swap: bool,
+        args: &Args,
+    ) -> Result<MigrationPlan, MigrationError> {
+        let name = "posts".to_string();
+        let data = ModelData::new(&name, ModelType::Table, code_table, db_table).with_rebuild_table(rebuild_table);
+        let mut plan = MigrationPlan::new();
+
+        ModelPlanner::new(&data, args).plan_table_rebuild_migration(&mut plan, swap, "StorageAttachedIndex")?;
+
+        Ok(plan)
+    }
+
+    fn steps(plan: &MigrationPlan) -> Vec<(String, StepKind)> {
+        plan.steps
+            .iter()
+            .map(|step| (step.object_name.clone(), step.kind))
+            .collect()
+    }
+
+    fn fields() -> [(&'static str, &'static str); 3] {
+        [("id", "uuid"), ("created_at", "timestamp"), ("title", "text")]
+    }
+
+    #[test]
+    fn swap_drops_tables_after_verified_copies() {
+        let code_table = table(&fields(), &["id"], &["created_at"]);
+        let db_table = table(&fields(), &["id"], &[]);
+
+        let plan = plan_rebuild(&code_table, &db_table, None, true, &Args::default()).unwrap();
+
+        assert_eq!(
+            steps(&plan),
+            vec![
+                ("posts_rebuild".to_string(), StepKind::Create),
+                ("posts_rebuild".to_string(), StepKind::CopyRows),
+                ("posts_rebuild".to_string(), StepKind::VerifyCopy),
+                ("posts_rebuild".to_string(), StepKind::CopyRows),
+                ("posts".to_string(), StepKind::Drop),
+                ("posts".to_string(), StepKind::Create),
+                ("posts".to_string(), StepKind::CopyRows),
+                ("posts".to_string(), StepKind::VerifyCopy),
+                ("posts".to_string(), StepKind::CopyRows),
+                ("posts_rebuild".to_string(), StepKind::Drop),
+            ]
+        );
+        assert_eq!(plan.steps[1].cql, "SELECT id, created_at FROM posts");
+        assert_eq!(
+            plan.steps[1].insert_cql.as_deref(),
+            Some("INSERT INTO posts_rebuild (id, created_at) VALUES (:id, :created_at)")
+        );
+        assert_eq!(plan.steps[2].cql, "SELECT id, created_at FROM posts_rebuild");
+        assert_eq!(
+            plan.steps[3].cql,
+            "SELECT TTL(title), WRITETIME(title), title, id, created_at FROM posts"
+        );
+        assert_eq!(
+            plan.steps[3].insert_cql.as_deref(),
+            Some(
+                "UPDATE posts_rebuild USING TTL :ttl AND TIMESTAMP :writetime SET title = :title \
+                WHERE id = :id AND created_at = :created_at"
+            )
+        );
+        assert_eq!(plan.steps[3].required_column.as_deref(), Some("title"));
+        assert_eq!(
+            plan.steps[8].cql,
+            "SELECT TTL(title), WRITETIME(title), title, id, created_at FROM posts_rebuild"
+        );
+        assert_eq!(plan.warnings.len(), 1);
+    }
+
+    #[test]
+    fn rebuilt_table_is_not_rebuilt_again_without_swap() {
+        let code_table = table(&fields(), &["id"], &["created_at"]);
+        let db_table = table(&fields(), &["id"], &[]);
+        let rebuild_table = table(&fields(), &["id"], &["created_at"]);
+
+        let plan = plan_rebuild(&code_table, &db_table, Some(&rebuild_table), false, &Args::default()).unwrap();
+
+        assert!(plan.steps.is_empty());
+        assert_eq!(plan.warnings.len(), 1);
+    }
+
+    #[test]
+    fn stale_rebuild_table_is_dropped_first() {
+        let code_table = table(&fields(), &["id"], &["created_at"]);
+        let db_table = table(&fields(), &["id"], &[]);
+        let rebuild_table = table(&fields(), &["id"], &["title"]);
+
+        let plan = plan_rebuild(&code_table, &db_table, Some(&rebuild_table), false, &Args::default()).unwrap();
+
+        assert_eq!(
+            steps(&plan),
+            vec![
+                ("posts_rebuild".to_string(), StepKind::Drop),
+                ("posts_rebuild".to_string(), StepKind::Create),
+                ("posts_rebuild".to_string(), StepKind::CopyRows),
+                ("posts_rebuild".to_string(), StepKind::VerifyCopy),
+                ("posts_rebuild".to_string(), StepKind::CopyRows),
+            ]
+        );
+    }
+
+    #[test]
+    fn counter_table_rebuild_is_error() {
+        let code_table = table(
+            &[("id", "uuid"), ("day", "date"), ("views", "counter")],
+            &["id"],
+            &["day"],
+        );
+        let db_table = table(
+            &[("id", "uuid"), ("day", "date"), ("views", "counter")],
+            &["id", "day"],
+            &[],
+        );
+
+        let result = plan_rebuild(&code_table, &db_table, None, true, &Args::default());
+
+        assert!(matches!(result, Err(MigrationError::CounterTableRebuild(_))));
+    }
+
+    #[test]
+    fn swap_waits_for_allowed_drops_of_removed_columns() {
+        let code_table = table(&[("id", "uuid"), ("created_at", "timestamp")], &["id"], &["created_at"]);
+        let db_table = table(&fields(), &["id"], &[]);
+
+        let plan = plan_rebuild(&code_table, &db_table, None, true, &Args::default()).unwrap();
+
+        assert_eq!(
+            steps(&plan),
+            vec![
+                ("posts_rebuild".to_string(), StepKind::Create),
+                ("posts_rebuild".to_string(), StepKind::CopyRows),
+                ("posts_rebuild".to_string(), StepKind::VerifyCopy),
+            ]
+        );
+        assert_eq!(plan.blocked.len(), 1);
+        assert_eq!(plan.blocked[0].allow_drop, "posts.title");
+        assert_eq!(plan.blocked[0].step.kind, StepKind::RemoveFields);
+        assert_eq!(plan.blocked[0].step.cql, "DROP TABLE IF EXISTS posts");
+        assert_eq!(plan.warnings.len(), 2);
+
+        let args = Args {
+            allow_drop: vec!["posts.title".to_string()],
+            ..Args::default()
+        };
+        let plan = plan_rebuild(&code_table, &db_table, None, true, &args).unwrap();
+
+        assert!(plan.blocked.is_empty());
+        assert_eq!(plan.steps.len(), 8);
+        assert_eq!(plan.steps[3].cql, "DROP TABLE IF EXISTS posts");
+    }
+
+    #[test]
+    fn rebuild_without_swap_keeps_removed_columns() {
+        let code_table = table(&[("id", "uuid"), ("created_at", "timestamp")], &["id"], &["created_at"]);
+        let db_table = table(&fields(), &["id"], &[]);
+
+        let plan = plan_rebuild(&code_table, &db_table, None, false, &Args::default()).unwrap();
+
+        assert_eq!(plan.steps.len(), 3);
+        assert!(plan.blocked.is_empty());
+    }
+
+    fn plan_removal(code_table: &SchemaObject, db_table: &SchemaObject, args: &Args) -> MigrationPlan {
+        let name = "posts".to_string();
+        let data = ModelData::new(&name, ModelType::Table, code_table, db_table);
+        let mut plan = MigrationPlan::new();
+
+        ModelPlanner::new(&data, args).plan_field_removed_migration(&mut plan);
+
+        plan
+    }
+
+    #[test]
+    fn removed_column_drops_are_blocked_unless_allowed() {
+        let code_table = table(&[("id", "uuid")], &["id"], &[]);
+        let db_table = table(&[("id", "uuid"), ("title", "text"), ("body", "text")], &["id"], &[]);
+
+        let plan = plan_removal(&code_table, &db_table, &Args::default());
+
+        assert!(plan.steps.is_empty());
+        assert_eq!(
+            plan.blocked_drops(),
+            vec![
+                ("posts.body".to_string(), StepKind::RemoveFields),
+                ("posts.title".to_string(), StepKind::RemoveFields),
+            ]
+        );
+
+        let args = Args {
+            allow_drop: vec!["posts.body".to_string()],
+            ..Args::default()
+        };
+        let plan = plan_removal(&code_table, &db_table, &args);
+
+        assert_eq!(steps(&plan), vec![("posts".to_string(), StepKind::RemoveFields)]);
+        assert_eq!(plan.steps[0].cql, "ALTER Table posts DROP (body)");
+        assert_eq!(
+            plan.blocked_drops(),
+            vec![("posts.title".to_string(), StepKind::RemoveFields)]
+        );
+
+        let args = Args {
+            allow_destructive: true,
+            ..Args::default()
+        };
+        let plan = plan_removal(&code_table, &db_table, &args);
+
+        assert_eq!(plan.steps.len(), 1);
+        assert!(plan.blocked.is_empty());
+    }
+
+    fn plan_rename(
+        code_table: &SchemaObject,
+        db_table: &SchemaObject,
+        args: &Args,
+    ) -> Result<MigrationPlan, MigrationError> {
+        let name = "posts".to_string();
+        let data = ModelData::new(&name, ModelType::Table, code_table, db_table);
+        let mut plan = MigrationPlan::new();
+
+        ModelPlanner::new(&data, args).plan_field_renamed_migration(&mut plan)?;
+
+        Ok(plan)
+    }
+
+    fn renamed(mut table: SchemaObject, from: &str, to: &str) -> SchemaObject {
+        table.renamed_fields.insert(to.to_string(), from.to_string());
+        table
+    }
+
+    #[test]
+    fn renamed_column_is_copied_with_ttl_and_write_time() {
+        let code_table = renamed(
+            table(
+                &[("id", "uuid"), ("created_at", "timestamp"), ("headline", "text")],
+                &["id"],
+                &["created_at"],
+            ),
+            "title",
+            "headline",
+        );
+        let db_table = table(&fields(), &["id"], &["created_at"]);
+        let args = Args {
+            allow_destructive: true,
+            ..Args::default()
+        };
+
+        let plan = plan_rename(&code_table, &db_table, &args).unwrap();
+
+        assert_eq!(
+            steps(&plan),
+            vec![
+                ("posts".to_string(), StepKind::AddFields),
+                ("posts".to_string(), StepKind::CopyRows),
+                ("posts".to_string(), StepKind::RemoveFields),
+            ]
+        );
+        assert_eq!(plan.steps[0].cql, "ALTER Table posts ADD headline text");
+        assert_eq!(
+            plan.steps[1].cql,
+            "SELECT TTL(title), WRITETIME(title), title, id, created_at FROM posts"
+        );
+        assert_eq!(
+            plan.steps[1].insert_cql.as_deref(),
+            Some(
+                "UPDATE posts USING TTL :ttl AND TIMESTAMP :writetime SET headline = :headline \
+                WHERE id = :id AND created_at = :created_at"
+            )
+        );
+        assert_eq!(plan.steps[1].required_column.as_deref(), Some("title"));
+        assert_eq!(plan.steps[2].cql, "ALTER Table posts DROP title");
+    }
+
+    #[test]
+    fn renamed_collection_is_copied_without_ttl() {
+        let code_table = renamed(
+            table(&[("id", "uuid"), ("labels", "set<text>")], &["id"], &[]),
+            "tags",
+            "labels",
+        );
+        let db_table = table(&[("id", "uuid"), ("tags", "set<text>")], &["id"], &[]);
+
+        let plan = plan_rename(&code_table, &db_table, &Args::default()).unwrap();
+
+        assert_eq!(plan.steps[1].cql, "SELECT tags, id FROM posts");
+        assert_eq!(
+            plan.steps[1].insert_cql.as_deref(),
+            Some("UPDATE posts SET labels = :labels WHERE id = :id")
+        );
+    }
+
+    #[test]
+    fn renamed_static_column_is_copied_once_per_partition() {
+        let mut code_table = renamed(
+            table(
+                &[("id", "uuid"), ("created_at", "timestamp"), ("owner", "text")],
+                &["id"],
+                &["created_at"],
+            ),
+            "author",
+            "owner",
+        );
+        code_table.static_columns.push("owner".to_string());
+        let mut db_table = table(
+            &[("id", "uuid"), ("created_at", "timestamp"), ("author", "text")],
+            &["id"],
+            &["created_at"],
+        );
+        db_table.static_columns.push("author".to_string());
+
+        let plan = plan_rename(&code_table, &db_table, &Args::default()).unwrap();
+
+        assert_eq!(plan.steps[0].cql, "ALTER Table posts ADD owner text static");
+        assert_eq!(
+            plan.steps[1].cql,
+            "SELECT DISTINCT TTL(author), WRITETIME(author), author, id FROM posts"
+        );
+        assert_eq!(
+            plan.steps[1].insert_cql.as_deref(),
+            Some("UPDATE posts USING TTL :ttl AND TIMESTAMP :writetime SET owner = :owner WHERE id = :id")
+        );
+    }
+
+    #[test]
+    fn renamed_column_drop_is_blocked() {
+        let code_table = renamed(
+            table(
+                &[("id", "uuid"), ("created_at", "timestamp"), ("headline", "text")],
+                &["id"],
+                &["created_at"],
+            ),
+            "title",
+            "headline",
+        );
+        let db_table = table(&fields(), &["id"], &["created_at"]);
+
+        let plan = plan_rename(&code_table, &db_table, &Args::default()).unwrap();
+
+        assert_eq!(
+            steps(&plan),
+            vec![
+                ("posts".to_string(), StepKind::AddFields),
+                ("posts".to_string(), StepKind::CopyRows),
+            ]
+        );
+        assert_eq!(plan.blocked.len(), 1);
+        assert_eq!(plan.blocked[0].step.cql, "ALTER Table posts DROP title");
+        assert_eq!(plan.blocked[0].allow_drop, "posts.title");
+    }
+
+    #[test]
+    fn renamed_key_is_renamed_in_place() {
+        let code_table = renamed(
+            table(
+                &[("id", "uuid"), ("published_at", "timestamp"), ("title", "text")],
+                &["id"],
+                &["published_at"],
+            ),
+            "created_at",
+            "published_at",
+        );
+        let db_table = table(&fields(), &["id"], &["created_at"]);
+
+        let plan = plan_rename(&code_table, &db_table, &Args::default()).unwrap();
+
+        assert_eq!(steps(&plan), vec![("posts".to_string(), StepKind::RenameField)]);
+        assert_eq!(plan.steps[0].cql, "ALTER TABLE posts RENAME created_at TO published_at");
+    }
+
+    #[test]
+    fn renamed_counter_is_error() {
+        let code_table = renamed(
+            table(&[("id", "uuid"), ("views", "counter")], &["id"], &[]),
+            "hits",
+            "views",
+        );
+        let db_table = table(&[("id", "uuid"), ("hits", "counter")], &["id"], &[]);
+
+        let result = plan_rename(&code_table, &db_table, &Args::default());
+
+        assert!(matches!(result, Err(MigrationError::CounterFieldRename(_, _))));
+    }
+
+    #[test]
+    fn changed_field_type_is_replaced_only_when_drop_is_allowed() {
+        let code_table = table(&[("id", "uuid"), ("views", "bigint")], &["id"], &[]);
+        let db_table = table(&[("id", "uuid"), ("views", "int")], &["id"], &[]);
+        let name = "posts".to_string();
+        let data = ModelData::new(&name, ModelType::Table, &code_table, &db_table);
+
+        let mut plan = MigrationPlan::new();
+        ModelPlanner::new(&data, &Args::default()).plan_field_type_changed_migration(&mut plan);
+
+        assert!(plan.steps.is_empty());
+        assert_eq!(plan.blocked.len(), 1);
+        assert_eq!(plan.blocked[0].allow_drop, "posts.views");
+        assert_eq!(plan.blocked[0].step.cql, "ALTER Table posts DROP views");
+
+        let args = Args {
+            allow_drop: vec!["posts.views".to_string()],
+            ..Args::default()
+        };
+        let mut plan = MigrationPlan::new();
+        ModelPlanner::new(&data, &args).plan_field_type_changed_migration(&mut plan);
+
+        assert!(plan.blocked.is_empty());
+        assert_eq!(
+            steps(&plan),
+            vec![
+                ("posts".to_string(), StepKind::RemoveFields),
+                ("posts".to_string(), StepKind::ChangeFieldType),
+            ]
+        );
+        assert_eq!(plan.steps[1].cql, "ALTER Table posts ADD views bigint");
+    }
+}
@@ -0,0 +1,690 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use charybdis_parser::schema::db_schema::DbSchema;
+use charybdis_parser::schema::secondary_indexes::{IndexDefinition, IndexTargetKind};
+use charybdis_parser::schema::table_options::TableOptionValue;
+use charybdis_parser::schema::SchemaObject;
+use colored::Colorize;
+
+use crate::errors::MigrationError;
+use crate::model::ModelType;
+use crate::report::OutputFormat;
+use crate::INTERNAL_TABLES;
+
+/// `charybdis::types` aliases of tuples with more than 2 elements.
+const TUPLE_ALIASES: [&str; 14] = [
+    "Tuple3", "Tuple4", "Tuple5", "Tuple6", "Tuple7", "Tuple8", "Tuple9", "Tuple10", "Tuple11", "Tuple12", "Tuple13",
+    "Tuple14", "Tuple15", "Tuple16",
+];
+
+/// Rust keywords that can't be used as field or module names. Names are suffixed with `_` instead of using raw
+/// identifiers, as `self`, `super`, `crate` and `Self` can't be raw.
+const RUST_KEYWORDS: [&str; 52] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do", "dyn", "else",
+    "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro", "match", "mod",
+    "move", "mut", "override", "priv", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+    "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Renders `#[charybdis_model]`, `#[charybdis_udt_model]` and `#[charybdis_view_model]` structs from the database
+/// schema, one file per object, together with `mod.rs` declaring them.
+pub struct Modelizer<'a> {
+    db_schema: &'a DbSchema,
+    udt_names: HashSet<String>,
+    format: OutputFormat,
+}
+
+/// Model file rendered from the database schema.
+pub struct ModelFile {
+    pub module_name: String,
+    pub model_type: ModelType,
+    pub content: String,
+}
+
+impl<'a> Modelizer<'a> {
+    pub fn new(db_schema: &'a DbSchema) -> Self {
+        let udt_names = db_schema.udts.keys().map(|name| name.to_lowercase()).collect();
+
+        Self {
+            db_schema,
+            udt_names,
+            format: OutputFormat::default(),
+        }
+    }
+
+    /// Output format of progress messages.
+    pub fn format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn render(&self) -> Vec<ModelFile> {
+        let mut files = vec![];
+
+        for (name, schema_object) in sorted(&self.db_schema.udts) {
+            files.push(self.render_model(name, ModelType::Udt, schema_object));
+        }
+
+        for (name, schema_object) in sorted(&self.db_schema.tables) {
+            if INTERNAL_TABLES.contains(&name.as_str()) {
+                continue;
+            }
+
+            files.push(self.render_model(name, ModelType::Table, schema_object));
+        }
+
+        for (name, schema_object) in sorted(&self.db_schema.materialized_views) {
+            files.push(self.render_model(name, ModelType::MaterializedView, schema_object));
+        }
+
+        files
+    }
+
+    /// Writes rendered models into `models_dir`. Existing files are kept unless `overwrite` is set.
+    pub fn write(&self, models_dir: &Path, overwrite: bool) -> Result<Vec<PathBuf>, MigrationError> {
+        let files = self.render();
+        let mut written = vec![];
+
+        fs::create_dir_all(models_dir)
+            .map_err(|e| MigrationError::MigrationFileError(models_dir.display().to_string(), e))?;
+
+        for file in files.iter() {
+            let path = models_dir.join(format!("{}.rs", file.module_name));
+
+            if path.exists() && !overwrite {
+                progress!(
+                    self.format,
+                    "{} {}",
+                    "Skipping existing model file:".bright_yellow(),
+                    path.display()
+                );
+                continue;
+            }
+
+            fs::write(&path, &file.content)
+                .map_err(|e| MigrationError::MigrationFileError(path.display().to_string(), e))?;
+            written.push(path);
+        }
+
+        let mod_path = models_dir.join("mod.rs");
+        let mut mod_content = fs::read_to_string(&mod_path).unwrap_or_default();
+
+        for file in files.iter() {
+            let declaration = format!("pub mod {};", file.module_name);
+
+            if !mod_content.lines().any(|line| line.trim() == declaration) {
+                mod_content.push_str(&declaration);
+                mod_content.push('\n');
+            }
+        }
+
+        fs::write(&mod_path, mod_content)
+            .map_err(|e| MigrationError::MigrationFileError(mod_path.display().to_string(), e))?;
+
+        Ok(written)
+    }
+
+    fn render_model(&self, name: &str, model_type: ModelType, schema_object: &SchemaObject) -> ModelFile {
+        let struct_name = to_pascal_case(name);
+        let mut types = BTreeSet::new();
+        let mut udts = BTreeSet::new();
+        let mut fields = vec![];
+
+        for (field_name, cql_type) in self.ordered_fields(model_type, schema_object) {
+            match self.rust_type(&cql_type, &mut types, &mut udts) {
+                Ok(rust_type) => {
+                    let is_primary_key = schema_object.partition_keys.contains(&field_name)
+                        || schema_object.clustering_keys.contains(&field_name);
+
+                    let rust_name = rust_identifier(&field_name);
+
+                    if rust_name != field_name {
+                        fields.push(format!(
+                            "    #[charybdis(column = \"{}\")]",
+                            field_name.replace('"', "\\\"")
+                        ));
+                    }
+
+                    if is_primary_key {
+                        fields.push(format!("    pub {}: {},", rust_name, rust_type));
+                    } else {
+                        fields.push(format!("    pub {}: Option<{}>,", rust_name, rust_type));
+                    }
+                }
+                Err(unsupported) => {
+                    progress!(
+                        self.format,
+                        "{} {} {} {}",
+                        "Unsupported column type".bright_red(),
+                        unsupported.bright_yellow(),
+                        "for".bright_red(),
+                        format!("{}.{}", name, field_name).bright_yellow(),
+                    );
+                    fields.push(format!("    // TODO: unsupported column type `{}`", cql_type));
+                    fields.push(format!("    // pub {}: ,", rust_identifier(&field_name)));
+                }
+            }
+        }
+
+        let macro_name = match model_type {
+            ModelType::Udt => "charybdis_udt_model",
+            ModelType::Table => "charybdis_model",
+            ModelType::MaterializedView => "charybdis_view_model",
+        };
+
+        let mut content = String::new();
+
+        content.push_str(&format!("use charybdis::macros::{};\n", macro_name));
+
+        if !types.is_empty() {
+            let types = types.into_iter().collect::<Vec<&str>>();
+
+            if types.len() == 1 {
+                content.push_str(&format!("use charybdis::types::{};\n", types[0]));
+            } else {
+                content.push_str(&format!("use charybdis::types::{{{}}};\n", types.join(", ")));
+            }
+        }
+
+        content.push_str("use serde::{Deserialize, Serialize};\n");
+
+        for udt in udts {
+            content.push_str(&format!(
+                "use super::{}::{};\n",
+                module_name(&udt),
+                to_pascal_case(&udt)
+            ));
+        }
+
+        content.push('\n');
+        content.push_str(&self.macro_attribute(name, macro_name, model_type, schema_object));
+        content.push_str("#[derive(Serialize, Deserialize, Clone, Debug)]\n");
+        content.push_str(&format!("pub struct {} {{\n", struct_name));
+        content.push_str(&fields.join("\n"));
+        content.push_str("\n}\n");
+
+        ModelFile {
+            module_name: module_name(name),
+            model_type,
+            content,
+        }
+    }
+
+    fn macro_attribute(
+        &self,
+        name: &str,
+        macro_name: &str,
+        model_type: ModelType,
+        schema_object: &SchemaObject,
+    ) -> String {
+        match model_type {
+            ModelType::Udt => format!("#[{}(type_name = {})]\n", macro_name, name),
+            ModelType::Table | ModelType::MaterializedView => {
+                let mut args = vec![format!("table_name = {}", name)];
+
+                if model_type == ModelType::MaterializedView {
+                    args.push(format!("base_table = {}", schema_object.base_table));
+                }
+
+                // macro arguments reference rust field names
+                args.push(format!(
+                    "partition_keys = [{}]",
+                    rust_identifiers(&schema_object.partition_keys)
+                ));
+                args.push(format!(
+                    "clustering_keys = [{}]",
+                    rust_identifiers(&schema_object.clustering_keys)
+                ));
+
+                if !schema_object.static_columns.is_empty() {
+                    args.push(format!(
+                        "static_columns = [{}]",
+                        rust_identifiers(&schema_object.static_columns)
+                    ));
+                }
+
+                if model_type == ModelType::Table {
+                    let global_indexes = index_targets(&schema_object.global_secondary_indexes);
+                    let local_indexes = index_targets(&schema_object.local_secondary_indexes);
+
+                    args.push(format!("global_secondary_indexes = [{}]", global_indexes.join(", ")));
+                    args.push(format!("local_secondary_indexes = [{}]", local_indexes.join(", ")));
+
+                    if !schema_object.vector_indexes.is_empty() {
+                        let vector_indexes = index_targets(&schema_object.vector_indexes);
+                        args.push(format!("vector_indexes = [{}]", vector_indexes.join(", ")));
+                    }
+
+                    if !schema_object.indexes.is_empty() {
+                        let indexes = schema_object
+                            .indexes
+                            .iter()
+                            .map(index_declaration)
+                            .collect::<Vec<String>>();
+                        args.push(format!("indexes = [{}]", indexes.join(", ")));
+                    }
+                }
+
+                // other options are database defaults, so only clustering order is kept
+                if let Some(table_options) = schema_object.parsed_table_options().clustering_order_clause() {
+                    args.push(format!("table_options = r#\"\n        {}\n    \"#", table_options));
+                }
+
+                format!("#[{}(\n    {}\n)]\n", macro_name, args.join(",\n    "))
+            }
+        }
+    }
+
+    /// Primary key fields go first in key order, other fields keep the order from the database.
+    fn ordered_fields(&self, model_type: ModelType, schema_object: &SchemaObject) -> Vec<(String, String)> {
+        if model_type == ModelType::Udt {
+            return schema_object
+                .fields
+                .iter()
+                .map(|(name, cql_type, _)| (name.clone(), cql_type.clone()))
+                .collect();
+        }
+
+        let mut fields = vec![];
+
+        for key in schema_object
+            .partition_keys
+            .iter()
+            .chain(schema_object.clustering_keys.iter())
+        {
+            if let Some(cql_type) = schema_object.types_by_name.get(key) {
+                fields.push((key.clone(), cql_type.clone()));
+            }
+        }
+
+        for (name, cql_type, _) in schema_object.fields.iter() {
+            if !schema_object.partition_keys.contains(name) && !schema_object.clustering_keys.contains(name) {
+                fields.push((name.clone(), cql_type.clone()));
+            }
+        }
+
+        fields
+    }
+
+    /// Converts CQL type, e.g. `map<text, frozen<list<int>>>` into `charybdis::types` based rust type.
+    fn rust_type(
+        &self,
+        cql_type: &str,
+        types: &mut BTreeSet<&'static str>,
+        udts: &mut BTreeSet<String>,
+    ) -> Result<String, String> {
+        let cql_type = cql_type.trim();
+        let (name, args) = match cql_type.find('<') {
+            Some(start) if cql_type.ends_with('>') => (
+                &cql_type[..start],
+                split_type_args(&cql_type[start + 1..cql_type.len() - 1]),
+            ),
+            _ => (cql_type, vec![]),
+        };
+
+        let name = name.trim().to_lowercase();
+
+        if let ("vector", [element, dimensions]) = (name.as_str(), args.as_slice()) {
+            let dimensions = dimensions.trim();
+
+            if dimensions.parse::<usize>().is_err() {
+                return Err(cql_type.to_string());
+            }
+
+            let element = self.rust_type(element, types, udts)?;
+            types.insert("Vector");
+
+            return Ok(format!("Vector<{}, {}>", element, dimensions));
+        }
+
+        let collection = match (name.as_str(), args.len()) {
+            ("frozen", 1) => Some("Frozen"),
+            ("list", 1) => Some("List"),
+            ("set", 1) => Some("Set"),
+            ("map", 2) => Some("Map"),
+            ("tuple", 2) => Some("Tuple"),
+            ("tuple", arity @ 3..=16) => Some(TUPLE_ALIASES[arity - 3]),
+            _ => None,
+        };
+
+        if let Some(collection) = collection {
+            let args = args
+                .iter()
+                .map(|arg| self.rust_type(arg, types, udts))
+                .collect::<Result<Vec<String>, String>>()?;
+
+            types.insert(collection);
+
+            return Ok(format!("{}<{}>", collection, args.join(", ")));
+        }
+
+        if !args.is_empty() {
+            return Err(cql_type.to_string());
+        }
+
+        let native = match name.as_str() {
+            "ascii" => "Ascii",
+            "bigint" => "BigInt",
+            "blob" => "Blob",
+            "boolean" => "Boolean",
+            "counter" => "Counter",
+            "date" => "Date",
+            "decimal" => "Decimal",
+            "double" => "Double",
+            "duration" => "Duration",
+            "float" => "Float",
+            "inet" => "Inet",
+            "int" => "Int",
+            "smallint" => "SmallInt",
+            "text" | "varchar" => "Text",
+            "time" => "Time",
+            "timestamp" => "Timestamp",
+            "timeuuid" => "Timeuuid",
+            "tinyint" => "TinyInt",
+            "uuid" => "Uuid",
+            "varint" => "Varint",
+            udt => {
+                let udt = udt.trim_matches('"').to_string();
+
+                if self.udt_names.contains(&udt) {
+                    let rust_type = to_pascal_case(&udt);
+                    udts.insert(udt);
+
+                    return Ok(rust_type);
+                }
+
+                return Err(cql_type.to_string());
+            }
+        };
+
+        types.insert(native);
+
+        Ok(native.to_string())
+    }
+}
+
+fn sorted(schema_objects: &charybdis_parser::schema::SchemaObjects) -> Vec<(&String, &SchemaObject)> {
+    let mut objects = schema_objects.iter().collect::<Vec<(&String, &SchemaObject)>>();
+    objects.sort_by_key(|(name, _)| *name);
+
+    objects
+}
+
+fn index_targets(indexes: &[(String, String)]) -> Vec<String> {
+    indexes.iter().map(|(_, target)| rust_identifier(target)).collect()
+}
+
+/// Declaration of index in `indexes` model argument, e.g. `custom(keys(tags), using = "...", name = tags_idx)`.
+fn index_declaration(index: &IndexDefinition) -> String {
+    let field = rust_identifier(&index.column);
+    let target = match index.kind {
+        IndexTargetKind::Column => field,
+        IndexTargetKind::Keys => format!("keys({})", field),
+        IndexTargetKind::Values => format!("values({})", field),
+        IndexTargetKind::Entries => format!("entries({})", field),
+        IndexTargetKind::Full => format!("full({})", field),
+    };
+
+    let mut args = vec![target];
+
+    if let Some(class_name) = &index.class_name {
+        args.push(format!("using = \"{}\"", class_name));
+    }
+
+    args.push(format!("name = {}", index.name));
+
+    if !index.options.is_empty() {
+        args.push(format!(
+            "options = \"{}\"",
+            TableOptionValue::Map(index.options.clone())
+        ));
+    }
+
+    let function = if index.class_name.is_some() { "custom" } else { "index" };
+
+    format!("{}({})", function, args.join(", "))
+}
+
+/// Splits top level generic arguments, e.g. `text, frozen<map<int, text>>` into `["text", "frozen<map<int, text>>"]`.
+fn split_type_args(args: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut depth = 0;
+    let mut current = String::new();
+
+    for c in args.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                result.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+
+        current.push(c);
+    }
+
+    if !current.trim().is_empty() {
+        result.push(current.trim().to_string());
+    }
+
+    result
+}
+
+/// Snake case rust identifier of CQL name, e.g. `display_name` for `displayName` and `type_` for `type`. Characters
+/// that can't be used in identifiers are replaced with `_`.
+fn rust_identifier(name: &str) -> String {
+    let mut identifier = String::new();
+    let mut previous: Option<char> = None;
+
+    for c in name.trim_matches('"').chars() {
+        if c.is_ascii_uppercase() && previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit()) {
+            identifier.push('_');
+        }
+
+        if c.is_ascii_alphanumeric() {
+            identifier.push(c.to_ascii_lowercase());
+        } else {
+            identifier.push('_');
+        }
+
+        previous = Some(c);
+    }
+
+    if identifier.chars().all(|c| c == '_') {
+        identifier.push_str("field");
+    } else if identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+
+    if RUST_KEYWORDS.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+
+    identifier
+}
+
+fn rust_identifiers(names: &[String]) -> String {
+    names
+        .iter()
+        .map(|name| rust_identifier(name))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn module_name(name: &str) -> String {
+    rust_identifier(name)
+}
+
+fn to_pascal_case(name: &str) -> String {
+    let pascal_case: String = rust_identifier(name)
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+
+            match chars.next() {
+                Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect();
+
+    match pascal_case.as_str() {
+        "Self" => "SelfModel".to_string(),
+        _ if pascal_case.starts_with(|c: char| c.is_ascii_digit()) => format!("Model{}", pascal_case),
+        _ => pascal_case,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use charybdis_parser::schema::SchemaObjects;
+
+    fn schema_object(fields: &[(&str, &str)], partition_keys: &[&str], clustering_keys: &[&str]) -> SchemaObject {
+        SchemaObject {
+            fields: fields
+                .iter()
+                .map(|(name, cql_type)| (name.to_string(), cql_type.to_string(), false))
+                .collect(),
+            field_names: fields.iter().map(|(name, _)| name.to_string()).collect(),
+            types_by_name: fields
+                .iter()
+                .map(|(name, cql_type)| (name.to_string(), cql_type.to_string()))
+                .collect(),
+            partition_keys: partition_keys.iter().map(|key| key.to_string()).collect(),
+            clustering_keys: clustering_keys.iter().map(|key| key.to_string()).collect(),
+            ..SchemaObject::default()
+        }
+    }
+
+    fn db_schema(tables: SchemaObjects, udts: SchemaObjects) -> DbSchema {
+        DbSchema {
+            tables,
+            udts,
+            materialized_views: SchemaObjects::new(),
+            keyspace_name: "test_keyspace".to_string(),
+        }
+    }
+
+    fn rust_type(modelizer: &Modelizer, cql_type: &str) -> Result<String, String> {
+        modelizer.rust_type(cql_type, &mut BTreeSet::new(), &mut BTreeSet::new())
+    }
+
+    #[test]
+    fn rust_identifiers() {
+        assert_eq!(rust_identifier("title"), "title");
+        assert_eq!(rust_identifier("displayName"), "display_name");
+        assert_eq!(rust_identifier("\"Display Name\""), "display_name");
+        assert_eq!(rust_identifier("type"), "type_");
+        assert_eq!(rust_identifier("self"), "self_");
+        assert_eq!(rust_identifier("1st"), "_1st");
+        assert_eq!(rust_identifier("\"-\""), "_field");
+    }
+
+    #[test]
+    fn struct_names() {
+        assert_eq!(to_pascal_case("user_posts"), "UserPosts");
+        assert_eq!(to_pascal_case("userPosts"), "UserPosts");
+        assert_eq!(to_pascal_case("self"), "SelfModel");
+    }
+
+    #[test]
+    fn tuple_types() {
+        let db_schema = db_schema(SchemaObjects::new(), SchemaObjects::new());
+        let modelizer = Modelizer::new(&db_schema);
+
+        assert_eq!(rust_type(&modelizer, "tuple<int, text>").unwrap(), "Tuple<Int, Text>");
+        assert_eq!(
+            rust_type(&modelizer, "frozen<tuple<float, float, text>>").unwrap(),
+            "Frozen<Tuple3<Float, Float, Text>>"
+        );
+
+        let tuple16 = format!("tuple<{}>", vec!["int"; 16].join(", "));
+        assert_eq!(
+            rust_type(&modelizer, &tuple16).unwrap(),
+            format!("Tuple16<{}>", vec!["Int"; 16].join(", "))
+        );
+
+        let tuple17 = format!("tuple<{}>", vec!["int"; 17].join(", "));
+        assert!(rust_type(&modelizer, &tuple17).is_err());
+    }
+
+    #[test]
+    fn collection_and_udt_types() {
+        let udts = SchemaObjects::from([("address".to_string(), schema_object(&[("city", "text")], &[], &[]))]);
+        let db_schema = db_schema(SchemaObjects::new(), udts);
+        let modelizer = Modelizer::new(&db_schema);
+
+        assert_eq!(
+            rust_type(&modelizer, "map<text, frozen<list<int>>>").unwrap(),
+            "Map<Text, Frozen<List<Int>>>"
+        );
+        assert_eq!(rust_type(&modelizer, "frozen<address>").unwrap(), "Frozen<Address>");
+        assert_eq!(rust_type(&modelizer, "vector<float, 3>").unwrap(), "Vector<Float, 3>");
+        assert!(rust_type(&modelizer, "unknown_udt").is_err());
+    }
+
+    #[test]
+    fn render_table_with_renamed_columns() {
+        let tables = SchemaObjects::from([(
+            "posts".to_string(),
+            schema_object(
+                &[
+                    ("id", "uuid"),
+                    ("createdAt", "timestamp"),
+                    ("type", "text"),
+                    ("title", "text"),
+                ],
+                &["id"],
+                &["createdAt"],
+            ),
+        )]);
+        let db_schema = db_schema(tables, SchemaObjects::new());
+
+        let files = Modelizer::new(&db_schema).render();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].module_name, "posts");
+        assert_eq!(
+            files[0].content,
+            "use charybdis::macros::charybdis_model;
+use charybdis::types::{Text, Timestamp, Uuid};
+use serde::{Deserialize, Serialize};
+
+#[charybdis_model(
+    table_name = posts,
+    partition_keys = [id],
+    clustering_keys = [created_at],
+    global_secondary_indexes = [],
+    local_secondary_indexes = []
+)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Posts {
+    pub id: Uuid,
+    #[charybdis(column = \"createdAt\")]
+    pub created_at: Timestamp,
+    #[charybdis(column = \"type\")]
+    pub type_: Option<Text>,
+    pub title: Option<Text>,
+}
+"
+        );
+    }
+
+    #[test]
+    fn internal_tables_are_not_rendered() {
+        let tables = SchemaObjects::from([(
+            INTERNAL_TABLES[0].to_string(),
+            schema_object(&[("version", "int")], &["version"], &[]),
+        )]);
+        let db_schema = db_schema(tables, SchemaObjects::new());
+
+        assert!(Modelizer::new(&db_schema).render().is_empty());
+    }
+}
@@ -0,0 +1,275 @@
+use std::fmt::Display;
+
+use colored::Colorize;
+use serde::Serialize;
+
+pub use crate::model::ModelType;
+
+/// Kind of change a single plan step applies to a migration object.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepKind {
+    Create,
+    ChangeTableOptions,
+    ChangeFieldType,
+    RenameField,
+    AddFields,
+    AddGlobalIndex,
+    AddLocalIndex,
+    AddVectorIndex,
+    RemoveVectorIndex,
+    AddIndex,
+    RemoveIndex,
+    RemoveLocalIndex,
+    RemoveGlobalIndex,
+    RemoveFields,
+    Drop,
+    CopyRows,
+    /// Checks that target table of preceding `CopyRows` step contains all copied rows.
+    VerifyCopy,
+}
+
+impl Display for StepKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StepKind::Create => write!(f, "create"),
+            StepKind::ChangeTableOptions => write!(f, "change table options"),
+            StepKind::ChangeFieldType => write!(f, "change field type"),
+            StepKind::RenameField => write!(f, "rename field"),
+            StepKind::AddFields => write!(f, "add fields"),
+            StepKind::AddGlobalIndex => write!(f, "add global index"),
+            StepKind::AddLocalIndex => write!(f, "add local index"),
+            StepKind::AddVectorIndex => write!(f, "add vector index"),
+            StepKind::RemoveVectorIndex => write!(f, "remove vector index"),
+            StepKind::AddIndex => write!(f, "add index"),
+            StepKind::RemoveIndex => write!(f, "remove index"),
+            StepKind::RemoveLocalIndex => write!(f, "remove local index"),
+            StepKind::RemoveGlobalIndex => write!(f, "remove global index"),
+            StepKind::RemoveFields => write!(f, "remove fields"),
+            StepKind::Drop => write!(f, "drop"),
+            StepKind::CopyRows => write!(f, "copy rows"),
+            StepKind::VerifyCopy => write!(f, "verify copy"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanStep {
+    pub object_name: String,
+    pub object_type: ModelType,
+    pub kind: StepKind,
+    /// CQL statement without any terminal colors, ready to be executed.
+    pub cql: String,
+    /// Statement that rows selected by `cql` are inserted with. Only set for `CopyRows` steps, which can't be
+    /// expressed in plain CQL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insert_cql: Option<String>,
+    /// Column selected by `cql` that must have value for row to be copied. Only set for `CopyRows` steps that copy
+    /// single column.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_column: Option<String>,
+}
+
+impl PlanStep {
+    pub(crate) fn new(object_name: &str, object_type: ModelType, kind: StepKind, cql: &str) -> Self {
+        Self {
+            object_name: object_name.to_string(),
+            object_type,
+            kind,
+            cql: strip_colors(cql),
+            insert_cql: None,
+            required_column: None,
+        }
+    }
+}
+
+/// Destructive step that is not executed unless dropped column or index is allowed.
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockedStep {
+    /// `--allow-drop` entry that unblocks the step, e.g. `users.bio`.
+    pub allow_drop: String,
+    #[serde(flatten)]
+    pub step: PlanStep,
+}
+
+/// Ordered list of CQL statements required to bring database schema in line with code schema.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MigrationPlan {
+    pub steps: Vec<PlanStep>,
+    /// Drops of removed columns and indexes, which are only executed with `--allow-destructive` or `--allow-drop`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocked: Vec<BlockedStep>,
+    /// Detected differences that are not migrated, e.g. model removed from code without `--drop-removed-models`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl MigrationPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub(crate) fn push(&mut self, object_name: &str, object_type: ModelType, kind: StepKind, cql: &str) {
+        self.steps.push(PlanStep::new(object_name, object_type, kind, cql));
+    }
+
+    pub(crate) fn push_blocked(
+        &mut self,
+        object_name: &str,
+        object_type: ModelType,
+        kind: StepKind,
+        cql: &str,
+        allow_drop: String,
+    ) {
+        self.blocked.push(BlockedStep {
+            allow_drop,
+            step: PlanStep::new(object_name, object_type, kind, cql),
+        });
+    }
+
+    pub(crate) fn warn(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    /// Pushes step that copies rows selected by `select_cql` into target table with `insert_cql`. Rows without value
+    /// of `required_column` are not copied.
+    pub(crate) fn push_copy(
+        &mut self,
+        target_table: &str,
+        select_cql: &str,
+        insert_cql: &str,
+        required_column: Option<&str>,
+    ) {
+        self.steps.push(PlanStep {
+            object_name: target_table.to_string(),
+            object_type: ModelType::Table,
+            kind: StepKind::CopyRows,
+            cql: strip_colors(select_cql),
+            insert_cql: Some(strip_colors(insert_cql)),
+            required_column: required_column.map(str::to_string),
+        });
+    }
+
+    /// `--allow-drop` names of blocked steps along with kind of the first step they block. Changed index is blocked
+    /// both on its drop and on its creation, and its drop comes first.
+    pub fn blocked_drops(&self) -> Vec<(String, StepKind)> {
+        let mut blocked_drops: Vec<(String, StepKind)> = self
+            .blocked
+            .iter()
+            .map(|blocked| (blocked.allow_drop.clone(), blocked.step.kind))
+            .collect();
+
+        blocked_drops.sort_by(|(a, _), (b, _)| a.cmp(b));
+        blocked_drops.dedup_by(|(a, _), (b, _)| a == b);
+
+        blocked_drops
+    }
+
+    /// Plan as a CQL script, statements separated by semicolons.
+    pub fn to_cql(&self) -> String {
+        self.steps
+            .iter()
+            .map(|step| format!("{};\n", step.cql))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+pub(crate) fn strip_colors(cql: &str) -> String {
+    let stripped = strip_ansi_escapes::strip(cql.as_bytes());
+
+    String::from_utf8_lossy(&stripped).trim().to_string()
+}
+
+impl Display for MigrationPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.steps.is_empty() && self.blocked.is_empty() {
+            writeln!(f, "{}", "No changes detected.".green())?;
+        }
+
+        for (index, step) in self.steps.iter().enumerate() {
+            writeln!(
+                f,
+                "{} {} {} {}\n{};",
+                format!("{}.", index + 1).bright_cyan(),
+                step.kind.to_string().bright_green(),
+                step.object_name.bright_yellow(),
+                step.object_type.to_string().bright_magenta(),
+                step.cql.bright_purple(),
+            )?;
+
+            match &step.insert_cql {
+                Some(insert_cql) => writeln!(f, "{};\n", insert_cql.bright_purple())?,
+                None => writeln!(f)?,
+            }
+        }
+
+        for blocked in self.blocked.iter() {
+            writeln!(
+                f,
+                "{} {} {} {} {}\n{};\n",
+                "blocked".bright_red(),
+                blocked.step.kind.to_string().bright_green(),
+                blocked.step.object_name.bright_yellow(),
+                blocked.step.object_type.to_string().bright_magenta(),
+                format!("(allow with `--allow-drop {}`)", blocked.allow_drop).bright_red(),
+                blocked.step.cql.bright_purple(),
+            )?;
+        }
+
+        for warning in self.warnings.iter() {
+            writeln!(f, "{} {}", "warning".bright_yellow(), warning.bright_yellow())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocked_drops_are_sorted_and_deduplicated() {
+        let mut plan = MigrationPlan::new();
+        let cql = "ALTER TABLE posts DROP title";
+
+        plan.push_blocked(
+            "posts",
+            ModelType::Table,
+            StepKind::RemoveFields,
+            cql,
+            "posts.title".to_string(),
+        );
+        plan.push_blocked(
+            "posts",
+            ModelType::Table,
+            StepKind::RemoveIndex,
+            "DROP INDEX IF EXISTS posts_body_idx",
+            "posts.posts_body_idx".to_string(),
+        );
+        plan.push_blocked(
+            "posts",
+            ModelType::Table,
+            StepKind::AddIndex,
+            "CREATE INDEX IF NOT EXISTS posts_body_idx ON posts (body)",
+            "posts.posts_body_idx".to_string(),
+        );
+
+        assert_eq!(
+            plan.blocked_drops(),
+            vec![
+                ("posts.posts_body_idx".to_string(), StepKind::RemoveIndex),
+                ("posts.title".to_string(), StepKind::RemoveFields),
+            ]
+        );
+    }
+}
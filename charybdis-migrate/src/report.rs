@@ -0,0 +1,263 @@
+use std::fmt::Arguments;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::errors::MigrationError;
+use crate::history::{MigrationFile, MigrationStatus};
+use crate::model::ModelType;
+use crate::plan::{strip_colors, PlanStep, StepKind};
+
+/// Output format of `migrate` command results.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum OutputFormat {
+    /// Colored, human readable output
+    #[default]
+    Text,
+    /// JSON report printed to stdout, while progress is printed to stderr
+    Json,
+}
+
+impl OutputFormat {
+    /// Prints progress message. With `Json`, it's printed to stderr so stdout only contains the report.
+    pub fn progress(self, message: Arguments) {
+        match self {
+            OutputFormat::Text => println!("{}", message),
+            OutputFormat::Json => eprintln!("{}", message),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    /// Step was planned in dry run.
+    Planned,
+    Executed,
+    /// Step was not executed, e.g. drop of removed model that is not allowed, or any step after failed one.
+    Skipped,
+    /// Destructive step that was not allowed with `--allow-destructive` or `--allow-drop`.
+    Blocked,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepReport {
+    pub kind: StepKind,
+    pub cql: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insert_cql: Option<String>,
+    pub status: StepStatus,
+    pub duration_ms: u64,
+    /// Number of copied rows for `CopyRows` steps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows_copied: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Steps detected for a single UDT, table or materialized view.
+#[derive(Debug, Clone, Serialize)]
+pub struct ObjectReport {
+    pub object_name: String,
+    pub object_type: ModelType,
+    pub steps: Vec<StepReport>,
+}
+
+/// Outcome of `Migration::run`, grouped by migrated object in order of execution.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    pub keyspace: String,
+    pub dry_run: bool,
+    /// RFC 3339 timestamp of migration start.
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub objects: Vec<ObjectReport>,
+    /// Detected differences that were not migrated.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl MigrationReport {
+    pub fn new(keyspace: &str, dry_run: bool) -> Self {
+        Self {
+            keyspace: keyspace.to_string(),
+            dry_run,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            duration_ms: 0,
+            objects: vec![],
+            warnings: vec![],
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.steps().all(|step| step.status != StepStatus::Failed)
+    }
+
+    pub fn steps(&self) -> impl Iterator<Item = &StepReport> {
+        self.objects.iter().flat_map(|object| object.steps.iter())
+    }
+
+    pub fn steps_with_status(&self, status: StepStatus) -> usize {
+        self.steps().filter(|step| step.status == status).count()
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    pub(crate) fn set_duration(&mut self, duration: Duration) {
+        self.duration_ms = duration.as_millis() as u64;
+    }
+
+    pub(crate) fn push(&mut self, step: &PlanStep, status: StepStatus) -> &mut StepReport {
+        let step_report = StepReport {
+            kind: step.kind,
+            cql: step.cql.clone(),
+            insert_cql: step.insert_cql.clone(),
+            status,
+            duration_ms: 0,
+            rows_copied: None,
+            error: None,
+        };
+
+        let position = self
+            .objects
+            .iter()
+            .position(|object| object.object_name == step.object_name && object.object_type == step.object_type);
+
+        let object = match position {
+            Some(position) => &mut self.objects[position],
+            None => {
+                self.objects.push(ObjectReport {
+                    object_name: step.object_name.clone(),
+                    object_type: step.object_type,
+                    steps: vec![],
+                });
+
+                self.objects.last_mut().unwrap()
+            }
+        };
+
+        object.steps.push(step_report);
+
+        object.steps.last_mut().unwrap()
+    }
+}
+
+/// Migration file applied by `up` command or listed by `status` command.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationFileReport {
+    pub version: i32,
+    pub name: String,
+    /// `applied`, `pending`, `partial`, `modified` or `missing`.
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applied_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applied_by: Option<String>,
+    /// Number of executed statements of partially applied migration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub applied_statements: Option<i32>,
+}
+
+impl MigrationFileReport {
+    /// Migration file applied by `up` command.
+    pub fn applied(file: &MigrationFile) -> Self {
+        Self {
+            version: file.version,
+            name: file.name.clone(),
+            status: "applied",
+            path: Some(file.path.display().to_string()),
+            applied_at: None,
+            applied_by: None,
+            applied_statements: None,
+        }
+    }
+}
+
+impl From<&MigrationStatus> for MigrationFileReport {
+    fn from(status: &MigrationStatus) -> Self {
+        let (file, applied, status) = match status {
+            MigrationStatus::Applied(file, applied) => (Some(file), Some(applied), "applied"),
+            MigrationStatus::Pending(file) => (Some(file), None, "pending"),
+            MigrationStatus::Partial(file, applied) => (Some(file), Some(applied), "partial"),
+            MigrationStatus::ChecksumMismatch(file, applied) => (Some(file), Some(applied), "modified"),
+            MigrationStatus::Missing(applied) => (None, Some(applied), "missing"),
+        };
+
+        let (version, name) = match file {
+            Some(file) => (file.version, file.name.clone()),
+            None => applied
+                .map(|applied| (applied.version, applied.name.clone()))
+                .unwrap_or_default(),
+        };
+
+        Self {
+            version,
+            name,
+            status,
+            path: file.map(|file| file.path.display().to_string()),
+            applied_at: applied.map(|applied| applied.applied_at.to_rfc3339()),
+            applied_by: applied.map(|applied| applied.applied_by.clone()),
+            applied_statements: applied
+                .filter(|applied| !applied.complete)
+                .map(|applied| applied.applied_statements),
+        }
+    }
+}
+
+/// Error of any command printed with `--format json`, so stdout always contains a JSON document.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorReport {
+    pub error: String,
+}
+
+impl ErrorReport {
+    pub fn new(error: &MigrationError) -> Self {
+        Self {
+            error: strip_colors(&error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::AppliedMigration;
+
+    fn applied_migration(complete: bool) -> AppliedMigration {
+        AppliedMigration {
+            version: 3,
+            name: "add_posts".to_string(),
+            checksum: String::new(),
+            applied_at: chrono::Utc::now(),
+            applied_by: "deploy".to_string(),
+            applied_statements: 2,
+            complete,
+        }
+    }
+
+    #[test]
+    fn missing_migration_file_report() {
+        let report = MigrationFileReport::from(&MigrationStatus::Missing(applied_migration(true)));
+
+        assert_eq!(report.version, 3);
+        assert_eq!(report.name, "add_posts");
+        assert_eq!(report.status, "missing");
+        assert_eq!(report.path, None);
+        assert_eq!(report.applied_by.as_deref(), Some("deploy"));
+        assert_eq!(report.applied_statements, None);
+    }
+
+    #[test]
+    fn error_report_without_colors() {
+        let report = ErrorReport::new(&MigrationError::ConnectionNotConfigured);
+
+        assert!(report.error.starts_with("Both `--host` and `--keyspace` are required"));
+        assert!(!report.error.contains('\u{1b}'));
+    }
+}
@@ -1,15 +1,39 @@
+use std::borrow::Cow;
 use std::time::Duration;
 
-use crate::args::Args;
-use openssl::ssl::{SslContextBuilder, SslMethod, SslVerifyMode};
+use crate::args::{Args, DEFAULT_TIMEOUT};
+use crate::errors::MigrationError;
+use openssl::ssl::{SslContextBuilder, SslFiletype, SslMethod, SslVerifyMode};
+use scylla::client::execution_profile::ExecutionProfile;
 use scylla::client::session::Session;
 use scylla::client::session_builder::SessionBuilder;
+use scylla::policies::load_balancing::DefaultPolicy;
+
+/// Creates session from arguments, with connection settings that are not given by arguments taken from
+/// `CHARYBDIS_*` environment variables and configuration file, unless configuration was already applied to them.
+pub async fn initialize_session(args: &Args) -> Result<Session, MigrationError> {
+    let mut args = Cow::Borrowed(args);
+    let config = match args.resolved_config.clone() {
+        Some(config) => config,
+        None => args.to_mut().apply_config()?,
+    };
 
-pub async fn initialize_session(args: &Args) -> Session {
     let mut builder = SessionBuilder::new()
-        .known_node(&args.host)
-        .use_keyspace(&args.keyspace, false)
-        .connection_timeout(Duration::from_secs(args.timeout));
+        .known_nodes(&args.host)
+        .connection_timeout(Duration::from_secs(args.timeout.unwrap_or(DEFAULT_TIMEOUT)))
+        // schema agreement is awaited by migration itself with `--schema-agreement-timeout`, so disagreement is
+        // reported separately from failed statement
+        .auto_await_schema_agreement(false);
+
+    if let Some(local_dc) = &args.local_dc {
+        let policy = DefaultPolicy::builder()
+            .prefer_datacenter(local_dc.clone())
+            .token_aware(true)
+            .build();
+        let profile = ExecutionProfile::builder().load_balancing_policy(policy).build();
+
+        builder = builder.default_execution_profile_handle(profile.into_handle());
+    }
 
     if let (Some(user), Some(password)) = (&args.user, &args.password) {
         builder = builder.user(user, password);
@@ -17,53 +41,72 @@ pub async fn initialize_session(args: &Args) -> Session {
 
     if let Some(ca) = &args.ca {
         let mut context_builder = SslContextBuilder::new(SslMethod::tls())
-            .map_err(|e| {
-                eprintln!("Failed to create SSL context: {}", e);
-                std::process::exit(1);
-            })
-            .unwrap();
+            .map_err(|e| MigrationError::TlsError("Failed to create SSL context:".to_string(), e))?;
 
         context_builder
             .set_ca_file(ca)
-            .map_err(|e| {
-                eprintln!("Failed to set CA file: {}", e);
-                std::process::exit(1);
-            })
-            .unwrap();
+            .map_err(|e| MigrationError::TlsError(format!("Failed to set CA file {}:", ca), e))?;
 
         context_builder.set_verify(SslVerifyMode::PEER);
 
-        if let Some(key) = &args.cert {
+        if let Some(cert) = &args.cert {
             context_builder
-                .set_certificate_file(key, openssl::ssl::SslFiletype::PEM)
-                .map_err(|e| {
-                    eprintln!("Failed to set certificate file: {}", e);
-                    std::process::exit(1);
-                })
-                .unwrap();
-
-            let key = args
-                .key
-                .as_ref()
-                .expect("Private key file is required when certificate is provided");
+                .set_certificate_file(cert, SslFiletype::PEM)
+                .map_err(|e| MigrationError::TlsError(format!("Failed to set certificate file {}:", cert), e))?;
+
+            let key = args.key.as_ref().ok_or(MigrationError::PrivateKeyRequired)?;
+
             context_builder
-                .set_private_key_file(key, openssl::ssl::SslFiletype::PEM)
-                .map_err(|e| {
-                    eprintln!("Failed to set private key file: {}", e);
-                    std::process::exit(1);
-                })
-                .unwrap();
+                .set_private_key_file(key, SslFiletype::PEM)
+                .map_err(|e| MigrationError::TlsError(format!("Failed to set private key file {}:", key), e))?;
         }
 
         builder = builder.tls_context(Some(context_builder.build()));
     }
 
-    builder
-        .build()
-        .await
-        .map_err(|e| {
-            eprintln!("Failed to create session: {}", e);
-            std::process::exit(1);
-        })
-        .unwrap()
+    let session = builder.build().await.map_err(MigrationError::NewSessionError)?;
+
+    if let Err(e) = session.use_keyspace(&args.keyspace, false).await {
+        // keyspace configured by `[keyspace]` section is created by migration itself
+        if config.keyspace.is_none() {
+            return Err(MigrationError::UseKeyspaceError(args.keyspace.clone(), e));
+        }
+    }
+
+    Ok(session)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> Args {
+        Args {
+            host: vec!["127.0.0.1:1".to_string()],
+            keyspace: "test_keyspace".to_string(),
+            timeout: Some(1),
+            current_dir: std::env::temp_dir().display().to_string(),
+            config: "missing_charybdis.toml".to_string(),
+            ..Args::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn missing_ca_file_is_error() {
+        let args = Args {
+            ca: Some("missing_ca.pem".to_string()),
+            ..args()
+        };
+
+        let result = initialize_session(&args).await;
+
+        assert!(matches!(result, Err(MigrationError::TlsError(_, _))));
+    }
+
+    #[tokio::test]
+    async fn unreachable_host_is_error() {
+        let result = initialize_session(&args()).await;
+
+        assert!(matches!(result, Err(MigrationError::NewSessionError(_))));
+    }
 }
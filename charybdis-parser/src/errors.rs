@@ -60,3 +60,71 @@ impl fmt::Display for DbSchemaParserError {
         }
     }
 }
+
+#[derive(Debug)]
+pub enum CodeSchemaError {
+    /// Field type that is neither native CQL type nor `#[charybdis_udt_model]` struct.
+    UnknownType {
+        model: String,
+        field: String,
+        type_name: String,
+    },
+    InvalidGlob {
+        pattern: String,
+        reason: String,
+    },
+    FileReadError {
+        path: String,
+        error: std::io::Error,
+    },
+    FileParseError {
+        path: String,
+        error: syn::Error,
+    },
+    /// Table, UDT or materialized view defined by more than one struct.
+    DuplicateModel {
+        kind: String,
+        name: String,
+        paths: (String, String),
+    },
+    /// Model macro alias declared with `use ... as` for different macros.
+    ConflictingMacroAlias {
+        alias: String,
+        macros: (String, String),
+        paths: (String, String),
+    },
+}
+
+impl Error for CodeSchemaError {}
+
+impl fmt::Display for CodeSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeSchemaError::UnknownType {
+                model,
+                field,
+                type_name,
+            } => write!(
+                f,
+                "Unknown type `{}` of field `{}` in `{}`. Use `charybdis::types` type, struct with \
+                `#[charybdis_udt_model]` or set `#[charybdis(column_type = \"...\")]` for custom type.",
+                type_name, field, model
+            ),
+            CodeSchemaError::InvalidGlob { pattern, reason } => {
+                write!(f, "Invalid models path pattern `{}`: {}", pattern, reason)
+            }
+            CodeSchemaError::FileReadError { path, error } => write!(f, "Unable to read file {}: {}", path, error),
+            CodeSchemaError::FileParseError { path, error } => write!(f, "Unable to parse file {}: {}", path, error),
+            CodeSchemaError::DuplicateModel { kind, name, paths } => write!(
+                f,
+                "{} `{}` is defined more than once, in {} and {}",
+                kind, name, paths.0, paths.1
+            ),
+            CodeSchemaError::ConflictingMacroAlias { alias, macros, paths } => write!(
+                f,
+                "Alias `{}` is declared for both `{}` in {} and `{}` in {}",
+                alias, macros.0, paths.0, macros.1, paths.1
+            ),
+        }
+    }
+}
@@ -5,9 +5,13 @@ use darling::FromAttributes;
 use syn::spanned::Spanned;
 use syn::{Data, DeriveInput, Fields, FieldsNamed, GenericArgument, PathArguments, Type};
 
+use crate::schema::secondary_indexes::IndexTargetKind;
 use crate::traits::CharybdisMacroArgs;
 
+/// Type names are parsed case-insensitively, so they match both `charybdis::types` names and CQL names used in
+/// `column_type` attribute, e.g. `BigInt` and `bigint`.
 #[derive(Clone, PartialEq, strum_macros::Display, strum_macros::EnumString)]
+#[strum(ascii_case_insensitive)]
 pub enum CqlType {
     Ascii,
     BigInt,
@@ -34,12 +38,33 @@ pub enum CqlType {
     List,
     Set,
     Tuple,
+    Vector,
     Ignored,
-    /// This is used when the type is not recognized. In future we might want to extend recognition to UDTs,
-    /// so we can panic if type is not recognized.
+    /// Type that is not native CQL type, e.g. UDT. Parser resolves it against `#[charybdis_udt_model]` structs
+    /// and fails if there is no such UDT.
     Unknown,
 }
 
+impl CqlType {
+    /// Parses `charybdis::types` name, e.g. `Text` or `Tuple3`, ignoring its type arguments.
+    pub fn from_type_name(name: &str) -> CqlType {
+        if is_tuple_type_name(name) {
+            return CqlType::Tuple;
+        }
+
+        name.parse::<CqlType>().unwrap_or(CqlType::Unknown)
+    }
+}
+
+/// Whether name is one of `Tuple`, `Tuple3`..`Tuple16` aliases.
+pub fn is_tuple_type_name(name: &str) -> bool {
+    name == "Tuple"
+        || name
+            .strip_prefix("Tuple")
+            .and_then(|arity| arity.parse::<usize>().ok())
+            .is_some_and(|arity| (3..=16).contains(&arity))
+}
+
 #[derive(FromAttributes, Clone)]
 #[darling(attributes(charybdis))]
 pub struct FieldAttributes {
@@ -48,8 +73,80 @@ pub struct FieldAttributes {
 
     #[darling(default)]
     pub column_type: Option<String>,
+
+    /// Previous name of the field, so migration renames the column instead of dropping it.
+    #[darling(default)]
+    pub renamed_from: Option<String>,
+
+    /// CQL column name, when it differs from the Rust field name.
+    #[darling(default)]
+    pub column: Option<String>,
 }
 
+/// CQL reserved keywords that can't be used as column names unless quoted.
+const RESERVED_KEYWORDS: &[&str] = &[
+    "add",
+    "allow",
+    "alter",
+    "and",
+    "apply",
+    "asc",
+    "authorize",
+    "batch",
+    "begin",
+    "by",
+    "columnfamily",
+    "create",
+    "delete",
+    "desc",
+    "describe",
+    "drop",
+    "entries",
+    "execute",
+    "from",
+    "full",
+    "grant",
+    "if",
+    "in",
+    "index",
+    "infinity",
+    "insert",
+    "into",
+    "is",
+    "keyspace",
+    "limit",
+    "materialized",
+    "mbean",
+    "mbeans",
+    "modify",
+    "nan",
+    "norecursive",
+    "not",
+    "null",
+    "of",
+    "on",
+    "or",
+    "order",
+    "primary",
+    "rename",
+    "replace",
+    "revoke",
+    "schema",
+    "select",
+    "set",
+    "table",
+    "to",
+    "token",
+    "truncate",
+    "unlogged",
+    "update",
+    "use",
+    "using",
+    "view",
+    "where",
+    "with",
+];
+
 pub struct Field<'a> {
     pub name: String,
     pub ident: syn::Ident,
@@ -57,6 +154,8 @@ pub struct Field<'a> {
     pub ty_path: syn::TypePath,
     pub outer_type: CqlType,
     pub column_type_override: Option<String>,
+    pub renamed_from: Option<String>,
+    pub column: Option<String>,
     pub span: proc_macro2::Span,
     pub attrs: &'a Vec<syn::Attribute>,
     pub ignore: bool,
@@ -79,21 +178,11 @@ impl<'a> Field<'a> {
                         if let Some(GenericArgument::Type(Type::Path(inner_type_path))) = args.args.first() {
                             let inner = inner_type_path.path.segments.last().expect("No inner type found");
 
-                            return inner
-                                .ident
-                                .to_string()
-                                .parse::<CqlType>()
-                                .ok()
-                                .unwrap_or(CqlType::Unknown);
+                            return CqlType::from_type_name(&inner.ident.to_string());
                         }
                     }
                 } else {
-                    return last_segment
-                        .ident
-                        .to_string()
-                        .parse::<CqlType>()
-                        .ok()
-                        .unwrap_or(CqlType::Unknown);
+                    return CqlType::from_type_name(&last_segment.ident.to_string());
                 }
             }
 
@@ -132,6 +221,8 @@ impl<'a> Field<'a> {
                     },
                     outer_type: column_type,
                     column_type_override: char_attrs.column_type,
+                    renamed_from: char_attrs.renamed_from,
+                    column: char_attrs.column,
                     span: field.span(),
                     attrs: &field.attrs,
                     ignore,
@@ -143,6 +234,17 @@ impl<'a> Field<'a> {
             .unwrap()
     }
 
+    /// Name of the column in database. Same as field name unless it's set with `#[charybdis(column = "...")]`.
+    pub fn column_name(&self) -> &str {
+        self.column.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Column name as it should appear in CQL statements, quoted if it's case-sensitive, contains special
+    /// characters or is a reserved keyword.
+    pub fn cql_name(&self) -> String {
+        quote_identifier(self.column_name())
+    }
+
     pub fn is_primary_key(&self) -> bool {
         self.is_partition_key || self.is_clustering_key
     }
@@ -171,6 +273,10 @@ impl<'a> Field<'a> {
         self.outer_type == CqlType::Tuple
     }
 
+    pub fn is_vector(&self) -> bool {
+        self.outer_type == CqlType::Vector
+    }
+
     pub fn is_frozen(&self) -> bool {
         self.ty_path
             .path
@@ -189,6 +295,9 @@ pub struct CharybdisFields<'a> {
     pub db_fields: Vec<&'a Field<'a>>,
     pub global_secondary_index_fields: Vec<&'a Field<'a>>,
     pub local_secondary_index_fields: Vec<&'a Field<'a>>,
+    pub vector_index_fields: Vec<&'a Field<'a>>,
+    /// Fields of indexes declared with `indexes` model argument, with the indexed part of the column.
+    pub index_fields: Vec<(&'a Field<'a>, IndexTargetKind)>,
 }
 
 impl CharybdisFields<'_> {
@@ -200,6 +309,30 @@ impl CharybdisFields<'_> {
             .collect()
     }
 
+    /// Non-key columns that support `WRITETIME` and `TTL` selectors, which excludes counters and non-frozen
+    /// collections.
+    pub fn writetime_fields(&self) -> Vec<&Field<'_>> {
+        self.non_primary_key_db_fields()
+            .into_iter()
+            .filter(|field| !field.is_counter() && (!field.is_collection() || field.is_frozen()))
+            .collect()
+    }
+
+    /// Fields listed in `vector_indexes` must be of `Vector` type, as ANN queries are generated for them. Error
+    /// points at the field, so it's reported as compile error instead of macro panic.
+    pub fn validate_vector_indexes(&self, args: &CharybdisMacroArgs) -> syn::Result<()> {
+        for field in self.db_fields.iter() {
+            if args.vector_indexes().contains(&field.name) && !field.is_vector() {
+                return Err(syn::Error::new(
+                    field.ident.span(),
+                    format!("Vector index field {} must be of Vector type", field.name),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn non_db_fields(&self) -> Vec<&Field> {
         self.all_fields.iter().filter(|field| field.ignore).collect()
     }
@@ -290,6 +423,15 @@ impl<'a> CharybdisFields<'a> {
                 self.local_secondary_index_fields.push(ch_field);
             }
 
+            // other fields are reported by `validate_vector_indexes`
+            if args.vector_indexes().contains(&ch_field.name) && ch_field.is_vector() {
+                self.vector_index_fields.push(ch_field);
+            }
+
+            for index in args.indexes().iter().filter(|index| index.column == ch_field.name) {
+                self.index_fields.push((ch_field, index.kind));
+            }
+
             if ch_field.is_partition_key {
                 let partition_key_index = *partition_key_indexes_by_name
                     .get(&ch_field.name)
@@ -368,16 +510,22 @@ impl<'a> CharybdisFields<'a> {
         }
     }
 
-    /// Map charybdis(ignore) to scylla(skip)
+    /// Map charybdis(ignore) to scylla(skip) and charybdis(column) to scylla(rename)
     pub fn proxy_charybdis_attrs_to_scylla(input: &mut DeriveInput) {
         if let Data::Struct(data_struct) = &mut input.data {
             if let Fields::Named(fields_named) = &mut data_struct.fields {
                 for field in &mut fields_named.named {
-                    if let Some(ignore) = &FieldAttributes::from_attributes(&field.attrs).unwrap().ignore {
+                    let char_attrs = FieldAttributes::from_attributes(&field.attrs).unwrap();
+
+                    if let Some(ignore) = &char_attrs.ignore {
                         if *ignore {
                             field.attrs.push(syn::parse_quote!(#[scylla(skip)]));
                         }
                     }
+
+                    if let Some(column) = &char_attrs.column {
+                        field.attrs.push(syn::parse_quote!(#[scylla(rename = #column)]));
+                    }
                 }
             }
         }
@@ -393,3 +541,100 @@ impl<'a> CharybdisFields<'a> {
         }
     }
 }
+
+/// Quotes CQL identifier if it's not a valid unquoted one, e.g. `userId` -> `"userId"`.
+pub fn quote_identifier(name: &str) -> String {
+    let mut chars = name.chars();
+    let is_plain = chars.next().is_some_and(|c| c.is_ascii_lowercase() || c == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+    if is_plain && !RESERVED_KEYWORDS.contains(&name) {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+/// Query selecting all rows of the table. It's `FIND_ALL_QUERY` of models, and it's used to page through rows
+/// that are copied by migrations.
+pub fn find_all_query<S: AsRef<str>>(table_name: &str, column_names: &[S]) -> String {
+    format!("SELECT {} FROM {}", cql_names(column_names), table_name)
+}
+
+/// Query inserting row with named bind markers. It's `INSERT_QUERY` of models, and it's used to insert rows that
+/// are copied by migrations.
+pub fn insert_query<S: AsRef<str>>(table_name: &str, column_names: &[S]) -> String {
+    let bind_markers = column_names
+        .iter()
+        .map(|name| format!(":{}", quote_identifier(name.as_ref())))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        table_name,
+        cql_names(column_names),
+        bind_markers
+    )
+}
+
+/// Query selecting values of `column` that are copied by migrations, followed by `key_columns` of their rows. With
+/// `with_ttl`, `TTL` and `WRITETIME` of the column are selected first. With `distinct`, single row is selected per
+/// partition, which is used to copy static columns.
+pub fn find_column_values_query<S: AsRef<str>>(
+    table_name: &str,
+    column: &str,
+    key_columns: &[S],
+    with_ttl: bool,
+    distinct: bool,
+) -> String {
+    let column = quote_identifier(column);
+    let ttl_selectors = if with_ttl {
+        format!("TTL({column}), WRITETIME({column}), ")
+    } else {
+        String::new()
+    };
+
+    format!(
+        "SELECT {}{}{}, {} FROM {}",
+        if distinct { "DISTINCT " } else { "" },
+        ttl_selectors,
+        column,
+        cql_names(key_columns),
+        table_name
+    )
+}
+
+/// Query setting `column` of row identified by `key_columns`, with values bound in order selected by
+/// `find_column_values_query`. Unlike `INSERT`, `UPDATE` doesn't write row marker, so copied values don't keep rows
+/// alive after they expire.
+pub fn update_column_query<S: AsRef<str>>(table_name: &str, column: &str, key_columns: &[S], with_ttl: bool) -> String {
+    let where_clause = key_columns
+        .iter()
+        .map(|name| {
+            let name = quote_identifier(name.as_ref());
+            format!("{name} = :{name}")
+        })
+        .collect::<Vec<String>>()
+        .join(" AND ");
+    let column = quote_identifier(column);
+
+    format!(
+        "UPDATE {}{} SET {column} = :{column} WHERE {}",
+        table_name,
+        if with_ttl {
+            " USING TTL :ttl AND TIMESTAMP :writetime"
+        } else {
+            ""
+        },
+        where_clause
+    )
+}
+
+fn cql_names<S: AsRef<str>>(column_names: &[S]) -> String {
+    column_names
+        .iter()
+        .map(|name| quote_identifier(name.as_ref()))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
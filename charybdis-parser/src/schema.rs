@@ -1,11 +1,16 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 
+use crate::fields::quote_identifier;
+use crate::schema::secondary_indexes::IndexDefinition;
+
 pub mod code_schema;
 pub mod db_schema;
+pub mod diff;
 pub mod secondary_indexes;
+pub mod table_options;
 
 pub type FieldName = String;
 pub type FieldType = String;
@@ -26,7 +31,16 @@ pub struct SchemaObject {
     pub static_columns: Vec<String>,
     pub global_secondary_indexes: Vec<(IndexName, IdxField)>,
     pub local_secondary_indexes: Vec<(IndexName, IdxField)>,
+    /// Storage-attached indexes of vector columns, used by ANN queries.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vector_indexes: Vec<(IndexName, IdxField)>,
+    /// Storage-attached, custom, named and collection indexes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub indexes: Vec<IndexDefinition>,
     pub table_options: Option<String>,
+    /// Previous names of renamed fields, by their current name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub renamed_fields: BTreeMap<FieldName, FieldName>,
 }
 
 impl SchemaObject {
@@ -39,6 +53,10 @@ impl SchemaObject {
     pub fn contains_field(&self, field_name: &str) -> bool {
         self.field_names.contains(field_name)
     }
+
+    pub fn parsed_table_options(&self) -> table_options::TableOptions {
+        table_options::TableOptions::parse(self.table_options.as_deref().unwrap_or_default())
+    }
 }
 
 impl SchemaObject {
@@ -55,7 +73,10 @@ impl SchemaObject {
             static_columns: Vec::new(),
             global_secondary_indexes: Vec::new(),
             local_secondary_indexes: Vec::new(),
+            vector_indexes: Vec::new(),
+            indexes: Vec::new(),
             table_options: None,
+            renamed_fields: BTreeMap::new(),
         }
     }
 
@@ -65,7 +86,7 @@ impl SchemaObject {
         for (field_name, field_type, is_static) in self.fields.iter() {
             cql_fields.push_str(&format!(
                 "    {} {}{},\n",
-                field_name.bright_cyan().bold(),
+                quote_identifier(field_name).bright_cyan().bold(),
                 field_type.bright_yellow(),
                 if *is_static { " static" } else { "" }
             ));
@@ -79,3 +100,38 @@ impl SchemaObject {
 
 pub type ModelName = String;
 pub type SchemaObjects = HashMap<ModelName, SchemaObject>;
+
+/// Schema objects of either code or database schema.
+pub trait Schema {
+    fn tables(&self) -> &SchemaObjects;
+    fn udts(&self) -> &SchemaObjects;
+    fn materialized_views(&self) -> &SchemaObjects;
+}
+
+impl Schema for code_schema::CodeSchema {
+    fn tables(&self) -> &SchemaObjects {
+        &self.tables
+    }
+
+    fn udts(&self) -> &SchemaObjects {
+        &self.udts
+    }
+
+    fn materialized_views(&self) -> &SchemaObjects {
+        &self.materialized_views
+    }
+}
+
+impl Schema for db_schema::DbSchema {
+    fn tables(&self) -> &SchemaObjects {
+        &self.tables
+    }
+
+    fn udts(&self) -> &SchemaObjects {
+        &self.udts
+    }
+
+    fn materialized_views(&self) -> &SchemaObjects {
+        &self.materialized_views
+    }
+}
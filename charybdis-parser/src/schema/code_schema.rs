@@ -1,21 +1,31 @@
+use std::collections::HashMap;
 use std::fmt::Display;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use colored::Colorize;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use walkdir::{DirEntry, WalkDir};
 
+use crate::errors::CodeSchemaError;
+use crate::fields::CqlType;
 use crate::schema::{SchemaObject, SchemaObjects};
 
+mod cfg;
+mod discovery;
 mod parser;
 
-#[derive(Eq, PartialEq)]
+pub use discovery::ModelDiscovery;
+
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub(crate) enum ModelMacro {
     Table,
     Udt,
     MaterializedView,
 }
 
+impl ModelMacro {
+    pub(crate) const ALL: [ModelMacro; 3] = [ModelMacro::Table, ModelMacro::Udt, ModelMacro::MaterializedView];
+}
+
 impl Display for ModelMacro {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -31,78 +41,580 @@ pub struct CodeSchema {
     pub tables: SchemaObjects,
     pub udts: SchemaObjects,
     pub materialized_views: SchemaObjects,
+    /// UDT type names by their struct names.
+    #[serde(skip)]
+    udt_type_names: HashMap<String, String>,
+    /// Scope of model discovery.
+    #[serde(skip)]
+    discovery: ModelDiscovery,
+    /// Discovered files that couldn't be read or parsed, so their models are missing.
+    #[serde(skip)]
+    skipped_files: Vec<SkippedFile>,
+}
+
+/// Discovered file that couldn't be read or parsed, along with the reason.
+#[derive(Debug)]
+pub struct SkippedFile {
+    pub path: String,
+    pub error: CodeSchemaError,
+}
+
+/// Models defined in a single source file, along with their struct names.
+#[derive(Default)]
+struct FileModels {
+    materialized_views: Vec<(String, SchemaObject)>,
+    udts: Vec<(String, SchemaObject)>,
+    tables: Vec<(String, SchemaObject)>,
+}
+
+impl FileModels {
+    fn models_mut(&mut self, model_macro: ModelMacro) -> &mut Vec<(String, SchemaObject)> {
+        match model_macro {
+            ModelMacro::Table => &mut self.tables,
+            ModelMacro::Udt => &mut self.udts,
+            ModelMacro::MaterializedView => &mut self.materialized_views,
+        }
+    }
+}
+
+/// Models of a file with macro used by an imported alias, parsed as each of model macros.
+type ImportedModels = Vec<(ModelMacro, Result<Vec<(String, SchemaObject)>, CodeSchemaError>)>;
+
+/// Source file parsed once, reduced to data that can be sent between threads, as syn AST can't.
+struct ParsedFile {
+    /// Model macro aliases declared in the file with `use ... as`, as pairs of macro name and its alias.
+    aliases: Vec<(String, String)>,
+    /// Models of the file, with model macros used by name, by path or by alias declared in the file.
+    models: Result<FileModels, CodeSchemaError>,
+    /// Models with macro used by a name imported with `use`, e.g. `use crate::model`, by that name. As alias
+    /// can be declared in another file, they are parsed as each of model macros, until it's known which macro
+    /// the alias stands for.
+    imported_models: Vec<(String, ImportedModels)>,
 }
 
 impl CodeSchema {
-    pub fn new(current_dir: &String) -> CodeSchema {
+    pub fn new(current_dir: &String) -> Result<CodeSchema, CodeSchemaError> {
+        CodeSchema::discover(current_dir, ModelDiscovery::default())
+    }
+
+    /// Parses models of the current directory within given discovery scope.
+    pub fn discover(current_dir: &String, discovery: ModelDiscovery) -> Result<CodeSchema, CodeSchemaError> {
+        discovery.validate()?;
+
         let mut current_code_schema = CodeSchema {
-            tables: SchemaObjects::new(),
-            udts: SchemaObjects::new(),
-            materialized_views: SchemaObjects::new(),
+            discovery,
+            ..Default::default()
         };
 
-        current_code_schema.get_models_from_code(current_dir);
+        current_code_schema.get_models_from_code(current_dir)?;
+        current_code_schema.resolve_types()?;
 
-        current_code_schema
+        Ok(current_code_schema)
     }
 
-    pub fn get_models_from_code(&mut self, current_dir: &String) {
-        let current_dir: PathBuf = PathBuf::from(current_dir);
-        let walker = WalkDir::new(&current_dir)
-            .into_iter()
-            .filter_entry(|e| !(e.file_type().is_dir() && e.file_name() == "target"));
+    /// Parses models from files in discovery scope in parallel, parsing each file once. Files that can't be read
+    /// or parsed are skipped and listed in `skipped_files`, while model defined in more than one file is an error.
+    ///
+    /// Model macro alias declared with `use ... as` applies to the file that declares it and to files that import
+    /// it with `use`, e.g. alias re-exported from crate root. Alias declared for different macros is an error.
+    pub fn get_models_from_code(&mut self, current_dir: &String) -> Result<(), CodeSchemaError> {
+        let files = self.discovery.model_files(&PathBuf::from(current_dir));
+        let features = self.discovery.features.as_deref();
 
-        for entry in walker {
-            // ignore target directory
-            let entry: DirEntry = entry.unwrap();
+        let parsed: Vec<Result<ParsedFile, CodeSchemaError>> =
+            files.par_iter().map(|path| parse_file_models(path, features)).collect();
 
-            if entry.path().is_file() {
-                let path = entry.path().to_str().unwrap();
+        // aliases along with macro names they stand for and paths of files that declare them
+        let mut aliases: HashMap<String, (String, String)> = HashMap::new();
+
+        for (path, parsed_file) in files.iter().zip(&parsed) {
+            for (macro_name, alias) in parsed_file.iter().flat_map(|parsed_file| &parsed_file.aliases) {
+                let path = path.display().to_string();
+
+                match aliases.get(alias.as_str()) {
+                    Some((first_macro, first_path)) if first_macro != macro_name => {
+                        return Err(CodeSchemaError::ConflictingMacroAlias {
+                            alias: alias.clone(),
+                            macros: (first_macro.clone(), macro_name.clone()),
+                            paths: (first_path.clone(), path),
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        aliases.insert(alias.clone(), (macro_name.clone(), path));
+                    }
+                }
+            }
+        }
+
+        // model names along with paths of files that define them
+        let mut model_paths: HashMap<(&str, String), String> = HashMap::new();
+
+        for (path, parsed_file) in files.iter().zip(parsed) {
+            let file_models = match parsed_file.and_then(|parsed_file| resolve_imported_models(parsed_file, &aliases)) {
+                Ok(file_models) => file_models,
+                Err(error) => {
+                    self.skipped_files.push(SkippedFile {
+                        path: path.display().to_string(),
+                        error,
+                    });
 
-                if !path.ends_with(".rs") {
                     continue;
                 }
+            };
+
+            let names = file_models
+                .materialized_views
+                .iter()
+                .map(|(_, view)| ("Materialized view", view.table_name.clone()))
+                .chain(
+                    file_models
+                        .udts
+                        .iter()
+                        .map(|(_, udt)| ("UDT", udt.type_name.to_lowercase())),
+                )
+                .chain(
+                    file_models
+                        .tables
+                        .iter()
+                        .map(|(_, table)| ("Table", table.table_name.clone())),
+                );
+
+            for (kind, name) in names {
+                let path = path.display().to_string();
 
-                let file_content: String = parser::parse_file_as_string(entry.path());
-                let ast: syn::File = syn::parse_file(&file_content)
-                    .map_err(|e| {
-                        println!(
-                            "{}\n",
-                            format!("Error parsing file: {}", file_content).bright_red().bold()
-                        );
-                        e
-                    })
-                    .unwrap();
-
-                self.populate_materialized_views(&ast);
-                self.populate_udts(&ast);
-                self.populate_tables(&ast);
+                if let Some(first_path) = model_paths.insert((kind, name.clone()), path.clone()) {
+                    return Err(CodeSchemaError::DuplicateModel {
+                        kind: kind.to_string(),
+                        name,
+                        paths: (first_path, path),
+                    });
+                }
             }
+
+            self.insert_materialized_views(file_models.materialized_views);
+            self.insert_udts(file_models.udts);
+            self.insert_tables(file_models.tables);
         }
+
+        Ok(())
+    }
+
+    /// Discovered files that couldn't be read or parsed. Models defined in them are missing from the schema, so
+    /// they would look removed.
+    pub fn skipped_files(&self) -> &[SkippedFile] {
+        &self.skipped_files
+    }
+
+    /// Adds materialized views defined in given file, replacing already added views of the same name.
+    pub fn populate_materialized_views(&mut self, ast: &syn::File) -> syn::Result<()> {
+        let schema_objects = self.parse_models(ast, ModelMacro::MaterializedView)?;
+
+        self.insert_materialized_views(schema_objects);
+
+        Ok(())
+    }
+
+    /// Adds UDTs defined in given file, replacing already added UDTs of the same name.
+    pub fn populate_udts(&mut self, ast: &syn::File) -> syn::Result<()> {
+        let schema_objects = self.parse_models(ast, ModelMacro::Udt)?;
+
+        self.insert_udts(schema_objects);
+
+        Ok(())
+    }
+
+    /// Adds tables defined in given file, replacing already added tables of the same name.
+    pub fn populate_tables(&mut self, ast: &syn::File) -> syn::Result<()> {
+        let schema_objects = self.parse_models(ast, ModelMacro::Table)?;
+
+        self.insert_tables(schema_objects);
+
+        Ok(())
     }
 
-    pub fn populate_materialized_views(&mut self, ast: &syn::File) {
-        let schema_objects: Vec<SchemaObject> = parser::parse_charybdis_model_def(ast, ModelMacro::MaterializedView);
+    /// Parses models of given file with aliases declared in it.
+    fn parse_models(&self, ast: &syn::File, model_macro: ModelMacro) -> syn::Result<Vec<(String, SchemaObject)>> {
+        let aliases = parser::macro_aliases(ast);
 
-        schema_objects.into_iter().for_each(|schema_object| {
+        parser::parse_charybdis_model_def(ast, model_macro, &aliases, self.discovery.features.as_deref())
+    }
+
+    fn insert_materialized_views(&mut self, schema_objects: Vec<(String, SchemaObject)>) {
+        schema_objects.into_iter().for_each(|(_, schema_object)| {
             self.materialized_views
                 .insert(schema_object.table_name.clone(), schema_object);
         });
     }
 
-    pub fn populate_udts(&mut self, ast: &syn::File) {
-        let schema_objects: Vec<SchemaObject> = parser::parse_charybdis_model_def(ast, ModelMacro::Udt);
-
-        schema_objects.into_iter().for_each(|schema_object| {
+    fn insert_udts(&mut self, schema_objects: Vec<(String, SchemaObject)>) {
+        schema_objects.into_iter().for_each(|(struct_name, schema_object)| {
+            self.udt_type_names
+                .insert(struct_name, schema_object.type_name.to_lowercase());
             self.udts.insert(schema_object.type_name.to_lowercase(), schema_object);
         });
     }
 
-    pub fn populate_tables(&mut self, ast: &syn::File) {
-        let schema_object: Vec<SchemaObject> = parser::parse_charybdis_model_def(ast, ModelMacro::Table);
-
-        schema_object.into_iter().for_each(|schema_object| {
+    fn insert_tables(&mut self, schema_objects: Vec<(String, SchemaObject)>) {
+        schema_objects.into_iter().for_each(|(_, schema_object)| {
             self.tables.insert(schema_object.table_name.clone(), schema_object);
         });
     }
+
+    /// Replaces UDT struct names in field types with UDT type names, e.g. `Frozen<UserAddress>` with
+    /// `Frozen<address>`, and fails on types that are neither native nor UDT.
+    pub fn resolve_types(&mut self) -> Result<(), CodeSchemaError> {
+        let mut udt_type_names = self.udt_type_names.clone();
+
+        // UDT can also be referenced by its type name
+        for type_name in self.udts.keys() {
+            udt_type_names.insert(type_name.clone(), type_name.clone());
+        }
+
+        for schema_objects in [&mut self.tables, &mut self.udts, &mut self.materialized_views] {
+            for (model, schema_object) in schema_objects.iter_mut() {
+                for (field, field_type, _) in schema_object.fields.iter_mut() {
+                    let resolved_type = resolve_type(field_type, &udt_type_names).map_err(|type_name| {
+                        CodeSchemaError::UnknownType {
+                            model: model.clone(),
+                            field: field.clone(),
+                            type_name,
+                        }
+                    })?;
+
+                    schema_object.types_by_name.insert(field.clone(), resolved_type.clone());
+                    *field_type = resolved_type;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Resolves each type name in type like `Map<Text, Frozen<Address>>`, or CQL type set with `column_type` attribute
+/// like `frozen<list<text>>`. Returns unknown type name on error.
+/// Reads and parses models of given file. Syn AST can't be sent between threads, so everything that is needed from
+/// it is extracted by the thread that parsed the file.
+fn parse_file_models(path: &Path, features: Option<&[String]>) -> Result<ParsedFile, CodeSchemaError> {
+    let ast = parser::parse_file(path)?;
+    let aliases = parser::macro_aliases(&ast);
+    let parse_error = |error| CodeSchemaError::FileParseError {
+        path: path.display().to_string(),
+        error,
+    };
+
+    let models = parse_models(&ast, &aliases, features).map_err(parse_error);
+
+    let imported_models = parser::imported_names(&ast)
+        .into_iter()
+        .filter(|name| !aliases.iter().any(|(_, alias)| alias == name))
+        .map(|name| {
+            let models: ImportedModels = ModelMacro::ALL
+                .into_iter()
+                .map(|model_macro| {
+                    let models = parser::parse_alias_models(&ast, model_macro, &name, features).map_err(parse_error);
+
+                    (model_macro, models)
+                })
+                .collect();
+
+            (name, models)
+        })
+        .filter(|(_, models)| {
+            models
+                .iter()
+                .any(|(_, models)| models.as_ref().map_or(true, |models| !models.is_empty()))
+        })
+        .collect();
+
+    Ok(ParsedFile {
+        aliases,
+        models,
+        imported_models,
+    })
+}
+
+fn parse_models(ast: &syn::File, aliases: &[(String, String)], features: Option<&[String]>) -> syn::Result<FileModels> {
+    let parse_models = |model_macro| parser::parse_charybdis_model_def(ast, model_macro, aliases, features);
+
+    Ok(FileModels {
+        materialized_views: parse_models(ModelMacro::MaterializedView)?,
+        udts: parse_models(ModelMacro::Udt)?,
+        tables: parse_models(ModelMacro::Table)?,
+    })
+}
+
+/// Adds models of given file whose macro is used by an imported alias, as the macro of alias is known.
+fn resolve_imported_models(
+    parsed_file: ParsedFile,
+    aliases: &HashMap<String, (String, String)>,
+) -> Result<FileModels, CodeSchemaError> {
+    let mut file_models = parsed_file.models?;
+
+    for (name, models) in parsed_file.imported_models {
+        let Some((macro_name, _)) = aliases.get(&name) else {
+            continue;
+        };
+
+        for (model_macro, models) in models {
+            if model_macro.to_string() == *macro_name {
+                file_models.models_mut(model_macro).extend(models?);
+            }
+        }
+    }
+
+    Ok(file_models)
+}
+
+fn resolve_type(field_type: &str, udt_type_names: &HashMap<String, String>) -> Result<String, String> {
+    let mut resolved = String::new();
+    let mut name = String::new();
+
+    for c in field_type.chars().chain(std::iter::once(' ')) {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            name.push(c);
+            continue;
+        }
+
+        if !matches!(c, '<' | '>' | ',' | ' ') {
+            return Err(field_type.to_string());
+        }
+
+        if !name.is_empty() {
+            let is_dimension = name.chars().all(|c| c.is_ascii_digit());
+            let resolved_name = if is_dimension
+                || name.eq_ignore_ascii_case("frozen")
+                || CqlType::from_type_name(&name) != CqlType::Unknown
+            {
+                name.clone()
+            } else {
+                udt_type_names
+                    .get(&name)
+                    .or_else(|| udt_type_names.get(&name.to_lowercase()))
+                    .cloned()
+                    .ok_or(name.clone())?
+            };
+
+            resolved.push_str(&resolved_name);
+            name.clear();
+        }
+
+        resolved.push(c);
+    }
+
+    resolved.pop();
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // UDTs are resolved by both struct name and type name
+    fn udt_type_names() -> HashMap<String, String> {
+        HashMap::from([
+            ("Address".to_string(), "address".to_string()),
+            ("address".to_string(), "address".to_string()),
+        ])
+    }
+
+    #[test]
+    fn resolves_native_and_udt_types() {
+        assert_eq!(
+            resolve_type("Map<Text, Frozen<Address>>", &udt_type_names()),
+            Ok("Map<Text, Frozen<address>>".to_string())
+        );
+        assert_eq!(
+            resolve_type("Vector<Float, 3>", &udt_type_names()),
+            Ok("Vector<Float, 3>".to_string())
+        );
+    }
+
+    #[test]
+    fn resolves_column_type_overrides() {
+        assert_eq!(resolve_type("text", &udt_type_names()), Ok("text".to_string()));
+        assert_eq!(
+            resolve_type("frozen<list<text>>", &udt_type_names()),
+            Ok("frozen<list<text>>".to_string())
+        );
+        assert_eq!(
+            resolve_type("map<bigint, frozen<address>>", &udt_type_names()),
+            Ok("map<bigint, frozen<address>>".to_string())
+        );
+    }
+
+    #[test]
+    fn resolves_tuple_aliases() {
+        assert_eq!(
+            resolve_type("Tuple3<Int, Text, Frozen<Address>>", &udt_type_names()),
+            Ok("Tuple3<Int, Text, Frozen<address>>".to_string())
+        );
+
+        let tuple16 = format!("Tuple16<{}>", vec!["Int"; 16].join(", "));
+
+        assert_eq!(resolve_type(&tuple16, &udt_type_names()), Ok(tuple16.clone()));
+        assert_eq!(
+            resolve_type("Tuple17<Int, Text>", &udt_type_names()),
+            Err("Tuple17".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_udt_is_error() {
+        assert_eq!(
+            resolve_type("Frozen<UserAddress>", &udt_type_names()),
+            Err("UserAddress".to_string())
+        );
+        assert_eq!(
+            resolve_type("List<Text>!", &udt_type_names()),
+            Err("List<Text>!".to_string())
+        );
+    }
+
+    fn models_dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("charybdis_code_schema_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for (file_name, content) in files {
+            std::fs::write(dir.join(file_name), content).unwrap();
+        }
+
+        dir
+    }
+
+    const USERS: &str = r#"
+        #[charybdis_model(table_name = users, partition_keys = [id], clustering_keys = [])]
+        pub struct User {
+            pub id: Uuid,
+        }
+    "#;
+
+    #[test]
+    fn model_defined_in_two_files_is_error() {
+        let dir = models_dir("duplicate", &[("a.rs", USERS), ("b.rs", USERS)]);
+
+        let result = CodeSchema::default().get_models_from_code(&dir.display().to_string());
+
+        match result {
+            Err(CodeSchemaError::DuplicateModel { kind, name, paths }) => {
+                assert_eq!((kind.as_str(), name.as_str()), ("Table", "users"));
+                assert_eq!(
+                    paths,
+                    (
+                        dir.join("a.rs").display().to_string(),
+                        dir.join("b.rs").display().to_string()
+                    )
+                );
+            }
+            other => panic!("expected duplicate model, got {:?}", other),
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_files_are_skipped() {
+        let invalid_model = "#[charybdis_model(partition_keys = [id], clustering_keys = [])] pub struct Post {}";
+        let dir = models_dir(
+            "skipped",
+            &[
+                ("invalid.rs", "pub struct"),
+                ("posts.rs", invalid_model),
+                ("users.rs", USERS),
+            ],
+        );
+        let mut code_schema = CodeSchema::default();
+
+        code_schema.get_models_from_code(&dir.display().to_string()).unwrap();
+
+        assert!(code_schema.tables.contains_key("users"));
+        assert_eq!(
+            code_schema
+                .skipped_files()
+                .iter()
+                .map(|skipped_file| skipped_file.path.clone())
+                .collect::<Vec<_>>(),
+            [
+                dir.join("invalid.rs").display().to_string(),
+                dir.join("posts.rs").display().to_string()
+            ]
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn aliases_apply_to_declaring_and_importing_files() {
+        let dir = models_dir(
+            "aliases",
+            &[
+                ("lib.rs", "pub use charybdis::macros::charybdis_model as model;"),
+                (
+                    "posts.rs",
+                    r#"
+                    use crate::model;
+
+                    #[model(table_name = posts, partition_keys = [id], clustering_keys = [])]
+                    pub struct Post {
+                        pub id: Uuid,
+                    }
+                    "#,
+                ),
+                (
+                    "tags.rs",
+                    r#"
+                    #[model(table_name = tags, partition_keys = [id], clustering_keys = [])]
+                    pub struct Tag {
+                        pub id: Uuid,
+                    }
+                    "#,
+                ),
+            ],
+        );
+        let mut code_schema = CodeSchema::default();
+
+        code_schema.get_models_from_code(&dir.display().to_string()).unwrap();
+
+        // `tags.rs` doesn't import the alias, so its `model` attribute is some other macro
+        assert!(code_schema.tables.contains_key("posts"));
+        assert!(!code_schema.tables.contains_key("tags"));
+        assert!(code_schema.skipped_files().is_empty());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn alias_declared_for_different_macros_is_error() {
+        let dir = models_dir(
+            "conflicting_aliases",
+            &[
+                ("a.rs", "use charybdis::macros::charybdis_model as model;"),
+                ("b.rs", "use charybdis::macros::charybdis_udt_model as model;"),
+            ],
+        );
+
+        let result = CodeSchema::default().get_models_from_code(&dir.display().to_string());
+
+        match result {
+            Err(CodeSchemaError::ConflictingMacroAlias { alias, macros, paths }) => {
+                assert_eq!(alias, "model");
+                assert_eq!(
+                    macros,
+                    ("charybdis_model".to_string(), "charybdis_udt_model".to_string())
+                );
+                assert_eq!(
+                    paths,
+                    (
+                        dir.join("a.rs").display().to_string(),
+                        dir.join("b.rs").display().to_string()
+                    )
+                );
+            }
+            other => panic!("expected conflicting alias, got {:?}", other),
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,134 @@
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, ExprLit, Lit, Meta, Token};
+
+/// Checks if item with given attributes is compiled with given features. Only `feature = "..."` predicates and
+/// their `all`, `any` and `not` combinations are evaluated. Items gated by other predicates, e.g. `test` or
+/// `target_os`, are considered enabled, as well as all items if features are not provided.
+pub(crate) fn is_enabled(attrs: &[Attribute], features: Option<&[String]>) -> bool {
+    let Some(features) = features else {
+        return true;
+    };
+
+    attrs.iter().filter(|attr| attr.path().is_ident("cfg")).all(|attr| {
+        attr.parse_args::<Meta>()
+            .map_or(true, |predicate| evaluate(&predicate, features).unwrap_or(true))
+    })
+}
+
+/// Evaluates cfg predicate, returning `None` if it depends on anything else than features.
+fn evaluate(predicate: &Meta, features: &[String]) -> Option<bool> {
+    match predicate {
+        Meta::NameValue(name_value) if name_value.path.is_ident("feature") => match &name_value.value {
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(feature), ..
+            }) => Some(features.contains(&feature.value())),
+            _ => None,
+        },
+        Meta::List(list) => {
+            let predicates = list
+                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .ok()?;
+            let mut results = predicates.iter().map(|predicate| evaluate(predicate, features));
+
+            if list.path.is_ident("not") {
+                return match predicates.len() {
+                    1 => results.next().flatten().map(|result| !result),
+                    _ => None,
+                };
+            }
+
+            let results: Vec<Option<bool>> = results.collect();
+
+            if list.path.is_ident("all") {
+                if results.contains(&Some(false)) {
+                    return Some(false);
+                }
+
+                return results.iter().all(|result| result.is_some()).then_some(true);
+            }
+
+            if list.path.is_ident("any") {
+                if results.contains(&Some(true)) {
+                    return Some(true);
+                }
+
+                return results.iter().all(|result| result.is_some()).then_some(false);
+            }
+
+            None
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate_str(predicate: &str, features: &[&str]) -> Option<bool> {
+        let predicate: Meta = syn::parse_str(predicate).unwrap();
+        let features: Vec<String> = features.iter().map(|feature| feature.to_string()).collect();
+
+        evaluate(&predicate, &features)
+    }
+
+    #[test]
+    fn evaluates_feature() {
+        assert_eq!(evaluate_str(r#"feature = "models""#, &["models"]), Some(true));
+        assert_eq!(evaluate_str(r#"feature = "models""#, &[]), Some(false));
+        assert_eq!(evaluate_str("feature = models", &["models"]), None);
+    }
+
+    #[test]
+    fn evaluates_all() {
+        assert_eq!(
+            evaluate_str(r#"all(feature = "a", feature = "b")"#, &["a", "b"]),
+            Some(true)
+        );
+        assert_eq!(
+            evaluate_str(r#"all(feature = "a", feature = "b")"#, &["a"]),
+            Some(false)
+        );
+        assert_eq!(evaluate_str(r#"all(feature = "a", test)"#, &[]), Some(false));
+        assert_eq!(evaluate_str(r#"all(feature = "a", test)"#, &["a"]), None);
+        assert_eq!(evaluate_str("all()", &[]), Some(true));
+    }
+
+    #[test]
+    fn evaluates_any() {
+        assert_eq!(evaluate_str(r#"any(feature = "a", feature = "b")"#, &["b"]), Some(true));
+        assert_eq!(evaluate_str(r#"any(feature = "a", feature = "b")"#, &[]), Some(false));
+        assert_eq!(evaluate_str(r#"any(feature = "a", test)"#, &["a"]), Some(true));
+        assert_eq!(evaluate_str(r#"any(feature = "a", test)"#, &[]), None);
+        assert_eq!(evaluate_str("any()", &[]), Some(false));
+    }
+
+    #[test]
+    fn evaluates_not() {
+        assert_eq!(evaluate_str(r#"not(feature = "a")"#, &[]), Some(true));
+        assert_eq!(evaluate_str(r#"not(feature = "a")"#, &["a"]), Some(false));
+        assert_eq!(
+            evaluate_str(r#"not(any(feature = "a", feature = "b"))"#, &["b"]),
+            Some(false)
+        );
+        assert_eq!(evaluate_str("not(test)", &[]), None);
+        assert_eq!(evaluate_str(r#"not(feature = "a", feature = "b")"#, &[]), None);
+    }
+
+    #[test]
+    fn items_with_unknown_predicates_are_enabled() {
+        let item: syn::ItemStruct = syn::parse_str(
+            r#"
+            #[cfg(feature = "models")]
+            #[cfg(not(test))]
+            struct User {}
+            "#,
+        )
+        .unwrap();
+        let features = vec!["models".to_string()];
+
+        assert!(is_enabled(&item.attrs, Some(&features)));
+        assert!(!is_enabled(&item.attrs, Some(&[])));
+        assert!(is_enabled(&item.attrs, None));
+    }
+}
@@ -0,0 +1,230 @@
+use std::path::{Component, Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::errors::CodeSchemaError;
+
+/// Scope of model discovery in the current directory. Globs are relative to the current directory and match files
+/// or directories, e.g. `src/models`, `crates/*/src/models` or `**/tests`. `*` matches any part of file or
+/// directory name, `?` single character and `**` any number of directories.
+#[derive(Debug, Clone, Default)]
+pub struct ModelDiscovery {
+    /// Files and directories to parse models from. All `.rs` files are parsed if empty.
+    pub models_paths: Vec<String>,
+    /// Files and directories to skip. `target` directories are always skipped.
+    pub exclude: Vec<String>,
+    /// Enabled features that `cfg(feature = "...")` gates of models are evaluated with. If not set, gated models
+    /// are always included.
+    pub features: Option<Vec<String>>,
+}
+
+impl ModelDiscovery {
+    pub(crate) fn validate(&self) -> Result<(), CodeSchemaError> {
+        for pattern in self.models_paths.iter().chain(&self.exclude) {
+            let reason = if normalize(pattern).is_empty() {
+                Some("pattern is empty")
+            } else if Path::new(pattern).is_absolute() {
+                Some("pattern must be relative to the current directory")
+            } else if pattern.split('/').any(|segment| segment == "..") {
+                Some("pattern can't reference parent directory")
+            } else {
+                None
+            };
+
+            if let Some(reason) = reason {
+                return Err(CodeSchemaError::InvalidGlob {
+                    pattern: pattern.clone(),
+                    reason: reason.to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `.rs` files of the current directory that are in scope, sorted by path.
+    pub(crate) fn model_files(&self, current_dir: &Path) -> Vec<PathBuf> {
+        let walker = WalkDir::new(current_dir)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| {
+                let is_target = e.file_type().is_dir() && e.file_name() == "target";
+                let is_excluded = e
+                    .path()
+                    .strip_prefix(current_dir)
+                    .is_ok_and(|path| self.is_excluded(path));
+
+                !is_target && !is_excluded
+            });
+
+        walker
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file() && entry.path().extension().is_some_and(|ext| ext == "rs"))
+            .filter(|entry| {
+                entry
+                    .path()
+                    .strip_prefix(current_dir)
+                    .is_ok_and(|path| self.is_included(path))
+            })
+            .map(|entry| entry.into_path())
+            .collect()
+    }
+
+    /// Excluded directories are not walked, so only path itself has to be checked.
+    fn is_excluded(&self, path: &Path) -> bool {
+        let segments = segments(path);
+
+        !segments.is_empty() && self.exclude.iter().any(|pattern| matches_glob(pattern, &segments))
+    }
+
+    fn is_included(&self, path: &Path) -> bool {
+        if self.models_paths.is_empty() {
+            return true;
+        }
+
+        let segments = segments(path);
+
+        // file is included if it or any of its parent directories matches
+        (1..=segments.len()).any(|len| {
+            self.models_paths
+                .iter()
+                .any(|pattern| matches_glob(pattern, &segments[..len]))
+        })
+    }
+}
+
+fn normalize(pattern: &str) -> &str {
+    let pattern = pattern.trim();
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+
+    pattern.trim_end_matches('/')
+}
+
+fn segments(path: &Path) -> Vec<&str> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect()
+}
+
+fn matches_glob(pattern: &str, path: &[&str]) -> bool {
+    let pattern: Vec<&str> = normalize(pattern)
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect();
+
+    matches_segments(&pattern, path)
+}
+
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skipped| matches_segments(rest, &path[skipped..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                matches_name(&segment.chars().collect::<Vec<_>>(), &name.chars().collect::<Vec<_>>())
+                    && matches_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn matches_name(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skipped| matches_name(rest, &name[skipped..])),
+        Some(('?', rest)) => !name.is_empty() && matches_name(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && matches_name(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        matches_glob(pattern, &segments(Path::new(path)))
+    }
+
+    fn discovery(models_paths: &[&str], exclude: &[&str]) -> ModelDiscovery {
+        ModelDiscovery {
+            models_paths: models_paths.iter().map(|path| path.to_string()).collect(),
+            exclude: exclude.iter().map(|path| path.to_string()).collect(),
+            features: None,
+        }
+    }
+
+    #[test]
+    fn double_star_matches_any_number_of_directories() {
+        assert!(matches("**/tests", "tests"));
+        assert!(matches("**/tests", "crates/app/tests"));
+        assert!(matches("src/**/models", "src/models"));
+        assert!(matches("src/**/models", "src/app/v1/models"));
+        assert!(matches("src/**", "src/app/models.rs"));
+        assert!(!matches("**/tests", "crates/tests/models"));
+    }
+
+    #[test]
+    fn star_matches_part_of_single_name() {
+        assert!(matches("crates/*/src", "crates/app/src"));
+        assert!(matches("src/*_models.rs", "src/user_models.rs"));
+        assert!(matches("src/*.rs", "src/.rs"));
+        assert!(!matches("crates/*/src", "crates/app/nested/src"));
+        assert!(!matches("src/*.rs", "src/models.rs.bak"));
+    }
+
+    #[test]
+    fn question_mark_matches_single_character() {
+        assert!(matches("src/v?/models", "src/v1/models"));
+        assert!(!matches("src/v?/models", "src/v/models"));
+        assert!(!matches("src/v?/models", "src/v10/models"));
+    }
+
+    #[test]
+    fn patterns_are_anchored_to_current_directory_and_whole_names() {
+        assert!(matches("./src/models/", "src/models"));
+        assert!(!matches("models", "src/models"));
+        assert!(!matches("src", "app/src"));
+        assert!(!matches("src/model", "src/models"));
+        assert!(!matches("src/models", "src/models/user.rs"));
+    }
+
+    #[test]
+    fn files_in_matching_directories_are_included() {
+        let discovery = discovery(&["crates/*/src/models", "src/lib.rs"], &[]);
+
+        assert!(discovery.is_included(Path::new("crates/app/src/models/user.rs")));
+        assert!(discovery.is_included(Path::new("src/lib.rs")));
+        assert!(!discovery.is_included(Path::new("src/main.rs")));
+        assert!(!discovery.is_included(Path::new("crates/app/src/main.rs")));
+        assert!(ModelDiscovery::default().is_included(Path::new("src/main.rs")));
+    }
+
+    #[test]
+    fn matching_paths_are_excluded() {
+        let discovery = discovery(&[], &["**/tests", "examples/*.rs"]);
+
+        assert!(discovery.is_excluded(Path::new("crates/app/tests")));
+        assert!(discovery.is_excluded(Path::new("examples/users.rs")));
+        assert!(!discovery.is_excluded(Path::new("examples/users/main.rs")));
+        assert!(!discovery.is_excluded(Path::new("")));
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        for pattern in ["", " ./ ", "/src/models", "src/../models"] {
+            let result = discovery(&[pattern], &[]).validate();
+
+            assert!(
+                matches!(result, Err(CodeSchemaError::InvalidGlob { .. })),
+                "{:?} should be rejected",
+                pattern
+            );
+        }
+
+        assert!(discovery(&["src/**/models"], &["./target/"]).validate().is_ok());
+    }
+}
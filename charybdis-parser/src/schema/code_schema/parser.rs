@@ -1,136 +1,481 @@
-use std::fs::File;
-use std::io::Read;
+use std::collections::HashMap;
+use std::fs;
 use std::path::Path;
 
-use syn::{Fields, GenericArgument, Item, ItemStruct, PathArguments};
+use syn::{Attribute, Fields, GenericArgument, Item, ItemMod, ItemStruct, PathArguments, Type, UseTree};
 
-use crate::fields::CharybdisFields;
-use crate::schema::code_schema::ModelMacro;
+use crate::errors::CodeSchemaError;
+use crate::fields::{is_tuple_type_name, CharybdisFields};
+use crate::schema::code_schema::{cfg, ModelMacro};
 use crate::schema::SchemaObject;
 use crate::traits::CharybdisMacroArgs;
 
-pub(crate) fn parse_file_as_string(path: &Path) -> String {
-    let mut file_content = String::new();
+/// Reads and parses Rust source file.
+pub(crate) fn parse_file(path: &Path) -> Result<syn::File, CodeSchemaError> {
+    let file_content = fs::read_to_string(path).map_err(|error| CodeSchemaError::FileReadError {
+        path: path.display().to_string(),
+        error,
+    })?;
 
-    File::open(path)
-        .unwrap()
-        .read_to_string(&mut file_content)
-        .unwrap_or_else(|_| panic!("Unable to read file: {}", path.display()));
-
-    file_content
+    syn::parse_file(&file_content).map_err(|error| CodeSchemaError::FileParseError {
+        path: path.display().to_string(),
+        error,
+    })
 }
 
-pub(crate) fn parse_charybdis_model_def(ast: &syn::File, model_macro: ModelMacro) -> Vec<SchemaObject> {
-    let mut schema_objects: Vec<SchemaObject> = Vec::new();
+/// Modules that model macros can be used from by path, e.g. `#[charybdis::macros::charybdis_model(...)]`.
+const MACRO_MODULES: [&[&str]; 2] = [&["charybdis", "macros"], &["charybdis_macros"]];
 
-    for item in &ast.items {
-        if let Item::Struct(item_struct) = item {
-            // If the struct doesn't have the required macro, continue to the next item.
-            if !item_struct
-                .attrs
+/// Returns schema objects of structs with given model macro, along with their struct names. Structs in inline
+/// modules are included, unless they or their modules are disabled by `cfg(feature = "...")` for given features.
+/// Macro can also be used by any of given aliases, as pairs of macro name and its alias. Fails on invalid macro
+/// arguments, e.g. missing `table_name`.
+pub(crate) fn parse_charybdis_model_def(
+    ast: &syn::File,
+    model_macro: ModelMacro,
+    aliases: &[(String, String)],
+    features: Option<&[String]>,
+) -> syn::Result<Vec<(String, SchemaObject)>> {
+    let macro_name = model_macro.to_string();
+    let macro_names: Vec<&str> = std::iter::once(macro_name.as_str())
+        .chain(
+            aliases
                 .iter()
-                .any(|attr| attr.path().is_ident(model_macro.to_string().as_str()))
-            {
-                continue;
+                .filter(|(name, _)| *name == macro_name)
+                .map(|(_, alias)| alias.as_str()),
+        )
+        .collect();
+
+    let mut schema_objects: Vec<(String, SchemaObject)> = Vec::new();
+    let is_macro = |path: &syn::Path| is_model_macro(path, &macro_names);
+    collect_schema_objects(&ast.items, &model_macro, &is_macro, features, &mut schema_objects)?;
+
+    Ok(schema_objects)
+}
+
+/// Returns schema objects of structs with given alias of model macro only, e.g. alias imported with `use` from a
+/// module that declares it, along with their struct names.
+pub(crate) fn parse_alias_models(
+    ast: &syn::File,
+    model_macro: ModelMacro,
+    alias: &str,
+    features: Option<&[String]>,
+) -> syn::Result<Vec<(String, SchemaObject)>> {
+    let mut schema_objects: Vec<(String, SchemaObject)> = Vec::new();
+    let is_alias = |path: &syn::Path| path.is_ident(alias);
+    collect_schema_objects(&ast.items, &model_macro, &is_alias, features, &mut schema_objects)?;
+
+    Ok(schema_objects)
+}
+
+fn collect_schema_objects(
+    items: &[Item],
+    model_macro: &ModelMacro,
+    is_macro: &impl Fn(&syn::Path) -> bool,
+    features: Option<&[String]>,
+    schema_objects: &mut Vec<(String, SchemaObject)>,
+) -> syn::Result<()> {
+    for item in items {
+        match item {
+            Item::Struct(item_struct) => {
+                if !cfg::is_enabled(&item_struct.attrs, features) {
+                    continue;
+                }
+
+                // If the struct doesn't have the required macro, continue to the next item.
+                let Some(attr) = item_struct.attrs.iter().find(|attr| is_macro(attr.path())) else {
+                    continue;
+                };
+
+                schema_objects.push((
+                    item_struct.ident.to_string(),
+                    extract_schema_object(item_struct, attr, model_macro)?,
+                ));
+            }
+            Item::Mod(ItemMod {
+                attrs,
+                content: Some((_, items)),
+                ..
+            }) if cfg::is_enabled(attrs, features) => {
+                collect_schema_objects(items, model_macro, is_macro, features, schema_objects)?;
             }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
 
-            schema_objects.push(extract_schema_object(item_struct, &model_macro));
+/// Model macro can be used by its name, e.g. `#[charybdis_model]`, by its path from `charybdis::macros` or
+/// `charybdis_macros`, or by alias imported with `use ... as`. First of macro names is the name of the macro.
+fn is_model_macro(path: &syn::Path, macro_names: &[&str]) -> bool {
+    let segments: Vec<String> = path.segments.iter().map(|segment| segment.ident.to_string()).collect();
+
+    match segments.split_last() {
+        Some((name, [])) => macro_names.contains(&name.as_str()),
+        Some((name, module)) => {
+            *name == macro_names[0] && MACRO_MODULES.iter().any(|macro_module| module == *macro_module)
         }
+        None => false,
     }
+}
+
+/// Collects aliases of model macros imported with `use ... as`, e.g. `use charybdis::macros::charybdis_model as
+/// model`, as pairs of macro name and its alias.
+pub(crate) fn macro_aliases(ast: &syn::File) -> Vec<(String, String)> {
+    let macro_names = ModelMacro::ALL.map(|m| m.to_string());
+    let mut aliases = vec![];
 
-    schema_objects
+    collect_macro_aliases(&ast.items, &macro_names, &mut aliases);
+
+    aliases
+}
+
+fn collect_macro_aliases(items: &[Item], macro_names: &[String], aliases: &mut Vec<(String, String)>) {
+    for item in items {
+        match item {
+            Item::Use(item_use) => collect_use_tree_aliases(&item_use.tree, macro_names, aliases),
+            Item::Mod(ItemMod {
+                content: Some((_, items)),
+                ..
+            }) => collect_macro_aliases(items, macro_names, aliases),
+            _ => {}
+        }
+    }
 }
 
-fn extract_schema_object(item_struct: &ItemStruct, model_macro: &ModelMacro) -> SchemaObject {
+fn collect_use_tree_aliases(tree: &UseTree, macro_names: &[String], aliases: &mut Vec<(String, String)>) {
+    match tree {
+        UseTree::Path(use_path) => collect_use_tree_aliases(&use_path.tree, macro_names, aliases),
+        UseTree::Group(use_group) => use_group
+            .items
+            .iter()
+            .for_each(|tree| collect_use_tree_aliases(tree, macro_names, aliases)),
+        UseTree::Rename(use_rename) if macro_names.iter().any(|name| use_rename.ident == name) => {
+            aliases.push((use_rename.ident.to_string(), use_rename.rename.to_string()))
+        }
+        _ => {}
+    }
+}
+
+/// Collects names imported with `use` without renaming, e.g. `model` of `use crate::model`. They can be aliases of
+/// model macros declared in other modules.
+pub(crate) fn imported_names(ast: &syn::File) -> Vec<String> {
+    let mut names = vec![];
+
+    collect_imported_names(&ast.items, &mut names);
+
+    names
+}
+
+fn collect_imported_names(items: &[Item], names: &mut Vec<String>) {
+    for item in items {
+        match item {
+            Item::Use(item_use) => collect_use_tree_names(&item_use.tree, names),
+            Item::Mod(ItemMod {
+                content: Some((_, items)),
+                ..
+            }) => collect_imported_names(items, names),
+            _ => {}
+        }
+    }
+}
+
+fn collect_use_tree_names(tree: &UseTree, names: &mut Vec<String>) {
+    match tree {
+        UseTree::Path(use_path) => collect_use_tree_names(&use_path.tree, names),
+        UseTree::Group(use_group) => use_group
+            .items
+            .iter()
+            .for_each(|tree| collect_use_tree_names(tree, names)),
+        UseTree::Name(use_name) => names.push(use_name.ident.to_string()),
+        _ => {}
+    }
+}
+
+fn extract_schema_object(
+    item_struct: &ItemStruct,
+    attr: &Attribute,
+    model_macro: &ModelMacro,
+) -> syn::Result<SchemaObject> {
     let mut schema_object: SchemaObject = SchemaObject::new();
+    let struct_name = &item_struct.ident;
+    let missing_argument = |argument: &str| {
+        syn::Error::new_spanned(
+            attr,
+            format!("`{}`: {} is required in {} macro", struct_name, argument, model_macro),
+        )
+    };
 
-    for attr in &item_struct.attrs {
-        if attr.path().is_ident(model_macro.to_string().as_str()) {
-            let args: CharybdisMacroArgs = attr.parse_args().unwrap();
+    let args: CharybdisMacroArgs = attr
+        .parse_args()
+        .map_err(|e| syn::Error::new(e.span(), format!("`{}`: {}", struct_name, e)))?;
 
-            if let Some(table_name) = args.table_name {
-                schema_object.table_name = table_name;
-            } else if model_macro == &ModelMacro::Table {
-                panic!("Table name is required in charybdis_model macro");
-            }
+    if let Some(table_name) = args.table_name {
+        schema_object.table_name = table_name;
+    } else if model_macro == &ModelMacro::Table {
+        return Err(missing_argument("table_name"));
+    }
 
-            if let Some(base_table) = args.base_table {
-                schema_object.base_table = base_table;
-            } else if model_macro == &ModelMacro::MaterializedView {
-                panic!("Base table is required in charybdis_view_model macro");
-            }
+    if let Some(base_table) = args.base_table {
+        schema_object.base_table = base_table;
+    } else if model_macro == &ModelMacro::MaterializedView {
+        return Err(missing_argument("base_table"));
+    }
 
-            if let Some(type_name) = args.type_name {
-                schema_object.type_name = type_name;
-            } else if model_macro == &ModelMacro::Udt {
-                panic!("Type name is required in charybdis_udt_model macro");
-            }
+    if let Some(type_name) = args.type_name {
+        schema_object.type_name = type_name;
+    } else if model_macro == &ModelMacro::Udt {
+        return Err(missing_argument("type_name"));
+    }
 
-            if let Some(partition_keys) = args.partition_keys {
-                schema_object.partition_keys = partition_keys;
-            } else if model_macro == &ModelMacro::Table {
-                panic!("Partition keys are required in charybdis_model macro");
-            }
+    if let Some(partition_keys) = args.partition_keys {
+        schema_object.partition_keys = partition_keys;
+    } else if model_macro == &ModelMacro::Table {
+        return Err(missing_argument("partition_keys"));
+    }
 
-            schema_object.clustering_keys = args.clustering_keys.unwrap_or_default();
-            schema_object.static_columns = args.static_columns.unwrap_or_default();
+    schema_object.clustering_keys = args.clustering_keys.unwrap_or_default();
+    schema_object.static_columns = args.static_columns.unwrap_or_default();
 
-            if let Some(gsi) = args.global_secondary_indexes {
-                gsi.iter().for_each(|global_idx| {
-                    schema_object
-                        .global_secondary_indexes
-                        .push(("".to_string(), global_idx.to_string()));
-                });
-            }
+    if let Some(gsi) = args.global_secondary_indexes {
+        gsi.iter().for_each(|global_idx| {
+            schema_object
+                .global_secondary_indexes
+                .push(("".to_string(), global_idx.to_string()));
+        });
+    }
 
-            if let Some(lsi) = args.local_secondary_indexes {
-                lsi.iter().for_each(|local_idx| {
-                    schema_object
-                        .local_secondary_indexes
-                        .push(("".to_string(), local_idx.clone()));
-                });
-            }
+    if let Some(lsi) = args.local_secondary_indexes {
+        lsi.iter().for_each(|local_idx| {
+            schema_object
+                .local_secondary_indexes
+                .push(("".to_string(), local_idx.clone()));
+        });
+    }
 
-            schema_object.table_options = args.table_options;
+    if let Some(vector_indexes) = args.vector_indexes {
+        vector_indexes.iter().for_each(|vector_idx| {
+            schema_object
+                .vector_indexes
+                .push(("".to_string(), vector_idx.to_string()));
+        });
+    }
+
+    // plain indexes are created same as global secondary indexes, just with optional name
+    for index in args.indexes.unwrap_or_default() {
+        if index.is_plain_index() {
+            schema_object.global_secondary_indexes.push((index.name, index.column));
+        } else {
+            schema_object.indexes.push(index);
         }
     }
 
+    schema_object.table_options = args.table_options;
+
     // parse struct fields
     if let Fields::Named(fields_named) = &item_struct.fields {
         let db_fields = CharybdisFields::db_fields(fields_named);
 
+        // macro arguments reference rust field names, while schema uses column names
+        let column_names: HashMap<String, String> = db_fields
+            .iter()
+            .map(|field| (field.name.clone(), field.column_name().to_string()))
+            .collect();
+        let to_column = |name: &mut String| {
+            if let Some(column_name) = column_names.get(name.as_str()) {
+                *name = column_name.clone();
+            }
+        };
+
+        schema_object.partition_keys.iter_mut().for_each(to_column);
+        schema_object.clustering_keys.iter_mut().for_each(to_column);
+        schema_object.static_columns.iter_mut().for_each(to_column);
+        schema_object
+            .global_secondary_indexes
+            .iter_mut()
+            .for_each(|(_, target)| to_column(target));
+        schema_object
+            .local_secondary_indexes
+            .iter_mut()
+            .for_each(|(_, target)| to_column(target));
+        schema_object
+            .vector_indexes
+            .iter_mut()
+            .for_each(|(_, target)| to_column(target));
+        schema_object
+            .indexes
+            .iter_mut()
+            .for_each(|index| to_column(&mut index.column));
+
         for field in db_fields {
-            let field_name = field.ident.to_string();
-            let field_type = field
-                .column_type_override
-                .unwrap_or_else(|| type_with_arguments(&field.ty_path));
+            let field_name = field.column_name().to_string();
+            let field_type = field.column_type_override.unwrap_or_else(|| code_type(&field.ty_path));
             let is_static = schema_object.static_columns.contains(&field_name);
 
+            if let Some(renamed_from) = field.renamed_from {
+                schema_object.renamed_fields.insert(field_name.clone(), renamed_from);
+            }
+
             schema_object.push_field(field_name, field_type, is_static);
         }
     }
 
-    schema_object
+    Ok(schema_object)
 }
 
-fn type_with_arguments(type_path: &syn::TypePath) -> String {
-    let first_segment = &type_path.path.segments[0];
-    let mut type_name = quote::quote! { #type_path }.to_string();
+/// Type of the field as written in code, without `Option` and module paths, e.g. `Frozen<List<Address>>`.
+/// `Tuple3`..`Tuple16` aliases are written as `Tuple`. UDT struct names are resolved to type names once all
+/// models are parsed.
+fn code_type(type_path: &syn::TypePath) -> String {
+    let segment = type_path.path.segments.last().expect("type path must have a segment");
+    let name = segment.ident.to_string();
 
-    // Check if the type is an Option<T>
-    if first_segment.ident == "Option" {
-        if let PathArguments::AngleBracketed(angle_bracketed_args) = &first_segment.arguments {
-            if let Some(GenericArgument::Type(inner_type)) = angle_bracketed_args.args.first() {
-                // Return the inner type of Option<T>
-                type_name = quote::quote! { #inner_type }.to_string();
-            }
-        }
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(angle_bracketed_args) => angle_bracketed_args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(Type::Path(arg_path)) => Some(code_type(arg_path)),
+                GenericArgument::Type(arg_type) => Some(quote::quote! { #arg_type }.to_string()),
+                // vector dimensions, e.g. `Vector<Float, 768>`
+                GenericArgument::Const(arg_const) => Some(quote::quote! { #arg_const }.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<String>>(),
+        _ => vec![],
+    };
+
+    if name == "Option" && args.len() == 1 {
+        return args[0].clone();
+    }
+
+    let name = if is_tuple_type_name(&name) {
+        "Tuple".to_string()
+    } else {
+        name
+    };
+
+    if args.is_empty() {
+        name
+    } else {
+        format!("{}<{}>", name, args.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model_names(source: &str, features: Option<&[String]>) -> Vec<String> {
+        let ast = syn::parse_file(source).unwrap();
+
+        parse_charybdis_model_def(&ast, ModelMacro::Table, &macro_aliases(&ast), features)
+            .unwrap()
+            .into_iter()
+            .map(|(struct_name, _)| struct_name)
+            .collect()
+    }
+
+    #[test]
+    fn model_macro_is_matched_by_name_and_charybdis_path() {
+        let source = r#"
+            #[charybdis_model(table_name = users, partition_keys = [id], clustering_keys = [])]
+            struct User { id: Uuid }
+
+            #[charybdis::macros::charybdis_model(table_name = posts, partition_keys = [id], clustering_keys = [])]
+            struct Post { id: Uuid }
+
+            #[::charybdis_macros::charybdis_model(table_name = tags, partition_keys = [id], clustering_keys = [])]
+            struct Tag { id: Uuid }
+
+            #[other::charybdis_model(table_name = others, partition_keys = [id], clustering_keys = [])]
+            struct Other { id: Uuid }
+
+            #[charybdis::macros::charybdis_udt_model(type_name = address)]
+            struct Address { street: Text }
+        "#;
+
+        assert_eq!(model_names(source, None), vec!["User", "Post", "Tag"]);
+    }
+
+    #[test]
+    fn model_macro_is_matched_by_alias() {
+        let source = r#"
+            use charybdis::macros::{charybdis_model as model, charybdis_udt_model as udt};
+
+            #[model(table_name = users, partition_keys = [id], clustering_keys = [])]
+            struct User { id: Uuid }
+
+            #[udt(type_name = address)]
+            struct Address { street: Text }
+        "#;
+        let ast = syn::parse_file(source).unwrap();
+
+        assert_eq!(
+            macro_aliases(&ast),
+            vec![
+                ("charybdis_model".to_string(), "model".to_string()),
+                ("charybdis_udt_model".to_string(), "udt".to_string()),
+            ]
+        );
+        assert_eq!(model_names(source, None), vec!["User"]);
+
+        // alias imported in another file
+        let ast =
+            syn::parse_file("#[model(table_name = users, partition_keys = [id])] struct User { id: Uuid }").unwrap();
+        let aliases = vec![("charybdis_model".to_string(), "model".to_string())];
+
+        assert_eq!(
+            parse_charybdis_model_def(&ast, ModelMacro::Table, &aliases, None)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(parse_charybdis_model_def(&ast, ModelMacro::Table, &[], None)
+            .unwrap()
+            .is_empty());
     }
 
-    // strip if full path is provided
-    if type_name.contains("::") {
-        type_name = type_name.split("::").last().unwrap().to_string();
+    #[test]
+    fn models_in_inline_modules_are_gated_by_features() {
+        let source = r#"
+            mod models {
+                #[charybdis_model(table_name = users, partition_keys = [id], clustering_keys = [])]
+                struct User { id: Uuid }
+
+                #[cfg(feature = "posts")]
+                mod posts {
+                    #[charybdis_model(table_name = posts, partition_keys = [id], clustering_keys = [])]
+                    struct Post { id: Uuid }
+                }
+            }
+        "#;
+        let features = vec!["posts".to_string()];
+
+        assert_eq!(model_names(source, None), vec!["User", "Post"]);
+        assert_eq!(model_names(source, Some(&features)), vec!["User", "Post"]);
+        assert_eq!(model_names(source, Some(&[])), vec!["User"]);
     }
 
-    type_name
+    #[test]
+    fn invalid_model_arguments_are_errors() {
+        let missing_table_name =
+            syn::parse_file("#[charybdis_model(partition_keys = [id], clustering_keys = [])] struct User { id: Uuid }")
+                .unwrap();
+        let invalid_arguments = syn::parse_file("#[charybdis_model(table_name)] struct User { id: Uuid }").unwrap();
+
+        let error = parse_charybdis_model_def(&missing_table_name, ModelMacro::Table, &[], None).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "`User`: table_name is required in charybdis_model macro"
+        );
+        assert!(
+            parse_charybdis_model_def(&invalid_arguments, ModelMacro::Table, &[], None)
+                .unwrap_err()
+                .to_string()
+                .starts_with("`User`: ")
+        );
+    }
 }
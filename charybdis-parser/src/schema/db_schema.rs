@@ -1,19 +1,39 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
-use colored::Colorize;
 use scylla::client::session::Session;
+use scylla::value::{CqlValue, Row};
 use serde::{Deserialize, Serialize};
 use serde_json::to_string_pretty;
 
 use crate::errors::DbSchemaParserError;
-use crate::schema::secondary_indexes::{IndexTarget, SecondaryIndex};
+use crate::schema::secondary_indexes::{IndexDefinition, IndexTarget, IndexTargetKind, SecondaryIndex};
+use crate::schema::table_options::{TableOptionValue, TableOptions};
 use crate::schema::{SchemaObject, SchemaObjects};
 
+/// Columns of `system_schema.tables` and `system_schema.views` that can be set in table options.
+const TABLE_OPTIONS: [&str; 12] = [
+    "bloom_filter_fp_chance",
+    "caching",
+    "comment",
+    "compaction",
+    "compression",
+    "crc_check_chance",
+    "default_time_to_live",
+    "gc_grace_seconds",
+    "max_index_interval",
+    "memtable_flush_period_in_ms",
+    "min_index_interval",
+    "speculative_retry",
+];
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DbSchema {
     pub tables: SchemaObjects,
     pub udts: SchemaObjects,
     pub materialized_views: SchemaObjects,
+    /// Missing in serialized `CodeSchema`, so it can be read as `DbSchema` as well.
+    #[serde(default)]
     pub keyspace_name: String,
 }
 
@@ -23,7 +43,7 @@ pub struct DbSchema {
  * It is used to compare the current state to the desired state of the database schema.
  */
 impl DbSchema {
-    pub async fn new(session: &Session, keyspace_name: String) -> DbSchema {
+    pub async fn new(session: &Session, keyspace_name: String) -> Result<DbSchema, DbSchemaParserError> {
         let mut current_schema = DbSchema {
             tables: HashMap::new(),
             udts: HashMap::new(),
@@ -31,49 +51,11 @@ impl DbSchema {
             keyspace_name,
         };
 
-        current_schema
-            .get_tables_from_system_schema(session)
-            .await
-            .map_err(|e| {
-                println!(
-                    "{}\n",
-                    format!("Error getting tables from system_schema: {}", e)
-                        .bright_red()
-                        .bold()
-                );
-                e
-            })
-            .unwrap();
-
-        current_schema
-            .get_udts_from_system_schema(session)
-            .await
-            .map_err(|e| {
-                println!(
-                    "{}\n",
-                    format!("Error getting udts from system_schema: {}", e)
-                        .bright_red()
-                        .bold()
-                );
-                e
-            })
-            .unwrap();
-
-        current_schema
-            .get_mvs_from_system_schema(session)
-            .await
-            .map_err(|e| {
-                println!(
-                    "{}\n",
-                    format!("Error getting materialized views from system_schema: {}", e)
-                        .bright_red()
-                        .bold()
-                );
-                e
-            })
-            .unwrap();
-
-        current_schema
+        current_schema.get_tables_from_system_schema(session).await?;
+        current_schema.get_udts_from_system_schema(session).await?;
+        current_schema.get_mvs_from_system_schema(session).await?;
+
+        Ok(current_schema)
     }
 
     async fn get_tables_from_system_schema(&mut self, session: &Session) -> Result<(), DbSchemaParserError> {
@@ -111,7 +93,7 @@ impl DbSchema {
         // get columns and types for provided table
         let cql = r#"
             SELECT
-                column_name, type
+                column_name, type, kind
             FROM system_schema.columns
             WHERE keyspace_name = ? 
                 AND table_name = ?
@@ -122,12 +104,16 @@ impl DbSchema {
             .await?
             .into_rows_result()?;
 
-        for row in result.rows::<(String, String)>()? {
-            let str_value: (String, String) = row?;
-            self.tables
-                .get_mut(table_name)
-                .unwrap()
-                .push_field(str_value.0, str_value.1, false);
+        for row in result.rows::<(String, String, String)>()? {
+            let (column_name, column_type, kind) = row?;
+            let table_schema = self.tables.get_mut(table_name).unwrap();
+            let is_static = kind == "static";
+
+            if is_static {
+                table_schema.static_columns.push(column_name.clone());
+            }
+
+            table_schema.push_field(column_name, column_type, is_static);
         }
 
         Ok(())
@@ -140,7 +126,7 @@ impl DbSchema {
     ) -> Result<(), DbSchemaParserError> {
         // get partition keys for provided table
         let cql = r#"
-            SELECT column_name
+            SELECT column_name, position
             FROM system_schema.columns
             WHERE keyspace_name = ?
                 AND table_name = ?
@@ -152,14 +138,11 @@ impl DbSchema {
             .await?
             .into_rows_result()?;
 
-        for row in result.rows::<(String,)>()? {
-            let str_value: (String,) = row?;
-            self.tables
-                .get_mut(table_name)
-                .unwrap()
-                .partition_keys
-                .push(str_value.0);
-        }
+        let mut partition_keys = result.rows::<(String, i32)>()?.collect::<Result<Vec<_>, _>>()?;
+        partition_keys.sort_by_key(|(_, position)| *position);
+
+        self.tables.get_mut(table_name).unwrap().partition_keys =
+            partition_keys.into_iter().map(|(column_name, _)| column_name).collect();
 
         Ok(())
     }
@@ -169,9 +152,28 @@ impl DbSchema {
         table_name: &String,
         session: &Session,
     ) -> Result<(), DbSchemaParserError> {
-        // get partition keys for provided table
+        // get clustering keys for provided table
+        let clustering_keys = Self::clustering_keys(&self.keyspace_name, table_name, session).await?;
+        let options = Self::table_options(&self.keyspace_name, "tables", "table_name", table_name, session).await?;
+        let table_schema = self.tables.get_mut(table_name).unwrap();
+
+        table_schema.table_options = Self::table_options_clause(&clustering_keys, options);
+        table_schema.clustering_keys = clustering_keys
+            .into_iter()
+            .map(|(column_name, _)| column_name)
+            .collect();
+
+        Ok(())
+    }
+
+    /// Clustering keys ordered by position, together with their clustering order.
+    async fn clustering_keys(
+        keyspace_name: &String,
+        table_name: &String,
+        session: &Session,
+    ) -> Result<Vec<(String, String)>, DbSchemaParserError> {
         let cql = r#"
-            SELECT column_name
+            SELECT column_name, clustering_order, position
             FROM system_schema.columns
             WHERE keyspace_name = ?
                 AND table_name = ?
@@ -179,20 +181,87 @@ impl DbSchema {
             ALLOW FILTERING"#;
 
         let result = session
-            .query_unpaged(cql, (&self.keyspace_name, &table_name))
+            .query_unpaged(cql, (keyspace_name, table_name))
             .await?
             .into_rows_result()?;
 
-        for row in result.rows::<(String,)>()? {
-            let str_value: (String,) = row?;
-            self.tables
-                .get_mut(table_name)
-                .unwrap()
-                .clustering_keys
-                .push(str_value.0);
+        let mut clustering_keys = result.rows::<(String, String, i32)>()?.collect::<Result<Vec<_>, _>>()?;
+        clustering_keys.sort_by_key(|(_, _, position)| *position);
+
+        Ok(clustering_keys
+            .into_iter()
+            .map(|(column_name, clustering_order, _)| (column_name, clustering_order))
+            .collect())
+    }
+
+    /// Options of table or view from `system_schema.tables` or `system_schema.views`.
+    async fn table_options(
+        keyspace_name: &String,
+        schema_table: &str,
+        name_column: &str,
+        name: &String,
+        session: &Session,
+    ) -> Result<BTreeMap<String, TableOptionValue>, DbSchemaParserError> {
+        let cql = format!(
+            "SELECT * FROM system_schema.{} WHERE keyspace_name = ? AND {} = ?",
+            schema_table, name_column
+        );
+
+        let result = session
+            .query_unpaged(cql, (keyspace_name, name))
+            .await?
+            .into_rows_result()?;
+
+        let column_names: Vec<String> = result
+            .column_specs()
+            .iter()
+            .map(|spec| spec.name().to_string())
+            .collect();
+        let mut options = BTreeMap::new();
+
+        if let Some(row) = result.rows::<Row>()?.next() {
+            for (column_name, value) in column_names.into_iter().zip(row?.columns) {
+                if !TABLE_OPTIONS.contains(&column_name.as_str()) {
+                    continue;
+                }
+
+                if let Some(value) = value.and_then(table_option_value) {
+                    options.insert(column_name, value);
+                }
+            }
         }
 
-        Ok(())
+        Ok(options)
+    }
+
+    /// Table options clause with current options and `CLUSTERING ORDER BY` in case any of clustering keys is in
+    /// descending order.
+    fn table_options_clause(
+        clustering_keys: &[(String, String)],
+        options: BTreeMap<String, TableOptionValue>,
+    ) -> Option<String> {
+        let mut table_options = TableOptions {
+            options,
+            ..Default::default()
+        };
+
+        if clustering_keys
+            .iter()
+            .any(|(_, order)| order.eq_ignore_ascii_case("desc"))
+        {
+            table_options.clustering_order = clustering_keys
+                .iter()
+                .map(|(column_name, order)| (column_name.clone(), order.to_uppercase()))
+                .collect();
+        }
+
+        let clause = table_options.to_string();
+
+        if clause.is_empty() {
+            return None;
+        }
+
+        Some(clause)
     }
 
     async fn populate_table_secondary_indexes(
@@ -222,11 +291,44 @@ impl DbSchema {
 
             match index_target.target {
                 IndexTarget::GlobalSecondaryIndex(target) => {
-                    table_schema.global_secondary_indexes.push((index_name, target));
+                    let (kind, column) = IndexDefinition::parse_target(&target);
+                    let index = IndexDefinition {
+                        name: index_name,
+                        kind,
+                        column,
+                        class_name: None,
+                        options: index_target.options,
+                    };
+
+                    if index.is_plain_index() {
+                        table_schema.global_secondary_indexes.push((index.name, index.column));
+                    } else {
+                        table_schema.indexes.push(index);
+                    }
                 }
                 IndexTarget::LocalSecondaryIndex(target) => {
                     table_schema.local_secondary_indexes.push((index_name, target));
                 }
+                IndexTarget::CustomIndex(class_name, target) => {
+                    let (kind, column) = IndexDefinition::parse_target(&target);
+                    let is_vector = table_schema
+                        .types_by_name
+                        .get(&column)
+                        .is_some_and(|cql_type| cql_type.starts_with("vector"));
+
+                    // index with options can only be declared with `indexes` model argument
+                    if is_vector && kind == IndexTargetKind::Column && index_target.options.is_empty() {
+                        table_schema.vector_indexes.push((index_name, column));
+                    } else {
+                        table_schema.indexes.push(IndexDefinition {
+                            name: index_name,
+                            kind,
+                            column,
+                            class_name: Some(class_name),
+                            options: index_target.options,
+                        });
+                    }
+                }
             }
         }
 
@@ -267,7 +369,7 @@ impl DbSchema {
     async fn get_mvs_from_system_schema(&mut self, session: &Session) -> Result<(), DbSchemaParserError> {
         // get tables as a HashMap of column_name => column_type
         let cql = r#"
-            SELECT view_name
+            SELECT view_name, base_table_name
             FROM system_schema.views
             WHERE keyspace_name = ?
             ALLOW FILTERING"#;
@@ -277,9 +379,12 @@ impl DbSchema {
             .await?
             .into_rows_result()?;
 
-        for row in result.rows::<(String,)>()? {
-            let view_name = row?.0;
-            self.materialized_views.insert(view_name.clone(), SchemaObject::new());
+        for row in result.rows::<(String, String)>()? {
+            let (view_name, base_table) = row?;
+            let mut schema_object = SchemaObject::new();
+            schema_object.base_table = base_table;
+
+            self.materialized_views.insert(view_name.clone(), schema_object);
             self.populate_mv_columns(&view_name, session).await?;
             self.populate_mv_partition_key(&view_name, session).await?;
             self.populate_mv_clustering_keys(&view_name, session).await?;
@@ -319,7 +424,7 @@ impl DbSchema {
         session: &Session,
     ) -> Result<(), DbSchemaParserError> {
         let cql = r#"
-            SELECT column_name
+            SELECT column_name, position
             FROM system_schema.columns
             WHERE keyspace_name = ?
                 AND table_name = ?
@@ -331,14 +436,13 @@ impl DbSchema {
             .await?
             .into_rows_result()?;
 
-        for row in result.rows::<(String,)>()? {
-            let str_value: (String,) = row?;
-            self.materialized_views
-                .get_mut(view_name)
-                .expect("Expected a materialized view")
-                .partition_keys
-                .push(str_value.0);
-        }
+        let mut partition_keys = result.rows::<(String, i32)>()?.collect::<Result<Vec<_>, _>>()?;
+        partition_keys.sort_by_key(|(_, position)| *position);
+
+        self.materialized_views
+            .get_mut(view_name)
+            .expect("Expected a materialized view")
+            .partition_keys = partition_keys.into_iter().map(|(column_name, _)| column_name).collect();
 
         Ok(())
     }
@@ -348,44 +452,91 @@ impl DbSchema {
         view_name: &String,
         session: &Session,
     ) -> Result<(), DbSchemaParserError> {
-        let cql = r#"
-            SELECT column_name
-            FROM system_schema.columns
-            WHERE keyspace_name = ?
-                AND table_name = ?
-                AND kind = 'clustering'
-            ALLOW FILTERING"#;
+        let clustering_keys = Self::clustering_keys(&self.keyspace_name, view_name, session).await?;
+        let options = Self::table_options(&self.keyspace_name, "views", "view_name", view_name, session).await?;
+        let view_schema = self
+            .materialized_views
+            .get_mut(view_name)
+            .expect("Expected a materialized view");
+
+        view_schema.table_options = Self::table_options_clause(&clustering_keys, options);
+        view_schema.clustering_keys = clustering_keys
+            .into_iter()
+            .map(|(column_name, _)| column_name)
+            .collect();
 
-        let result = session
-            .query_unpaged(cql, (&self.keyspace_name, &view_name))
-            .await?
-            .into_rows_result()?;
+        Ok(())
+    }
 
-        for row in result.rows::<(String,)>()? {
-            let str_value: (String,) = row?;
-            self.materialized_views
-                .get_mut(view_name)
-                .expect("Expected a materialized view")
-                .clustering_keys
-                .push(str_value.0);
+    pub fn get_current_schema_as_json(&self) -> Result<String, serde_json::Error> {
+        to_string_pretty(&self)
+    }
+
+    /// Writes schema to `current_schema.json` in `current_dir` and returns path of the written file.
+    pub fn write_schema_to_json(&self, current_dir: &str) -> std::io::Result<PathBuf> {
+        let json = self.get_current_schema_as_json()?;
+        let path = Path::new(current_dir).join("current_schema.json");
+
+        std::fs::write(&path, json)?;
+
+        Ok(path)
+    }
+}
+
+fn table_option_value(value: CqlValue) -> Option<TableOptionValue> {
+    fn scalar(value: CqlValue) -> Option<String> {
+        match value {
+            CqlValue::Text(value) | CqlValue::Ascii(value) => Some(value),
+            CqlValue::Int(value) => Some(value.to_string()),
+            CqlValue::Double(value) => Some(value.to_string()),
+            CqlValue::Float(value) => Some(value.to_string()),
+            CqlValue::Boolean(value) => Some(value.to_string()),
+            _ => None,
         }
+    }
 
-        Ok(())
+    match value {
+        CqlValue::Map(entries) => Some(TableOptionValue::Map(
+            entries
+                .into_iter()
+                .filter_map(|(key, value)| Some((scalar(key)?, scalar(value)?)))
+                .collect(),
+        )),
+        value => scalar(value).map(TableOptionValue::Scalar),
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    pub fn get_current_schema_as_json(&self) -> String {
-        to_string_pretty(&self).unwrap_or_else(|e| {
-            panic!("Error serializing schema to json: {}", e);
-        })
+    fn db_schema() -> DbSchema {
+        DbSchema {
+            tables: SchemaObjects::new(),
+            udts: SchemaObjects::new(),
+            materialized_views: SchemaObjects::new(),
+            keyspace_name: "test_keyspace".to_string(),
+        }
     }
 
-    pub fn write_schema_to_json(&self, current_dir: &str) {
-        let json = self.get_current_schema_as_json();
+    #[test]
+    fn write_schema_to_json() {
+        let dir = std::env::temp_dir().join(format!("charybdis_db_schema_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let path = db_schema().write_schema_to_json(dir.to_str().unwrap()).unwrap();
+        let written: DbSchema = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+        assert_eq!(path, dir.join("current_schema.json"));
+        assert_eq!(written.keyspace_name, "test_keyspace");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 
-        let path = current_dir.to_string() + "/current_schema.json";
+    #[test]
+    fn write_schema_to_json_in_missing_dir() {
+        let dir = std::env::temp_dir().join("charybdis_missing_dir").join("nested");
 
-        std::fs::write(path, json).unwrap_or_else(|e| {
-            panic!("Error writing schema to json: {}", e);
-        });
+        assert!(db_schema().write_schema_to_json(dir.to_str().unwrap()).is_err());
     }
 }
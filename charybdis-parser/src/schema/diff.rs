@@ -0,0 +1,869 @@
+use std::fmt::Display;
+
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::schema::secondary_indexes::IndexDefinition;
+use crate::schema::table_options::TableOptions;
+use crate::schema::{FieldName, FieldType, IdxField, IndexName, Schema, SchemaObject, SchemaObjects};
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaObjectType {
+    Udt,
+    Table,
+    MaterializedView,
+}
+
+impl Display for SchemaObjectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaObjectType::Udt => write!(f, "UDT"),
+            SchemaObjectType::Table => write!(f, "Table"),
+            SchemaObjectType::MaterializedView => write!(f, "Materialized View"),
+        }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SchemaChangeKind {
+    Created,
+    Dropped,
+    FieldAdded {
+        field_name: String,
+        field_type: String,
+    },
+    FieldRemoved {
+        field_name: String,
+    },
+    FieldRenamed {
+        from: String,
+        to: String,
+    },
+    FieldTypeChanged {
+        field_name: String,
+        from: String,
+        to: String,
+    },
+    PartitionKeyChanged {
+        from: Vec<String>,
+        to: Vec<String>,
+    },
+    ClusteringKeyChanged {
+        from: Vec<String>,
+        to: Vec<String>,
+    },
+    ClusteringOrderChanged {
+        from: Vec<String>,
+        to: Vec<String>,
+    },
+    BaseTableChanged {
+        from: String,
+        to: String,
+    },
+    GlobalIndexAdded {
+        target: String,
+    },
+    GlobalIndexRemoved {
+        target: String,
+    },
+    LocalIndexAdded {
+        target: String,
+    },
+    LocalIndexRemoved {
+        target: String,
+    },
+    VectorIndexAdded {
+        target: String,
+    },
+    VectorIndexRemoved {
+        target: String,
+    },
+    IndexAdded {
+        name: String,
+        index: String,
+    },
+    IndexRemoved {
+        name: String,
+        index: String,
+    },
+    /// Options set in new schema that differ from old schema, as `ALTER TABLE ... WITH` clause.
+    TableOptionsChanged {
+        options: String,
+    },
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Serialize)]
+pub struct SchemaChange {
+    pub object_name: String,
+    pub object_type: SchemaObjectType,
+    #[serde(flatten)]
+    pub kind: SchemaChangeKind,
+}
+
+impl SchemaChange {
+    /// Unsafe changes either lose data or can't be applied by altering existing schema object.
+    pub fn is_unsafe(&self) -> bool {
+        match &self.kind {
+            SchemaChangeKind::Created
+            | SchemaChangeKind::FieldRenamed { .. }
+            | SchemaChangeKind::GlobalIndexAdded { .. }
+            | SchemaChangeKind::GlobalIndexRemoved { .. }
+            | SchemaChangeKind::LocalIndexAdded { .. }
+            | SchemaChangeKind::LocalIndexRemoved { .. }
+            | SchemaChangeKind::VectorIndexAdded { .. }
+            | SchemaChangeKind::VectorIndexRemoved { .. }
+            | SchemaChangeKind::IndexAdded { .. }
+            | SchemaChangeKind::IndexRemoved { .. }
+            | SchemaChangeKind::TableOptionsChanged { .. } => false,
+            // materialized views can only be changed by recreating them
+            SchemaChangeKind::FieldAdded { .. } => self.object_type == SchemaObjectType::MaterializedView,
+            SchemaChangeKind::Dropped
+            | SchemaChangeKind::FieldRemoved { .. }
+            | SchemaChangeKind::FieldTypeChanged { .. }
+            | SchemaChangeKind::PartitionKeyChanged { .. }
+            | SchemaChangeKind::ClusteringKeyChanged { .. }
+            | SchemaChangeKind::ClusteringOrderChanged { .. }
+            | SchemaChangeKind::BaseTableChanged { .. } => true,
+        }
+    }
+}
+
+impl Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = match &self.kind {
+            SchemaChangeKind::Created => "created".to_string(),
+            SchemaChangeKind::Dropped => "dropped".to_string(),
+            SchemaChangeKind::FieldAdded { field_name, field_type } => {
+                format!("field {} {} added", field_name, field_type)
+            }
+            SchemaChangeKind::FieldRemoved { field_name } => format!("field {} removed", field_name),
+            SchemaChangeKind::FieldRenamed { from, to } => format!("field {} renamed to {}", from, to),
+            SchemaChangeKind::FieldTypeChanged { field_name, from, to } => {
+                format!("field {} type changed from {} to {}", field_name, from, to)
+            }
+            SchemaChangeKind::PartitionKeyChanged { from, to } => {
+                format!(
+                    "partition key changed from [{}] to [{}]",
+                    from.join(", "),
+                    to.join(", ")
+                )
+            }
+            SchemaChangeKind::ClusteringKeyChanged { from, to } => {
+                format!(
+                    "clustering key changed from [{}] to [{}]",
+                    from.join(", "),
+                    to.join(", ")
+                )
+            }
+            SchemaChangeKind::ClusteringOrderChanged { from, to } => {
+                format!(
+                    "clustering order changed from [{}] to [{}]",
+                    from.join(", "),
+                    to.join(", ")
+                )
+            }
+            SchemaChangeKind::BaseTableChanged { from, to } => format!("base table changed from {} to {}", from, to),
+            SchemaChangeKind::GlobalIndexAdded { target } => format!("global index on {} added", target),
+            SchemaChangeKind::GlobalIndexRemoved { target } => format!("global index on {} removed", target),
+            SchemaChangeKind::LocalIndexAdded { target } => format!("local index on {} added", target),
+            SchemaChangeKind::LocalIndexRemoved { target } => format!("local index on {} removed", target),
+            SchemaChangeKind::VectorIndexAdded { target } => format!("vector index on {} added", target),
+            SchemaChangeKind::VectorIndexRemoved { target } => format!("vector index on {} removed", target),
+            SchemaChangeKind::IndexAdded { name, index } => format!("index {} on {} added", name, index),
+            SchemaChangeKind::IndexRemoved { name, index } => format!("index {} on {} removed", name, index),
+            SchemaChangeKind::TableOptionsChanged { options } => format!("table options changed: {}", options),
+        };
+
+        write!(
+            f,
+            "{} {} {}",
+            self.object_type.to_string().bright_magenta(),
+            self.object_name.bright_yellow(),
+            description
+        )?;
+
+        if self.is_unsafe() {
+            write!(f, " {}", "(unsafe)".bright_red())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Differences between two schemas, e.g. checked in schema snapshot and current code schema. It doesn't require a
+/// database connection, so it can be used to detect unsafe changes in CI.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SchemaDiff {
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    /// Changes required to go from `from` schema to `to` schema, UDTs first, then tables and materialized views.
+    pub fn new(from: &impl Schema, to: &impl Schema) -> Self {
+        let mut diff = SchemaDiff::default();
+
+        diff.compare_objects(SchemaObjectType::Udt, from.udts(), to.udts());
+        diff.compare_objects(SchemaObjectType::Table, from.tables(), to.tables());
+        diff.compare_objects(
+            SchemaObjectType::MaterializedView,
+            from.materialized_views(),
+            to.materialized_views(),
+        );
+
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn has_unsafe_changes(&self) -> bool {
+        self.changes.iter().any(|change| change.is_unsafe())
+    }
+
+    pub fn unsafe_changes(&self) -> Vec<&SchemaChange> {
+        self.changes.iter().filter(|change| change.is_unsafe()).collect()
+    }
+
+    fn push(&mut self, object_name: &str, object_type: SchemaObjectType, kind: SchemaChangeKind) {
+        self.changes.push(SchemaChange {
+            object_name: object_name.to_string(),
+            object_type,
+            kind,
+        });
+    }
+
+    fn compare_objects(&mut self, object_type: SchemaObjectType, from: &SchemaObjects, to: &SchemaObjects) {
+        let mut names: Vec<&String> = from.keys().chain(to.keys()).collect();
+        names.sort();
+        names.dedup();
+
+        for name in names {
+            match (from.get(name), to.get(name)) {
+                (None, Some(_)) => self.push(name, object_type, SchemaChangeKind::Created),
+                (Some(_), None) => self.push(name, object_type, SchemaChangeKind::Dropped),
+                (Some(from), Some(to)) => self.compare_object(name, object_type, from, to),
+                (None, None) => {}
+            }
+        }
+    }
+
+    fn compare_object(&mut self, name: &str, object_type: SchemaObjectType, from: &SchemaObject, to: &SchemaObject) {
+        let diff = ObjectDiff::new(name, object_type, from, to);
+
+        if object_type != SchemaObjectType::Udt {
+            if diff.partition_key_changed() {
+                let kind = SchemaChangeKind::PartitionKeyChanged {
+                    from: from.partition_keys.clone(),
+                    to: to.partition_keys.clone(),
+                };
+                self.push(name, object_type, kind);
+            }
+
+            if diff.clustering_key_changed() {
+                let kind = SchemaChangeKind::ClusteringKeyChanged {
+                    from: from.clustering_keys.clone(),
+                    to: to.clustering_keys.clone(),
+                };
+                self.push(name, object_type, kind);
+            } else if diff.clustering_order_changed() {
+                let kind = SchemaChangeKind::ClusteringOrderChanged {
+                    from: clustering_order(from),
+                    to: clustering_order(to),
+                };
+                self.push(name, object_type, kind);
+            }
+        }
+
+        if object_type == SchemaObjectType::MaterializedView && diff.base_table_changed() {
+            let kind = SchemaChangeKind::BaseTableChanged {
+                from: from.base_table.clone(),
+                to: to.base_table.clone(),
+            };
+            self.push(name, object_type, kind);
+        }
+
+        for (from_name, to_name) in diff.renamed_fields.iter() {
+            let kind = SchemaChangeKind::FieldRenamed {
+                from: from_name.clone(),
+                to: to_name.clone(),
+            };
+            self.push(name, object_type, kind);
+        }
+
+        for (field_name, field_type) in diff.new_fields.iter() {
+            let kind = SchemaChangeKind::FieldAdded {
+                field_name: field_name.clone(),
+                field_type: field_type.clone(),
+            };
+            self.push(name, object_type, kind);
+        }
+
+        for (field_name, from_type, to_type) in diff.changed_field_types.iter() {
+            let kind = SchemaChangeKind::FieldTypeChanged {
+                field_name: field_name.clone(),
+                from: from_type.clone(),
+                to: to_type.clone(),
+            };
+            self.push(name, object_type, kind);
+        }
+
+        for field_name in diff.removed_fields.iter() {
+            let kind = SchemaChangeKind::FieldRemoved {
+                field_name: field_name.clone(),
+            };
+            self.push(name, object_type, kind);
+        }
+
+        if object_type == SchemaObjectType::Table {
+            self.push_index_changes(&diff);
+        }
+
+        if let (true, Some(options)) = (
+            object_type != SchemaObjectType::Udt,
+            diff.changed_table_options().alter_clause(),
+        ) {
+            self.push(name, object_type, SchemaChangeKind::TableOptionsChanged { options });
+        }
+    }
+
+    fn push_index_changes(&mut self, diff: &ObjectDiff) {
+        let (name, object_type) = (diff.object_name, SchemaObjectType::Table);
+
+        for target in diff.new_global_secondary_indexes.iter() {
+            let target = target.clone();
+            self.push(name, object_type, SchemaChangeKind::GlobalIndexAdded { target });
+        }
+
+        for (_, target) in diff.removed_global_secondary_indexes.iter() {
+            let target = target.clone();
+            self.push(name, object_type, SchemaChangeKind::GlobalIndexRemoved { target });
+        }
+
+        for target in diff.new_local_secondary_indexes.iter() {
+            let target = target.clone();
+            self.push(name, object_type, SchemaChangeKind::LocalIndexAdded { target });
+        }
+
+        for (_, target) in diff.removed_local_secondary_indexes.iter() {
+            let target = target.clone();
+            self.push(name, object_type, SchemaChangeKind::LocalIndexRemoved { target });
+        }
+
+        for target in diff.new_vector_indexes.iter() {
+            let target = target.clone();
+            self.push(name, object_type, SchemaChangeKind::VectorIndexAdded { target });
+        }
+
+        for (_, target) in diff.removed_vector_indexes.iter() {
+            let target = target.clone();
+            self.push(name, object_type, SchemaChangeKind::VectorIndexRemoved { target });
+        }
+
+        for index in diff.new_indexes.iter() {
+            let kind = SchemaChangeKind::IndexAdded {
+                name: index.index_name(name),
+                index: index.to_string(),
+            };
+            self.push(name, object_type, kind);
+        }
+
+        for index in diff.removed_indexes.iter() {
+            let kind = SchemaChangeKind::IndexRemoved {
+                name: index.index_name(name),
+                index: index.to_string(),
+            };
+            self.push(name, object_type, kind);
+        }
+    }
+}
+
+impl Display for SchemaDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.changes.is_empty() {
+            return writeln!(f, "{}", "No schema changes.".green());
+        }
+
+        for change in self.changes.iter() {
+            writeln!(f, "{}", change)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Differences between two versions of a single UDT, table or materialized view. It's used both by `SchemaDiff` and
+/// by migration planning, so schema diff reports the same changes that migration would apply.
+pub struct ObjectDiff<'a> {
+    pub object_name: &'a str,
+    pub object_type: SchemaObjectType,
+    pub from: &'a SchemaObject,
+    pub to: &'a SchemaObject,
+    pub new_fields: Vec<(FieldName, FieldType)>,
+    pub removed_fields: Vec<FieldName>,
+    /// Fields renamed with `renamed_from` attribute whose old field still exists, as `(from, to)` pairs.
+    pub renamed_fields: Vec<(FieldName, FieldName)>,
+    /// Fields present in both schemas with different type, as `(name, from type, to type)`.
+    pub changed_field_types: Vec<(FieldName, FieldType, FieldType)>,
+    /// Targets of new global secondary indexes.
+    pub new_global_secondary_indexes: Vec<IdxField>,
+    pub removed_global_secondary_indexes: Vec<&'a (IndexName, IdxField)>,
+    /// Targets of new local secondary indexes.
+    pub new_local_secondary_indexes: Vec<IdxField>,
+    pub removed_local_secondary_indexes: Vec<&'a (IndexName, IdxField)>,
+    /// Targets of new vector indexes.
+    pub new_vector_indexes: Vec<IdxField>,
+    pub removed_vector_indexes: Vec<&'a (IndexName, IdxField)>,
+    /// Indexes that differ in class, options or name are both removed and added.
+    pub new_indexes: Vec<&'a IndexDefinition>,
+    pub removed_indexes: Vec<&'a IndexDefinition>,
+}
+
+impl<'a> ObjectDiff<'a> {
+    pub fn new(
+        object_name: &'a str,
+        object_type: SchemaObjectType,
+        from: &'a SchemaObject,
+        to: &'a SchemaObject,
+    ) -> Self {
+        let renamed_fields = renamed_fields(object_type, from, to);
+
+        let new_fields = to
+            .fields
+            .iter()
+            .filter(|(field_name, _, _)| !renamed_fields.iter().any(|(_, to_name)| to_name == field_name))
+            .filter(|(field_name, _, _)| !from.contains_field(field_name))
+            .map(|(field_name, field_type, _)| (field_name.clone(), field_type.clone()))
+            .collect();
+
+        let removed_fields = from
+            .fields
+            .iter()
+            .filter(|(field_name, _, _)| !renamed_fields.iter().any(|(from_name, _)| from_name == field_name))
+            .filter(|(field_name, _, _)| !to.contains_field(field_name))
+            .map(|(field_name, _, _)| field_name.clone())
+            .collect();
+
+        let changed_field_types = to
+            .fields
+            .iter()
+            .filter_map(|(field_name, to_type, _)| {
+                let from_type = from.types_by_name.get(field_name)?;
+
+                (normalize_type(from_type) != normalize_type(to_type))
+                    .then(|| (field_name.clone(), from_type.clone(), to_type.clone()))
+            })
+            .collect();
+
+        Self {
+            object_name,
+            object_type,
+            from,
+            to,
+            new_fields,
+            removed_fields,
+            renamed_fields,
+            changed_field_types,
+            new_global_secondary_indexes: new_targets(&from.global_secondary_indexes, &to.global_secondary_indexes),
+            removed_global_secondary_indexes: removed_targets(
+                &from.global_secondary_indexes,
+                &to.global_secondary_indexes,
+            ),
+            new_local_secondary_indexes: new_targets(&from.local_secondary_indexes, &to.local_secondary_indexes),
+            removed_local_secondary_indexes: removed_targets(
+                &from.local_secondary_indexes,
+                &to.local_secondary_indexes,
+            ),
+            new_vector_indexes: new_targets(&from.vector_indexes, &to.vector_indexes),
+            removed_vector_indexes: removed_targets(&from.vector_indexes, &to.vector_indexes)
+                .into_iter()
+                .filter(|vector_index| {
+                    !to.indexes
+                        .iter()
+                        .any(|index| index.is_vector_index(object_name, vector_index))
+                })
+                .collect(),
+            new_indexes: to
+                .indexes
+                .iter()
+                .filter(|index| {
+                    !from
+                        .indexes
+                        .iter()
+                        .any(|current| index.is_equivalent(object_name, current))
+                })
+                // e.g. `sai(embedding)`, that's read from database as vector index
+                .filter(|index| {
+                    !from
+                        .vector_indexes
+                        .iter()
+                        .any(|vector_index| index.is_vector_index(object_name, vector_index))
+                })
+                .collect(),
+            removed_indexes: from
+                .indexes
+                .iter()
+                .filter(|current| !to.indexes.iter().any(|index| index.is_equivalent(object_name, current)))
+                .collect(),
+        }
+    }
+
+    /// Name of `from` field in `to` schema, which differs if field was renamed.
+    pub fn renamed_name<'b>(&'b self, from_name: &'b FieldName) -> &'b FieldName {
+        self.renamed_fields
+            .iter()
+            .find(|(old_name, _)| old_name == from_name)
+            .map_or(from_name, |(_, new_name)| new_name)
+    }
+
+    /// Compares sets of partition keys, so renaming a key column isn't detected as key change.
+    pub fn partition_key_changed(&self) -> bool {
+        sorted(self.renamed_keys(&self.from.partition_keys)) != sorted(self.to.partition_keys.clone())
+    }
+
+    /// Compares sets of clustering keys, while their order is compared by `clustering_order_changed`.
+    pub fn clustering_key_changed(&self) -> bool {
+        sorted(self.renamed_keys(&self.from.clustering_keys)) != sorted(self.to.clustering_keys.clone())
+    }
+
+    pub fn keys_changed(&self) -> bool {
+        self.partition_key_changed() || self.clustering_key_changed()
+    }
+
+    /// Compares clustering keys along with their order. Clustering keys without explicit order are ascending.
+    pub fn clustering_order_changed(&self) -> bool {
+        self.to
+            .parsed_table_options()
+            .clustering_order(&self.to.clustering_keys)
+            != self
+                .from
+                .parsed_table_options()
+                .clustering_order(&self.from.clustering_keys)
+                .into_iter()
+                .map(|(key, order)| (self.renamed_name(&key).clone(), order))
+                .collect::<Vec<(FieldName, String)>>()
+    }
+
+    pub fn base_table_changed(&self) -> bool {
+        !self.to.base_table.eq_ignore_ascii_case(&self.from.base_table)
+    }
+
+    /// Table options set in `to` schema that differ from `from` schema.
+    pub fn changed_table_options(&self) -> TableOptions {
+        self.to
+            .parsed_table_options()
+            .changed_options(&self.from.parsed_table_options())
+    }
+
+    fn renamed_keys(&self, keys: &[FieldName]) -> Vec<FieldName> {
+        keys.iter().map(|key| self.renamed_name(key).clone()).collect()
+    }
+}
+
+/// Fields renamed with `renamed_from` attribute, as `(from, to)` pairs. New field could already exist in `from` schema,
+/// e.g. when previous migration failed before old field was dropped. Materialized views can't rename their columns.
+fn renamed_fields(
+    object_type: SchemaObjectType,
+    from: &SchemaObject,
+    to: &SchemaObject,
+) -> Vec<(FieldName, FieldName)> {
+    if object_type == SchemaObjectType::MaterializedView {
+        return vec![];
+    }
+
+    to.renamed_fields
+        .iter()
+        .filter(|(to_name, from_name)| {
+            to.contains_field(to_name) && from.contains_field(from_name) && !to.contains_field(from_name)
+        })
+        .map(|(to_name, from_name)| (from_name.clone(), to_name.clone()))
+        .collect()
+}
+
+fn new_targets(from: &[(IndexName, IdxField)], to: &[(IndexName, IdxField)]) -> Vec<IdxField> {
+    to.iter()
+        .filter(|(_, target)| !from.iter().any(|(_, current)| current == target))
+        .map(|(_, target)| target.clone())
+        .collect()
+}
+
+fn removed_targets<'a>(
+    from: &'a [(IndexName, IdxField)],
+    to: &[(IndexName, IdxField)],
+) -> Vec<&'a (IndexName, IdxField)> {
+    from.iter()
+        .filter(|(_, current)| !to.iter().any(|(_, target)| target == current))
+        .collect()
+}
+
+fn sorted(mut keys: Vec<FieldName>) -> Vec<FieldName> {
+    keys.sort();
+    keys
+}
+
+fn normalize_type(cql_type: &str) -> String {
+    cql_type.to_lowercase().replace(' ', "")
+}
+
+/// Clustering keys with their order, e.g. `["created_at DESC", "id ASC"]`.
+fn clustering_order(schema_object: &SchemaObject) -> Vec<String> {
+    schema_object
+        .parsed_table_options()
+        .clustering_order(&schema_object.clustering_keys)
+        .into_iter()
+        .map(|(key, order)| format!("{} {}", key, order))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::secondary_indexes::IndexTargetKind;
+    use std::collections::HashMap;
+
+    fn table(fields: &[(&str, &str)], partition_keys: &[&str], clustering_keys: &[&str]) -> SchemaObject {
+        let mut table = SchemaObject::new();
+
+        for (field_name, field_type) in fields {
+            table.push_field(field_name.to_string(), field_type.to_string(), false);
+        }
+
+        table.partition_keys = partition_keys.iter().map(|key| key.to_string()).collect();
+        table.clustering_keys = clustering_keys.iter().map(|key| key.to_string()).collect();
+
+        table
+    }
+
+    fn renamed(mut table: SchemaObject, from: &str, to: &str) -> SchemaObject {
+        table.renamed_fields.insert(to.to_string(), from.to_string());
+        table
+    }
+
+    #[test]
+    fn detects_field_changes() {
+        let from = table(
+            &[
+                ("id", "uuid"),
+                ("title", "text"),
+                ("views", "int"),
+                ("tags", "set<text>"),
+            ],
+            &["id"],
+            &[],
+        );
+        let to = table(
+            &[
+                ("id", "uuid"),
+                ("views", "bigint"),
+                ("tags", "SET< TEXT >"),
+                ("bio", "text"),
+            ],
+            &["id"],
+            &[],
+        );
+
+        let diff = ObjectDiff::new("posts", SchemaObjectType::Table, &from, &to);
+
+        assert_eq!(diff.new_fields, vec![("bio".to_string(), "text".to_string())]);
+        assert_eq!(diff.removed_fields, vec!["title".to_string()]);
+        assert_eq!(
+            diff.changed_field_types,
+            vec![("views".to_string(), "int".to_string(), "bigint".to_string())]
+        );
+    }
+
+    #[test]
+    fn renamed_field_is_not_added_or_removed() {
+        let from = table(&[("id", "uuid"), ("title", "text")], &["id"], &[]);
+        let to = renamed(
+            table(&[("id", "uuid"), ("headline", "text")], &["id"], &[]),
+            "title",
+            "headline",
+        );
+
+        let diff = ObjectDiff::new("posts", SchemaObjectType::Table, &from, &to);
+
+        assert_eq!(diff.renamed_fields, vec![("title".to_string(), "headline".to_string())]);
+        assert!(diff.new_fields.is_empty());
+        assert!(diff.removed_fields.is_empty());
+    }
+
+    #[test]
+    fn renamed_field_that_already_exists_is_renamed() {
+        let from = table(&[("id", "uuid"), ("title", "text"), ("headline", "text")], &["id"], &[]);
+        let to = renamed(
+            table(&[("id", "uuid"), ("headline", "text")], &["id"], &[]),
+            "title",
+            "headline",
+        );
+
+        let diff = ObjectDiff::new("posts", SchemaObjectType::Table, &from, &to);
+
+        assert_eq!(diff.renamed_fields, vec![("title".to_string(), "headline".to_string())]);
+        assert!(diff.removed_fields.is_empty());
+    }
+
+    #[test]
+    fn completed_rename_is_not_renamed_again() {
+        let from = table(&[("id", "uuid"), ("headline", "text")], &["id"], &[]);
+        let to = renamed(
+            table(&[("id", "uuid"), ("headline", "text")], &["id"], &[]),
+            "title",
+            "headline",
+        );
+
+        let diff = ObjectDiff::new("posts", SchemaObjectType::Table, &from, &to);
+
+        assert!(diff.renamed_fields.is_empty());
+        assert!(diff.new_fields.is_empty());
+        assert!(diff.removed_fields.is_empty());
+    }
+
+    #[test]
+    fn view_fields_are_not_renamed() {
+        let from = table(&[("id", "uuid"), ("title", "text")], &["id"], &[]);
+        let to = renamed(
+            table(&[("id", "uuid"), ("headline", "text")], &["id"], &[]),
+            "title",
+            "headline",
+        );
+
+        let diff = ObjectDiff::new("posts_by_id", SchemaObjectType::MaterializedView, &from, &to);
+
+        assert!(diff.renamed_fields.is_empty());
+        assert_eq!(diff.new_fields, vec![("headline".to_string(), "text".to_string())]);
+        assert_eq!(diff.removed_fields, vec!["title".to_string()]);
+    }
+
+    #[test]
+    fn renamed_key_is_not_key_change() {
+        let from = table(&[("id", "uuid"), ("created_at", "timestamp")], &["id"], &["created_at"]);
+        let to = renamed(
+            table(
+                &[("post_id", "uuid"), ("created_at", "timestamp")],
+                &["post_id"],
+                &["created_at"],
+            ),
+            "id",
+            "post_id",
+        );
+
+        let diff = ObjectDiff::new("posts", SchemaObjectType::Table, &from, &to);
+
+        assert!(!diff.keys_changed());
+        assert!(!diff.clustering_order_changed());
+    }
+
+    #[test]
+    fn detects_key_changes() {
+        let from = table(&[("id", "uuid"), ("created_at", "timestamp")], &["id"], &[]);
+        let to = table(&[("id", "uuid"), ("created_at", "timestamp")], &["id"], &["created_at"]);
+
+        let diff = ObjectDiff::new("posts", SchemaObjectType::Table, &from, &to);
+
+        assert!(!diff.partition_key_changed());
+        assert!(diff.clustering_key_changed());
+    }
+
+    #[test]
+    fn detects_index_changes() {
+        let mut from = table(&[("id", "uuid"), ("title", "text"), ("bio", "text")], &["id"], &[]);
+        from.global_secondary_indexes = vec![("posts_title_idx".to_string(), "title".to_string())];
+        let mut to = from.clone();
+        to.global_secondary_indexes = vec![(String::new(), "bio".to_string())];
+
+        let diff = ObjectDiff::new("posts", SchemaObjectType::Table, &from, &to);
+
+        assert_eq!(diff.new_global_secondary_indexes, vec!["bio".to_string()]);
+        assert_eq!(
+            diff.removed_global_secondary_indexes,
+            vec![&("posts_title_idx".to_string(), "title".to_string())]
+        );
+    }
+
+    #[test]
+    fn sai_index_on_vector_column_is_not_changed() {
+        let mut from = table(&[("id", "uuid"), ("embedding", "vector<float, 3>")], &["id"], &[]);
+        from.vector_indexes = vec![("products_embedding_idx".to_string(), "embedding".to_string())];
+        let mut to = table(&[("id", "uuid"), ("embedding", "vector<float, 3>")], &["id"], &[]);
+        to.indexes = vec![IndexDefinition {
+            name: String::new(),
+            kind: IndexTargetKind::Column,
+            column: "embedding".to_string(),
+            class_name: Some(IndexDefinition::STORAGE_ATTACHED_INDEX.to_string()),
+            options: Default::default(),
+        }];
+
+        let diff = ObjectDiff::new("products", SchemaObjectType::Table, &from, &to);
+
+        assert!(diff.new_indexes.is_empty());
+        assert!(diff.removed_vector_indexes.is_empty());
+        assert!(diff.new_vector_indexes.is_empty());
+    }
+
+    #[test]
+    fn schema_diff_reports_unsafe_changes() {
+        struct TestSchema(SchemaObjects);
+
+        impl Schema for TestSchema {
+            fn tables(&self) -> &SchemaObjects {
+                &self.0
+            }
+
+            fn udts(&self) -> &SchemaObjects {
+                static EMPTY: std::sync::OnceLock<SchemaObjects> = std::sync::OnceLock::new();
+                EMPTY.get_or_init(HashMap::new)
+            }
+
+            fn materialized_views(&self) -> &SchemaObjects {
+                self.udts()
+            }
+        }
+
+        let from = TestSchema(HashMap::from([
+            (
+                "posts".to_string(),
+                table(&[("id", "uuid"), ("title", "text")], &["id"], &[]),
+            ),
+            ("old".to_string(), table(&[("id", "uuid")], &["id"], &[])),
+        ]));
+        let to = TestSchema(HashMap::from([
+            (
+                "posts".to_string(),
+                table(&[("id", "uuid"), ("bio", "text")], &["id"], &[]),
+            ),
+            ("new".to_string(), table(&[("id", "uuid")], &["id"], &[])),
+        ]));
+
+        let diff = SchemaDiff::new(&from, &to);
+        let kinds: Vec<(&str, &SchemaChangeKind)> = diff
+            .changes
+            .iter()
+            .map(|change| (change.object_name.as_str(), &change.kind))
+            .collect();
+
+        assert_eq!(
+            kinds,
+            vec![
+                ("new", &SchemaChangeKind::Created),
+                ("old", &SchemaChangeKind::Dropped),
+                (
+                    "posts",
+                    &SchemaChangeKind::FieldAdded {
+                        field_name: "bio".to_string(),
+                        field_type: "text".to_string()
+                    }
+                ),
+                (
+                    "posts",
+                    &SchemaChangeKind::FieldRemoved {
+                        field_name: "title".to_string()
+                    }
+                ),
+            ]
+        );
+        assert_eq!(diff.unsafe_changes().len(), 2);
+    }
+}
@@ -1,4 +1,10 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
 use crate::errors::DbSchemaParserError;
+use crate::fields::quote_identifier;
+use crate::schema::table_options::{class_names_equivalent, TableOptionValue, TableOptions};
+use crate::schema::{IdxField, IndexName};
 use scylla::_macro_internal::{ColumnType, DeserializeValue};
 use scylla::cluster::metadata::CollectionType;
 use scylla::deserialize::{DeserializationError, FrameSlice, TypeCheckError};
@@ -16,15 +22,182 @@ pub struct LocalIndexStructure {
 pub enum IndexTarget {
     GlobalSecondaryIndex(String),
     LocalSecondaryIndex(String),
+    /// Index created with `CREATE CUSTOM INDEX`, e.g. storage-attached index, with its class name and target.
+    CustomIndex(String, String),
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SecondaryIndex {
     pub target: IndexTarget,
+    /// Options of custom index apart from `class_name` and `target`.
+    pub options: BTreeMap<String, String>,
+}
+
+/// Part of the column that is indexed, e.g. `KEYS(attributes)` for index on map keys.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexTargetKind {
+    Column,
+    Keys,
+    Values,
+    Entries,
+    Full,
+}
+
+impl IndexTargetKind {
+    pub fn from_function_name(name: &str) -> Option<IndexTargetKind> {
+        match name.to_lowercase().as_str() {
+            "keys" => Some(IndexTargetKind::Keys),
+            "values" => Some(IndexTargetKind::Values),
+            "entries" => Some(IndexTargetKind::Entries),
+            "full" => Some(IndexTargetKind::Full),
+            _ => None,
+        }
+    }
+}
+
+/// Index declared with `indexes` model argument or read from `system_schema.indexes`, e.g.
+/// `CREATE CUSTOM INDEX posts_title_idx ON posts (title) USING 'StorageAttachedIndex'`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct IndexDefinition {
+    /// Empty if index name is not set in code, so default name is used.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    pub kind: IndexTargetKind,
+    pub column: String,
+    /// Class of custom index, e.g. `StorageAttachedIndex`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class_name: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, String>,
+}
+
+impl IndexDefinition {
+    pub const STORAGE_ATTACHED_INDEX: &'static str = "StorageAttachedIndex";
+
+    /// Parses index target as stored in database, e.g. `keys(attributes)` or `title`.
+    pub fn parse_target(target: &str) -> (IndexTargetKind, String) {
+        let target = target.trim();
+
+        if let (Some(start), true) = (target.find('('), target.ends_with(')')) {
+            if let Some(kind) = IndexTargetKind::from_function_name(&target[..start]) {
+                let column = target[start + 1..target.len() - 1].trim();
+
+                return (kind, unquote_identifier(column));
+            }
+        }
+
+        (IndexTargetKind::Column, unquote_identifier(target))
+    }
+
+    /// Parses CQL map of index options, e.g. `{'case_sensitive': 'false'}`.
+    pub fn parse_options(options: &str) -> BTreeMap<String, String> {
+        match TableOptions::parse(&format!("options = {}", options))
+            .options
+            .remove("options")
+        {
+            Some(TableOptionValue::Map(options)) => options,
+            _ => BTreeMap::new(),
+        }
+    }
+
+    /// Index without class and options on a column or collection values, that is created with plain
+    /// `CREATE INDEX` the same way as global secondary index.
+    pub fn is_plain_index(&self) -> bool {
+        matches!(self.kind, IndexTargetKind::Column | IndexTargetKind::Values)
+            && self.class_name.is_none()
+            && self.options.is_empty()
+    }
+
+    /// Index target as used in `CREATE INDEX` statement, e.g. `KEYS(attributes)`.
+    pub fn target(&self) -> String {
+        let column = quote_identifier(&self.column);
+
+        match self.kind {
+            IndexTargetKind::Column => column,
+            IndexTargetKind::Keys => format!("KEYS({})", column),
+            IndexTargetKind::Values => format!("VALUES({})", column),
+            IndexTargetKind::Entries => format!("ENTRIES({})", column),
+            IndexTargetKind::Full => format!("FULL({})", column),
+        }
+    }
+
+    /// Name of the index, e.g. `posts_tags_keys_idx` for index on keys of `tags` column of `posts` table.
+    pub fn index_name(&self, table_name: &str) -> String {
+        if !self.name.is_empty() {
+            return self.name.clone();
+        }
+
+        // index names can't be quoted, so case-sensitive column names are lowercased
+        let column = self.column.to_lowercase();
+
+        match self.kind {
+            IndexTargetKind::Column => format!("{}_{}_idx", table_name, column),
+            IndexTargetKind::Keys => format!("{}_{}_keys_idx", table_name, column),
+            IndexTargetKind::Values => format!("{}_{}_values_idx", table_name, column),
+            IndexTargetKind::Entries => format!("{}_{}_entries_idx", table_name, column),
+            IndexTargetKind::Full => format!("{}_{}_full_idx", table_name, column),
+        }
+    }
+
+    /// Compares code index with the one in database. Class names are compared without package and options the
+    /// same way as map table options.
+    pub fn is_equivalent(&self, table_name: &str, current: &IndexDefinition) -> bool {
+        let class_equivalent = match (&self.class_name, &current.class_name) {
+            (Some(class_name), Some(current)) => {
+                class_names_equivalent(&normalized_class_name(class_name), &normalized_class_name(current))
+            }
+            (None, None) => true,
+            _ => false,
+        };
+
+        self.index_name(table_name) == current.index_name(table_name)
+            && self.kind == current.kind
+            && self.column == current.column
+            && class_equivalent
+            && TableOptionValue::Map(self.options.clone())
+                .is_equivalent(&TableOptionValue::Map(current.options.clone()))
+    }
+
+    /// Whether index is the same as given vector index. Index on vector column without options is read from
+    /// database as vector index, as it can't be told apart from the one declared with `vector_indexes`.
+    pub fn is_vector_index(&self, table_name: &str, (index_name, column): &(IndexName, IdxField)) -> bool {
+        self.kind == IndexTargetKind::Column
+            && self.class_name.is_some()
+            && self.options.is_empty()
+            && &self.column == column
+            && &self.index_name(table_name) == index_name
+    }
 }
 
-// cql returns {'target': '{"pk":["node_id"],"ck":["id"]}'} for a local secondary index
-// and {'target': 'node_id'} for a global secondary index
+impl Display for IndexDefinition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.class_name {
+            Some(class_name) => write!(f, "{} using {}", self.target(), class_name),
+            None => write!(f, "{}", self.target()),
+        }
+    }
+}
+
+/// `sai` is a shorthand for `StorageAttachedIndex` class.
+fn normalized_class_name(class_name: &str) -> String {
+    if class_name.eq_ignore_ascii_case("sai") {
+        return IndexDefinition::STORAGE_ATTACHED_INDEX.to_string();
+    }
+
+    class_name.to_string()
+}
+
+fn unquote_identifier(identifier: &str) -> String {
+    identifier
+        .strip_prefix('"')
+        .and_then(|identifier| identifier.strip_suffix('"'))
+        .map_or_else(|| identifier.to_string(), |identifier| identifier.replace("\"\"", "\""))
+}
+
+// cql returns {'target': '{"pk":["node_id"],"ck":["id"]}'} for a local secondary index,
+// {'target': 'node_id'} for a global secondary index
+// and {'class_name': 'StorageAttachedIndex', 'target': 'embedding'} for a custom index
 impl<'frame, 'metadata> DeserializeValue<'frame, 'metadata> for SecondaryIndex {
     fn type_check(typ: &ColumnType) -> Result<(), TypeCheckError> {
         if let ColumnType::Collection { frozen: _frozen, typ } = typ {
@@ -46,9 +219,32 @@ impl<'frame, 'metadata> DeserializeValue<'frame, 'metadata> for SecondaryIndex {
             Some(slice) => {
                 let value: CqlValue = CqlValue::deserialize(typ, Some(slice))?;
                 if let CqlValue::Map(map) = value {
-                    let target = map[0].1.clone();
+                    let option = |name: &str| {
+                        map.iter()
+                            .find(|(key, _)| matches!(key, CqlValue::Text(key) if key == name))
+                            .map(|(_, value)| value.clone())
+                    };
+
+                    if let Some(CqlValue::Text(target)) = option("target") {
+                        if let Some(CqlValue::Text(class_name)) = option("class_name") {
+                            let options = map
+                                .iter()
+                                .filter_map(|(key, value)| match (key, value) {
+                                    (CqlValue::Text(key), CqlValue::Text(value))
+                                        if key != "class_name" && key != "target" =>
+                                    {
+                                        Some((key.clone(), value.clone()))
+                                    }
+                                    _ => None,
+                                })
+                                .collect();
+
+                            return Ok(SecondaryIndex {
+                                target: IndexTarget::CustomIndex(class_name, target),
+                                options,
+                            });
+                        }
 
-                    if let CqlValue::Text(target) = target {
                         // local secondary index
                         if target.starts_with('{') {
                             let parsed: LocalIndexStructure =
@@ -57,12 +253,14 @@ impl<'frame, 'metadata> DeserializeValue<'frame, 'metadata> for SecondaryIndex {
 
                             return Ok(SecondaryIndex {
                                 target: IndexTarget::LocalSecondaryIndex(idx.to_string()),
+                                options: BTreeMap::new(),
                             });
                         }
 
                         // global secondary index
                         Ok(SecondaryIndex {
                             target: IndexTarget::GlobalSecondaryIndex(target),
+                            options: BTreeMap::new(),
                         })
                     } else {
                         Err(DeserializationError::new(DbSchemaParserError::TypeError(
@@ -81,3 +279,57 @@ impl<'frame, 'metadata> DeserializeValue<'frame, 'metadata> for SecondaryIndex {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sai_index(options: &[(&str, &str)]) -> IndexDefinition {
+        IndexDefinition {
+            name: String::new(),
+            kind: IndexTargetKind::Column,
+            column: "title".to_string(),
+            class_name: Some("sai".to_string()),
+            options: options
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn parses_database_targets() {
+        assert_eq!(
+            IndexDefinition::parse_target("keys(attributes)"),
+            (IndexTargetKind::Keys, "attributes".to_string())
+        );
+        assert_eq!(
+            IndexDefinition::parse_target("entries(\"Attributes\")"),
+            (IndexTargetKind::Entries, "Attributes".to_string())
+        );
+        assert_eq!(
+            IndexDefinition::parse_target(" title "),
+            (IndexTargetKind::Column, "title".to_string())
+        );
+    }
+
+    #[test]
+    fn index_options_are_compared_in_both_directions() {
+        let index = sai_index(&[("case_sensitive", "false")]);
+        let mut current = sai_index(&[("case_sensitive", "FALSE")]);
+        current.class_name = Some("org.apache.cassandra.index.sai.StorageAttachedIndex".to_string());
+
+        assert!(index.is_equivalent("posts", &current));
+        assert!(!index.is_equivalent("posts", &sai_index(&[])));
+        assert!(!sai_index(&[]).is_equivalent("posts", &index));
+    }
+
+    #[test]
+    fn vector_index_requires_default_name_and_no_options() {
+        let vector_index = ("posts_title_idx".to_string(), "title".to_string());
+
+        assert!(sai_index(&[]).is_vector_index("posts", &vector_index));
+        assert!(!sai_index(&[("similarity_function", "COSINE")]).is_vector_index("posts", &vector_index));
+        assert!(!sai_index(&[]).is_vector_index("products", &vector_index));
+    }
+}
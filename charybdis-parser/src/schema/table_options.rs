@@ -0,0 +1,489 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// Value of a single table option, e.g. `86400` for `gc_grace_seconds` or `{'class': 'LeveledCompactionStrategy'}`
+/// for `compaction`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableOptionValue {
+    Scalar(String),
+    Map(BTreeMap<String, String>),
+}
+
+impl TableOptionValue {
+    /// Compares values the way database stores them, e.g. `{'class': 'LeveledCompactionStrategy'}` is equal to
+    /// `{'class': 'org.apache.cassandra.db.compaction.LeveledCompactionStrategy'}` and `0.1` is equal to `0.10`.
+    /// Map entries that are only in `current` value make it different, unless database added them with their
+    /// default value.
+    pub fn is_equivalent(&self, current: &TableOptionValue) -> bool {
+        match (self, current) {
+            (TableOptionValue::Scalar(value), TableOptionValue::Scalar(current)) => {
+                values_equivalent(None, value, current)
+            }
+            (TableOptionValue::Map(map), TableOptionValue::Map(current)) => {
+                map.iter().all(|(key, value)| {
+                    map_value(current, key)
+                        .is_some_and(|current_value| values_equivalent(Some(key), value, current_value))
+                }) && current
+                    .iter()
+                    .all(|(key, current_value)| map_value(map, key).is_some() || is_map_default(key, current_value))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Display for TableOptionValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableOptionValue::Scalar(value) => {
+                if value.parse::<f64>().is_ok() || value == "true" || value == "false" {
+                    write!(f, "{}", value)
+                } else {
+                    write!(f, "'{}'", value.replace('\'', "''"))
+                }
+            }
+            TableOptionValue::Map(map) => {
+                let entries = map
+                    .iter()
+                    .map(|(key, value)| format!("'{}': '{}'", key, value.replace('\'', "''")))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+
+                write!(f, "{{{}}}", entries)
+            }
+        }
+    }
+}
+
+/// Structured form of table options clause, e.g.
+/// `CLUSTERING ORDER BY (created_at DESC) AND gc_grace_seconds = 86400 AND compaction = {'class': '...'}`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableOptions {
+    /// Explicitly ordered clustering keys, e.g. `[("created_at", "DESC")]`.
+    pub clustering_order: Vec<(String, String)>,
+    pub compact_storage: bool,
+    pub options: BTreeMap<String, TableOptionValue>,
+}
+
+impl TableOptions {
+    pub fn parse(table_options: &str) -> TableOptions {
+        let mut parsed = TableOptions::default();
+        let table_options = table_options.trim();
+        let table_options = strip_prefix_ignore_case(table_options, "WITH").unwrap_or(table_options);
+
+        for part in split_top_level(table_options, Separator::And) {
+            if let Some(order) = strip_prefix_ignore_case(&part, "CLUSTERING ORDER BY") {
+                let order = order.trim().trim_start_matches('(').trim_end_matches(')');
+
+                for column_order in order.split(',') {
+                    let mut column_order = column_order.split_whitespace();
+
+                    if let Some(column) = column_order.next() {
+                        let order = column_order.next().unwrap_or("ASC").to_uppercase();
+                        parsed.clustering_order.push((column.to_string(), order));
+                    }
+                }
+            } else if part.eq_ignore_ascii_case("COMPACT STORAGE") {
+                parsed.compact_storage = true;
+            } else if let Some((name, value)) = part.split_once('=') {
+                parsed
+                    .options
+                    .insert(name.trim().to_lowercase(), parse_value(value.trim()));
+            }
+        }
+
+        parsed
+    }
+
+    /// Order of each of given clustering keys, where keys without explicit order are ascending.
+    pub fn clustering_order(&self, clustering_keys: &[String]) -> Vec<(String, String)> {
+        clustering_keys
+            .iter()
+            .map(|key| {
+                let order = self
+                    .clustering_order
+                    .iter()
+                    .find(|(column, _)| column == key)
+                    .map_or_else(|| "ASC".to_string(), |(_, order)| order.clone());
+
+                (key.clone(), order)
+            })
+            .collect()
+    }
+
+    /// Options that are set here and differ from `current` options. Clustering order and compact storage are not
+    /// included, as they can't be altered.
+    pub fn changed_options(&self, current: &TableOptions) -> TableOptions {
+        let options = self
+            .options
+            .iter()
+            .filter(|(name, value)| {
+                current
+                    .options
+                    .get(*name)
+                    .map_or(true, |current_value| !value.is_equivalent(current_value))
+            })
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+
+        TableOptions {
+            options,
+            ..Default::default()
+        }
+    }
+
+    /// Options that can be used in `ALTER TABLE ... WITH` clause, if any.
+    pub fn alter_clause(&self) -> Option<String> {
+        if self.options.is_empty() {
+            return None;
+        }
+
+        let clause = self
+            .options
+            .iter()
+            .map(|(name, value)| format!("{} = {}", name, value))
+            .collect::<Vec<String>>()
+            .join(" AND ");
+
+        Some(clause)
+    }
+
+    /// `CLUSTERING ORDER BY` clause, if any of clustering keys is explicitly ordered.
+    pub fn clustering_order_clause(&self) -> Option<String> {
+        if self.clustering_order.is_empty() {
+            return None;
+        }
+
+        let order = self
+            .clustering_order
+            .iter()
+            .map(|(column, order)| format!("{} {}", column, order))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        Some(format!("CLUSTERING ORDER BY ({})", order))
+    }
+}
+
+impl Display for TableOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut clauses = vec![];
+
+        if let Some(clustering_order) = self.clustering_order_clause() {
+            clauses.push(clustering_order);
+        }
+
+        if self.compact_storage {
+            clauses.push("COMPACT STORAGE".to_string());
+        }
+
+        if let Some(alter_clause) = self.alter_clause() {
+            clauses.push(alter_clause);
+        }
+
+        write!(f, "{}", clauses.join(" AND "))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Separator {
+    And,
+    Comma,
+}
+
+/// Splits on separator that is not inside quotes, braces or parentheses.
+fn split_top_level(input: &str, separator: Separator) -> Vec<String> {
+    let mut parts = vec![];
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut in_quotes = false;
+    let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        match c {
+            '\'' => in_quotes = !in_quotes,
+            '{' | '(' if !in_quotes => depth += 1,
+            '}' | ')' if !in_quotes => depth -= 1,
+            _ => {}
+        }
+
+        if !in_quotes && depth == 0 {
+            match separator {
+                Separator::Comma if c == ',' => {
+                    parts.push(current.trim().to_string());
+                    current.clear();
+                    i += 1;
+                    continue;
+                }
+                Separator::And if c.is_whitespace() && is_and_keyword(&chars, i + 1) => {
+                    parts.push(current.trim().to_string());
+                    current.clear();
+                    i += 4;
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        current.push(c);
+        i += 1;
+    }
+
+    if !current.trim().is_empty() {
+        parts.push(current.trim().to_string());
+    }
+
+    parts.retain(|part| !part.is_empty());
+
+    parts
+}
+
+/// Checks if `AND` keyword followed by whitespace starts at given position.
+fn is_and_keyword(chars: &[char], start: usize) -> bool {
+    chars.len() > start + 3
+        && chars[start..start + 3]
+            .iter()
+            .collect::<String>()
+            .eq_ignore_ascii_case("AND")
+        && chars[start + 3].is_whitespace()
+}
+
+fn parse_value(value: &str) -> TableOptionValue {
+    if value.starts_with('{') && value.ends_with('}') {
+        let entries = split_top_level(&value[1..value.len() - 1], Separator::Comma)
+            .into_iter()
+            .filter_map(|entry| {
+                let (key, value) = entry.split_once(':')?;
+
+                Some((unquote(key.trim()).to_lowercase(), unquote(value.trim())))
+            })
+            .collect();
+
+        return TableOptionValue::Map(entries);
+    }
+
+    TableOptionValue::Scalar(unquote(value))
+}
+
+fn unquote(value: &str) -> String {
+    value
+        .strip_prefix('\'')
+        .and_then(|value| value.strip_suffix('\''))
+        .map_or_else(|| value.to_string(), |value| value.replace("''", "'"))
+}
+
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    if value.len() >= prefix.len() && value.is_char_boundary(prefix.len()) {
+        let (start, rest) = value.split_at(prefix.len());
+
+        if start.eq_ignore_ascii_case(prefix) {
+            return Some(rest.trim_start());
+        }
+    }
+
+    None
+}
+
+/// Value of map option with given key. Compression class is stored as `sstable_compression` by database.
+fn map_value<'a>(map: &'a BTreeMap<String, String>, key: &str) -> Option<&'a String> {
+    match key {
+        "class" => map.get("class").or_else(|| map.get("sstable_compression")),
+        "sstable_compression" => map.get("sstable_compression").or_else(|| map.get("class")),
+        _ => map.get(key),
+    }
+}
+
+/// Map entries that database adds when they are not set, with their default values.
+const MAP_DEFAULTS: [(&str, &[&str]); 5] = [
+    ("min_threshold", &["4"]),
+    ("max_threshold", &["32"]),
+    ("chunk_length_in_kb", &["4", "16"]),
+    ("crc_check_chance", &["1"]),
+    ("rows_per_partition", &["NONE"]),
+];
+
+fn is_map_default(key: &str, value: &str) -> bool {
+    MAP_DEFAULTS.iter().any(|(default_key, defaults)| {
+        *default_key == key && defaults.iter().any(|default| values_equivalent(None, default, value))
+    })
+}
+
+/// Compares class names, which database stores with package, e.g. `StorageAttachedIndex` is equal to
+/// `org.apache.cassandra.index.sai.StorageAttachedIndex`.
+pub(crate) fn class_names_equivalent(class_name: &str, current: &str) -> bool {
+    let class_name = class_name.rsplit('.').next().unwrap_or(class_name);
+    let current = current.rsplit('.').next().unwrap_or(current);
+
+    class_name.eq_ignore_ascii_case(current)
+}
+
+/// Compares values of options, or of entries of map options with given key.
+fn values_equivalent(key: Option<&str>, value: &str, current: &str) -> bool {
+    if let (Ok(value), Ok(current)) = (value.parse::<f64>(), current.parse::<f64>()) {
+        return value == current;
+    }
+
+    if percentile_equivalent(value, current) {
+        return true;
+    }
+
+    if matches!(key, Some("class" | "sstable_compression")) {
+        return class_names_equivalent(value, current);
+    }
+
+    value.eq_ignore_ascii_case(current)
+}
+
+/// `speculative_retry` is stored as e.g. `99.0PERCENTILE` when `99PERCENTILE` or `99p` is set.
+fn percentile_equivalent(value: &str, current: &str) -> bool {
+    let percentile = |value: &str| {
+        let value = value.to_uppercase();
+        let number = value.strip_suffix("PERCENTILE").or_else(|| value.strip_suffix('P'))?;
+
+        number.parse::<f64>().ok()
+    };
+
+    matches!((percentile(value), percentile(current)), (Some(value), Some(current)) if value == current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(entries: &[(&str, &str)]) -> TableOptionValue {
+        TableOptionValue::Map(
+            entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        )
+    }
+
+    fn scalar(value: &str) -> TableOptionValue {
+        TableOptionValue::Scalar(value.to_string())
+    }
+
+    #[test]
+    fn parse_table_options() {
+        let options = TableOptions::parse(
+            "WITH CLUSTERING ORDER BY (created_at DESC, id) AND COMPACT STORAGE AND gc_grace_seconds = 86400 \
+            AND comment = 'posts and comments' AND compaction = {'class': 'LeveledCompactionStrategy', \
+            'sstable_size_in_mb': '160'}",
+        );
+
+        assert_eq!(
+            options.clustering_order,
+            vec![
+                ("created_at".to_string(), "DESC".to_string()),
+                ("id".to_string(), "ASC".to_string())
+            ]
+        );
+        assert!(options.compact_storage);
+        assert_eq!(options.options["gc_grace_seconds"], scalar("86400"));
+        assert_eq!(options.options["comment"], scalar("posts and comments"));
+        assert_eq!(
+            options.options["compaction"],
+            map(&[("class", "LeveledCompactionStrategy"), ("sstable_size_in_mb", "160")])
+        );
+    }
+
+    #[test]
+    fn parse_quoted_values() {
+        let options = TableOptions::parse("comment = 'it''s here' and caching = {'keys': 'ALL'}");
+
+        assert_eq!(options.options["comment"], scalar("it's here"));
+        assert_eq!(options.options["caching"], map(&[("keys", "ALL")]));
+        assert_eq!(
+            options.alter_clause().unwrap(),
+            "caching = {'keys': 'ALL'} AND comment = 'it''s here'"
+        );
+    }
+
+    #[test]
+    fn clustering_order_defaults_to_ascending() {
+        let options = TableOptions::parse("CLUSTERING ORDER BY (created_at DESC)");
+        let keys = vec!["created_at".to_string(), "id".to_string()];
+
+        assert_eq!(
+            options.clustering_order(&keys),
+            vec![
+                ("created_at".to_string(), "DESC".to_string()),
+                ("id".to_string(), "ASC".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn scalar_equivalence() {
+        assert!(scalar("0.1").is_equivalent(&scalar("0.10")));
+        assert!(scalar("99PERCENTILE").is_equivalent(&scalar("99.0PERCENTILE")));
+        assert!(scalar("99p").is_equivalent(&scalar("99.0PERCENTILE")));
+        assert!(!scalar("0.1").is_equivalent(&scalar("0.2")));
+        // only class names are compared without package
+        assert!(!scalar("v1.posts").is_equivalent(&scalar("v2.posts")));
+        assert!(!scalar("LeveledCompactionStrategy")
+            .is_equivalent(&scalar("org.apache.cassandra.db.compaction.LeveledCompactionStrategy")));
+    }
+
+    #[test]
+    fn class_is_compared_without_package() {
+        let code = map(&[("class", "LeveledCompactionStrategy")]);
+        let db = map(&[("class", "org.apache.cassandra.db.compaction.LeveledCompactionStrategy")]);
+
+        assert!(code.is_equivalent(&db));
+        assert!(!map(&[("class", "SizeTieredCompactionStrategy")]).is_equivalent(&db));
+    }
+
+    #[test]
+    fn compression_class_is_stored_as_sstable_compression() {
+        let code = map(&[("class", "LZ4Compressor")]);
+        let db = map(&[("sstable_compression", "org.apache.cassandra.io.compress.LZ4Compressor")]);
+
+        assert!(code.is_equivalent(&db));
+    }
+
+    #[test]
+    fn map_entries_removed_in_code_are_detected() {
+        let code = map(&[("class", "LeveledCompactionStrategy")]);
+        let db = map(&[("class", "LeveledCompactionStrategy"), ("sstable_size_in_mb", "160")]);
+
+        assert!(!code.is_equivalent(&db));
+        assert!(!db.is_equivalent(&code));
+    }
+
+    #[test]
+    fn map_entries_added_by_database_are_ignored() {
+        let code = map(&[("class", "SizeTieredCompactionStrategy")]);
+        let db = map(&[
+            ("class", "SizeTieredCompactionStrategy"),
+            ("max_threshold", "32"),
+            ("min_threshold", "4"),
+        ]);
+
+        assert!(code.is_equivalent(&db));
+        assert!(!code.is_equivalent(&map(&[
+            ("class", "SizeTieredCompactionStrategy"),
+            ("min_threshold", "8")
+        ])));
+    }
+
+    #[test]
+    fn changed_options() {
+        let code =
+            TableOptions::parse("gc_grace_seconds = 3600 AND compaction = {'class': 'LeveledCompactionStrategy'}");
+        let db = TableOptions::parse(
+            "gc_grace_seconds = 3600 AND default_time_to_live = 0 AND compaction = {'class': \
+            'LeveledCompactionStrategy', 'tombstone_threshold': '0.3'}",
+        );
+
+        let changed = code.changed_options(&db);
+
+        assert_eq!(
+            changed.alter_clause().unwrap(),
+            "compaction = {'class': 'LeveledCompactionStrategy'}"
+        );
+    }
+}
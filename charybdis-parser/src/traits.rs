@@ -4,14 +4,18 @@ use proc_macro2::TokenStream;
 use quote::ToTokens;
 use syn::parse::{Parse, ParseStream};
 
+use crate::schema::secondary_indexes::IndexDefinition;
 use crate::traits::array::ToStringCollection;
 use crate::traits::hash::hash_expr_lit_to_hash;
+use crate::traits::index::parse_index_definition;
 
 mod array;
 mod hash;
+mod index;
 pub mod string;
 
 static EMPTY_VEC: Vec<String> = Vec::new();
+static EMPTY_INDEXES: Vec<IndexDefinition> = Vec::new();
 
 #[derive(Debug, Default, Clone)]
 pub struct CharybdisMacroArgs {
@@ -23,6 +27,12 @@ pub struct CharybdisMacroArgs {
     pub static_columns: Option<Vec<String>>,
     pub global_secondary_indexes: Option<Vec<String>>,
     pub local_secondary_indexes: Option<Vec<String>>,
+    pub vector_indexes: Option<Vec<String>>,
+    pub indexes: Option<Vec<IndexDefinition>>,
+    /// Default TTL in seconds of inserted and updated rows.
+    pub default_ttl: Option<u64>,
+    /// Name of generated `WritetimeModel::Writetime` struct, `<Model>Writetime` by default.
+    pub writetime_struct: Option<String>,
     pub exclude_partial_model: Option<bool>,
     pub fields_names: Option<Vec<String>>,
     pub field_types_hash: Option<HashMap<String, TokenStream>>,
@@ -55,6 +65,14 @@ impl CharybdisMacroArgs {
         self.local_secondary_indexes.as_ref().map_or(&EMPTY_VEC, |x| x)
     }
 
+    pub fn vector_indexes(&self) -> &Vec<String> {
+        self.vector_indexes.as_ref().map_or(&EMPTY_VEC, |x| x)
+    }
+
+    pub fn indexes(&self) -> &Vec<IndexDefinition> {
+        self.indexes.as_ref().map_or(&EMPTY_INDEXES, |x| x)
+    }
+
     pub fn primary_key(&self) -> Vec<&String> {
         self.partition_keys().iter().chain(self.clustering_keys()).collect()
     }
@@ -70,6 +88,10 @@ impl Parse for CharybdisMacroArgs {
         let mut static_columns = None;
         let mut global_secondary_indexes = None;
         let mut local_secondary_indexes = None;
+        let mut vector_indexes = None;
+        let mut indexes = None;
+        let mut default_ttl = None;
+        let mut writetime_struct = None;
         let mut fields_names = None;
         let mut field_types_hash = None;
         let mut field_attributes_hash = None;
@@ -123,6 +145,30 @@ impl Parse for CharybdisMacroArgs {
 
                     local_secondary_indexes = Some(parsed)
                 }
+                "vector_indexes" => {
+                    let array: syn::ExprArray = input.parse()?;
+                    let parsed = array.to_vec();
+
+                    vector_indexes = Some(parsed)
+                }
+                "indexes" => {
+                    let array: syn::ExprArray = input.parse()?;
+                    let parsed = array
+                        .elems
+                        .iter()
+                        .map(parse_index_definition)
+                        .collect::<syn::Result<Vec<IndexDefinition>>>()?;
+
+                    indexes = Some(parsed)
+                }
+                "default_ttl" => {
+                    let value: syn::LitInt = input.parse()?;
+                    default_ttl = Some(value.base10_parse::<u64>()?);
+                }
+                "writetime_struct" => {
+                    let value: syn::Ident = input.parse()?;
+                    writetime_struct = Some(value.to_string());
+                }
                 "exclude_partial_model" => {
                     let value: syn::LitBool = input.parse()?;
                     exclude_partial_model = Option::from(value.value());
@@ -167,6 +213,10 @@ impl Parse for CharybdisMacroArgs {
             static_columns,
             global_secondary_indexes,
             local_secondary_indexes,
+            vector_indexes,
+            indexes,
+            default_ttl,
+            writetime_struct,
             fields_names,
             field_types_hash,
             field_attributes_hash,
@@ -0,0 +1,186 @@
+use quote::ToTokens;
+use syn::spanned::Spanned;
+use syn::{Expr, Lit};
+
+use crate::schema::secondary_indexes::{IndexDefinition, IndexTargetKind};
+
+/// Parses single element of `indexes` model argument:
+///
+/// - `tags`, `keys(tags)`, `values(tags)`, `entries(tags)` or `full(tags)` for index on column or collection part
+/// - `index(keys(tags), name = posts_tag_keys)` for named index
+/// - `sai(title, name = ..., options = "{'case_sensitive': 'false'}")` for storage-attached index
+/// - `custom(title, using = "org.apache.cassandra.index.sasi.SASIIndex", options = "{'mode': 'CONTAINS'}")` for
+///   index with custom class
+pub(crate) fn parse_index_definition(expr: &Expr) -> syn::Result<IndexDefinition> {
+    if let Expr::Call(call) = expr {
+        let function = call.func.to_token_stream().to_string();
+
+        let class_name = match function.as_str() {
+            "index" => None,
+            "sai" => Some(IndexDefinition::STORAGE_ATTACHED_INDEX.to_string()),
+            "custom" => Some(String::new()),
+            _ => return parse_target(expr).map(|(kind, column)| definition(kind, column)),
+        };
+
+        let mut args = call.args.iter();
+        let target = args
+            .next()
+            .ok_or_else(|| syn::Error::new(call.span(), format!("{} index requires target column", function)))?;
+        let (kind, column) = parse_target(target)?;
+        let mut index = definition(kind, column);
+        index.class_name = class_name;
+
+        for arg in args {
+            let Expr::Assign(assign) = arg else {
+                return Err(syn::Error::new(arg.span(), "Expected `key = value` index argument"));
+            };
+
+            let key = assign.left.to_token_stream().to_string();
+            let value = literal_or_ident(&assign.right)?;
+
+            match key.as_str() {
+                "name" => index.name = value,
+                "using" => index.class_name = Some(value),
+                "options" => index.options = IndexDefinition::parse_options(&value),
+                _ => {
+                    return Err(syn::Error::new(
+                        assign.left.span(),
+                        format!("Unknown index argument `{}`. Use `name`, `using` or `options`.", key),
+                    ))
+                }
+            }
+        }
+
+        if index
+            .class_name
+            .as_ref()
+            .is_some_and(|class_name| class_name.is_empty())
+        {
+            return Err(syn::Error::new(
+                call.span(),
+                "Custom index requires class, e.g. `custom(title, using = \"StorageAttachedIndex\")`",
+            ));
+        }
+
+        return Ok(index);
+    }
+
+    parse_target(expr).map(|(kind, column)| definition(kind, column))
+}
+
+fn definition(kind: IndexTargetKind, column: String) -> IndexDefinition {
+    IndexDefinition {
+        name: String::new(),
+        kind,
+        column,
+        class_name: None,
+        options: Default::default(),
+    }
+}
+
+/// Parses `tags` or `keys(tags)` into target kind and field name.
+fn parse_target(expr: &Expr) -> syn::Result<(IndexTargetKind, String)> {
+    match expr {
+        Expr::Path(path) => Ok((IndexTargetKind::Column, path.to_token_stream().to_string())),
+        Expr::Call(call) => {
+            let function = call.func.to_token_stream().to_string();
+            let kind = IndexTargetKind::from_function_name(&function).ok_or_else(|| {
+                syn::Error::new(
+                    call.func.span(),
+                    format!(
+                        "Unknown index target `{}`. Use `keys`, `values`, `entries` or `full`.",
+                        function
+                    ),
+                )
+            })?;
+
+            match call.args.first() {
+                Some(Expr::Path(path)) if call.args.len() == 1 => Ok((kind, path.to_token_stream().to_string())),
+                _ => Err(syn::Error::new(call.span(), "Expected single field as index target")),
+            }
+        }
+        _ => Err(syn::Error::new(
+            expr.span(),
+            "Expected field or collection index target",
+        )),
+    }
+}
+
+fn literal_or_ident(expr: &Expr) -> syn::Result<String> {
+    match expr {
+        Expr::Lit(lit) => match &lit.lit {
+            Lit::Str(value) => Ok(value.value()),
+            _ => Err(syn::Error::new(lit.span(), "Expected string literal")),
+        },
+        Expr::Path(path) => Ok(path.to_token_stream().to_string()),
+        _ => Err(syn::Error::new(expr.span(), "Expected string literal or identifier")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(expr: &str) -> syn::Result<IndexDefinition> {
+        parse_index_definition(&syn::parse_str::<Expr>(expr).unwrap())
+    }
+
+    #[test]
+    fn parses_targets() {
+        let target = |expr: &str| parse_target(&syn::parse_str::<Expr>(expr).unwrap());
+
+        assert_eq!(target("tags").unwrap(), (IndexTargetKind::Column, "tags".to_string()));
+        assert_eq!(
+            target("keys(tags)").unwrap(),
+            (IndexTargetKind::Keys, "tags".to_string())
+        );
+        assert_eq!(
+            target("full(tags)").unwrap(),
+            (IndexTargetKind::Full, "tags".to_string())
+        );
+        assert!(target("prefix(tags)").is_err());
+        assert!(target("keys(tags, labels)").is_err());
+        assert!(target("\"tags\"").is_err());
+    }
+
+    #[test]
+    fn parses_plain_and_named_indexes() {
+        let index = parse("values(tags)").unwrap();
+
+        assert_eq!(index.kind, IndexTargetKind::Values);
+        assert_eq!(index.column, "tags");
+        assert!(index.is_plain_index());
+
+        let index = parse("index(keys(attributes), name = posts_attribute_keys)").unwrap();
+
+        assert_eq!(index.name, "posts_attribute_keys");
+        assert_eq!(index.kind, IndexTargetKind::Keys);
+        assert_eq!(index.class_name, None);
+    }
+
+    #[test]
+    fn parses_custom_indexes() {
+        let index = parse("sai(title, options = \"{'case_sensitive': 'false'}\")").unwrap();
+
+        assert_eq!(
+            index.class_name.as_deref(),
+            Some(IndexDefinition::STORAGE_ATTACHED_INDEX)
+        );
+        assert_eq!(index.options.get("case_sensitive").map(String::as_str), Some("false"));
+
+        let index = parse("custom(title, using = \"org.apache.cassandra.index.sasi.SASIIndex\")").unwrap();
+
+        assert_eq!(
+            index.class_name.as_deref(),
+            Some("org.apache.cassandra.index.sasi.SASIIndex")
+        );
+    }
+
+    #[test]
+    fn invalid_index_arguments_are_errors() {
+        assert!(parse("custom(title)").is_err());
+        assert!(parse("sai()").is_err());
+        assert!(parse("sai(title, mode = \"CONTAINS\")").is_err());
+        assert!(parse("sai(title, \"options\")").is_err());
+    }
+}
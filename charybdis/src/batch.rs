@@ -1,9 +1,10 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::errors::CharybdisError;
 use crate::model::Model;
 use crate::options::{Consistency, ExecutionProfileHandle, RetryPolicy, SerialConsistency};
-use crate::query::{CharybdisQuery, QueryExecutor, QueryValue};
+use crate::query::{ttl_seconds, CharybdisQuery, QueryExecutor, QueryValue, TtlValues};
 use scylla::_macro_internal::{RowSerializationContext, RowWriter, SerializationError};
 use scylla::client::caching_session::CachingSession;
 use scylla::observability::history::HistoryListener;
@@ -13,7 +14,10 @@ use scylla::statement::batch::{Batch, BatchType};
 
 pub struct CharybdisModelBatch<'a, Val: SerializeRow, M: Model> {
     inner: Batch,
-    values: Vec<QueryValue<'a, Val, M>>,
+    values: Vec<TtlValues<QueryValue<'a, Val, M>>>,
+    ttl: Option<i32>,
+    /// Model query appended while TTL was set, but without `USING TTL ?` variant, which fails the batch.
+    missing_ttl_query: Option<&'static str>,
 }
 
 impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
@@ -21,6 +25,8 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
         Self {
             inner: Batch::default(),
             values: Vec::new(),
+            ttl: M::DEFAULT_TTL.map(ttl_seconds),
+            missing_ttl_query: None,
         }
     }
 
@@ -28,13 +34,17 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
         Self {
             inner: Batch::new(BatchType::Unlogged),
             values: Vec::new(),
+            ttl: M::DEFAULT_TTL.map(ttl_seconds),
+            missing_ttl_query: None,
         }
     }
 
-    pub(crate) fn from_batch(batch: &Batch) -> Self {
+    pub(crate) fn from_batch(batch: &Batch, ttl: Option<i32>) -> Self {
         Self {
             inner: batch.clone(),
             values: Vec::new(),
+            ttl,
+            missing_ttl_query: None,
         }
     }
 
@@ -42,6 +52,32 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
         self.inner.append_statement(statement);
     }
 
+    /// Appends model insert or update, using its `USING TTL ?` variant if TTL is set. Models implemented without
+    /// macro may not define it, in which case the batch fails on execution.
+    fn append_model_query(&mut self, query: &'static str, ttl_query: &'static str, value: QueryValue<'a, Val, M>) {
+        match self.ttl {
+            Some(_) if !ttl_query.is_empty() => {
+                self.append_query_to_batch(ttl_query);
+                self.values.push(TtlValues::new(value, self.ttl));
+            }
+            ttl => {
+                if ttl.is_some() {
+                    self.missing_ttl_query.get_or_insert(query);
+                }
+
+                self.append_query_to_batch(query);
+                self.values.push(TtlValues::new(value, None));
+            }
+        }
+    }
+
+    /// Sets TTL of inserts and updates appended afterwards, overriding model's `default_ttl`. Statements appended
+    /// with `append_statement` bind their own TTL.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl_seconds(ttl));
+        self
+    }
+
     pub fn consistency(mut self, consistency: Consistency) -> Self {
         self.inner.set_consistency(consistency);
         self
@@ -96,7 +132,7 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
         let chunks = iter.chunks(chunk_size);
 
         for chunk in chunks {
-            let mut batch: CharybdisModelBatch<M, M> = CharybdisModelBatch::from_batch(&self.inner);
+            let mut batch: CharybdisModelBatch<M, M> = CharybdisModelBatch::from_batch(&self.inner, self.ttl);
 
             batch.append_inserts(chunk);
 
@@ -115,7 +151,7 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
         let chunks = iter.chunks(chunk_size);
 
         for chunk in chunks {
-            let mut batch: CharybdisModelBatch<M, M> = CharybdisModelBatch::from_batch(&self.inner);
+            let mut batch: CharybdisModelBatch<M, M> = CharybdisModelBatch::from_batch(&self.inner, self.ttl);
 
             batch.append_inserts_if_not_exist(chunk);
 
@@ -134,7 +170,7 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
         let chunks = iter.chunks(chunk_size);
 
         for chunk in chunks {
-            let mut batch: CharybdisModelBatch<M, M> = CharybdisModelBatch::from_batch(&self.inner);
+            let mut batch: CharybdisModelBatch<M, M> = CharybdisModelBatch::from_batch(&self.inner, self.ttl);
 
             batch.append_updates(chunk);
 
@@ -153,7 +189,7 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
         let chunks = iter.chunks(chunk_size);
 
         for chunk in chunks {
-            let mut batch: CharybdisModelBatch<M, M> = CharybdisModelBatch::from_batch(&self.inner);
+            let mut batch: CharybdisModelBatch<M, M> = CharybdisModelBatch::from_batch(&self.inner, self.ttl);
 
             for model in chunk {
                 batch.append_delete(model);
@@ -174,7 +210,7 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
         let chunks = iter.chunks(chunk_size);
 
         for chunk in chunks {
-            let mut batch: CharybdisModelBatch<M, M> = CharybdisModelBatch::from_batch(&self.inner);
+            let mut batch: CharybdisModelBatch<M, M> = CharybdisModelBatch::from_batch(&self.inner, self.ttl);
 
             batch.append_deletes_by_partition_key(chunk);
 
@@ -193,7 +229,7 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
     ) -> Result<(), CharybdisError> {
         while !values.is_empty() {
             let chunk: Vec<Val> = values.drain(..std::cmp::min(chunk_size, values.len())).collect();
-            let mut batch: CharybdisModelBatch<Val, M> = CharybdisModelBatch::from_batch(&self.inner);
+            let mut batch: CharybdisModelBatch<Val, M> = CharybdisModelBatch::from_batch(&self.inner, self.ttl);
 
             batch.append_statements(statement, chunk)?;
 
@@ -212,14 +248,12 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
     }
 
     pub fn append_insert(&mut self, model: &'a M) -> &mut Self {
-        self.append_query_to_batch(M::INSERT_QUERY);
-        self.values.push(QueryValue::Model(model));
+        self.append_model_query(M::INSERT_QUERY, M::INSERT_WITH_TTL_QUERY, QueryValue::Model(model));
         self
     }
 
     pub fn append_insert_owned(&mut self, model: M) -> &mut Self {
-        self.append_query_to_batch(M::INSERT_QUERY);
-        self.values.push(QueryValue::ModelOwned(model));
+        self.append_model_query(M::INSERT_QUERY, M::INSERT_WITH_TTL_QUERY, QueryValue::ModelOwned(model));
         self
     }
 
@@ -231,8 +265,11 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
     }
 
     pub fn append_insert_if_not_exist(&mut self, model: &'a M) -> &mut Self {
-        self.append_query_to_batch(M::INSERT_IF_NOT_EXIST_QUERY);
-        self.values.push(QueryValue::Model(model));
+        self.append_model_query(
+            M::INSERT_IF_NOT_EXIST_QUERY,
+            M::INSERT_IF_NOT_EXIST_WITH_TTL_QUERY,
+            QueryValue::Model(model),
+        );
         self
     }
 
@@ -244,14 +281,12 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
     }
 
     pub fn append_update(&mut self, model: &'a M) -> &mut Self {
-        self.append_query_to_batch(M::UPDATE_QUERY);
-        self.values.push(QueryValue::Model(model));
+        self.append_model_query(M::UPDATE_QUERY, M::UPDATE_WITH_TTL_QUERY, QueryValue::Model(model));
         self
     }
 
     pub fn append_update_owned(&mut self, model: M) -> &mut Self {
-        self.append_query_to_batch(M::UPDATE_QUERY);
-        self.values.push(QueryValue::ModelOwned(model));
+        self.append_model_query(M::UPDATE_QUERY, M::UPDATE_WITH_TTL_QUERY, QueryValue::ModelOwned(model));
         self
     }
 
@@ -264,13 +299,15 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
 
     pub fn append_delete(&mut self, model: &M) -> &mut Self {
         self.append_query_to_batch(M::DELETE_QUERY);
-        self.values.push(QueryValue::PrimaryKey(model.primary_key_values()));
+        self.values
+            .push(TtlValues::new(QueryValue::PrimaryKey(model.primary_key_values()), None));
         self
     }
 
     pub fn append_delete_owned(&mut self, model: M) -> &mut Self {
         self.append_query_to_batch(M::DELETE_QUERY);
-        self.values.push(QueryValue::PrimaryKey(model.primary_key_values()));
+        self.values
+            .push(TtlValues::new(QueryValue::PrimaryKey(model.primary_key_values()), None));
         self
     }
 
@@ -283,7 +320,10 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
 
     pub fn append_delete_by_partition_key(&mut self, model: &'a M) -> &mut Self {
         self.append_query_to_batch(M::DELETE_BY_PARTITION_KEY_QUERY);
-        self.values.push(QueryValue::PartitionKey(model.partition_key_values()));
+        self.values.push(TtlValues::new(
+            QueryValue::PartitionKey(model.partition_key_values()),
+            None,
+        ));
         self
     }
 
@@ -296,11 +336,15 @@ impl<'a, Val: SerializeRow, M: Model> CharybdisModelBatch<'a, Val, M> {
 
     pub fn append_statement(&mut self, statement: &str, val: Val) -> &mut Self {
         self.append_query_to_batch(statement);
-        self.values.push(QueryValue::Owned(val));
+        self.values.push(TtlValues::new(QueryValue::Owned(val), None));
         self
     }
 
     pub async fn execute(&self, db_session: &CachingSession) -> Result<QueryResult, CharybdisError> {
+        if let Some(query) = self.missing_ttl_query {
+            return Err(CharybdisError::MissingTtlQuery(query));
+        }
+
         let result = db_session
             .batch(&self.inner, &self.values)
             .await
@@ -408,7 +452,8 @@ impl<'a> CharybdisBatch<'a> {
     {
         self.inner.append_statement(query.query_string);
 
-        self.values.push(SerializeRowBox::new(query.values));
+        self.values
+            .push(SerializeRowBox::new(TtlValues::new(query.values, query.ttl)));
 
         self
     }
@@ -24,6 +24,8 @@ pub enum CharybdisError {
     NextRowError(&'static str, NextRowError),
     TypeCheckError(&'static str, TypeCheckError),
     NotFoundError(&'static str),
+    /// TTL is set on model query, but model doesn't define its `USING TTL ?` variant.
+    MissingTtlQuery(&'static str),
     JsonError(serde_json::Error),
 }
 
@@ -68,6 +70,11 @@ impl fmt::Display for CharybdisError {
             CharybdisError::TypeCheckError(query, e) => {
                 write!(f, "Statement: {}\nTypeCheckError: {:?}", query.bright_purple(), e)
             }
+            CharybdisError::MissingTtlQuery(query) => write!(
+                f,
+                "TTL is set, but model doesn't define `USING TTL ?` variant of statement: {}",
+                query.bright_purple()
+            ),
             CharybdisError::JsonError(e) => write!(f, "JsonError: {:?}", e),
         }
     }
@@ -90,3 +97,19 @@ impl Error for CharybdisError {
         }
     }
 }
+
+/// Returned on serialization of query values when TTL is set, but statement has no `USING TTL ?` bind marker as
+/// its first or last bind marker.
+#[derive(Debug)]
+pub struct TtlBindMarkerError;
+
+impl fmt::Display for TtlBindMarkerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "TTL is set, but statement has no `USING TTL ?` bind marker as its first or last bind marker"
+        )
+    }
+}
+
+impl Error for TtlBindMarkerError {}
@@ -1,5 +1,6 @@
 use scylla::deserialize::row::DeserializeRow;
 use scylla::serialize::row::SerializeRow;
+use std::time::Duration;
 
 pub trait BaseModel: SerializeRow + for<'frame, 'metadata> DeserializeRow<'frame, 'metadata> {
     // usually tuple of primary key values
@@ -57,6 +58,26 @@ pub trait Model: BaseModel {
     const UPDATE_QUERY: &'static str;
     const DELETE_QUERY: &'static str;
     const DELETE_BY_PARTITION_KEY_QUERY: &'static str;
+
+    /// `USING TTL ?` variants of insert and update queries, used once TTL is set on query or batch. They are
+    /// generated by macro, so models implemented by hand don't need them unless they set TTL, which otherwise fails
+    /// with `CharybdisError::MissingTtlQuery`.
+    const INSERT_WITH_TTL_QUERY: &'static str = "";
+    const INSERT_IF_NOT_EXIST_WITH_TTL_QUERY: &'static str = "";
+    const UPDATE_WITH_TTL_QUERY: &'static str = "";
+
+    /// TTL of inserted and updated rows, set with `default_ttl` model argument.
+    const DEFAULT_TTL: Option<Duration> = None;
+}
+
+/// Implemented by `charybdis_model` for models having at least one non-key column that supports `WRITETIME` and
+/// `TTL` selectors, i.e. one that isn't counter or non-frozen collection.
+pub trait WritetimeModel: Model {
+    /// `WRITETIME` and `TTL` of non-key columns, generated as `<Model>Writetime` struct unless renamed with
+    /// `writetime_struct` model argument.
+    type Writetime: for<'frame, 'metadata> DeserializeRow<'frame, 'metadata>;
+
+    const FIND_WRITETIME_BY_PRIMARY_KEY_QUERY: &'static str;
 }
 
 ///
@@ -1,5 +1,5 @@
-use crate::model::BaseModel;
-use crate::query::{CharybdisQuery, ModelPaged, ModelRow, ModelStream, OptionalModelRow, QueryValue};
+use crate::model::{BaseModel, WritetimeModel};
+use crate::query::{CharybdisQuery, ModelPaged, ModelRow, ModelStream, ModelWritetime, OptionalModelRow, QueryValue};
 use scylla::response::PagingState;
 use scylla::serialize::row::SerializeRow;
 
@@ -83,6 +83,26 @@ where
             QueryValue::Owned(self.partition_key_values()),
         )
     }
+
+    /// Finds `WRITETIME` and `TTL` of non-key columns of the row with given primary key.
+    fn find_writetime_by_primary_key_value<'a>(
+        value: Self::PrimaryKey,
+    ) -> CharybdisQuery<'a, Self::PrimaryKey, Self, ModelWritetime>
+    where
+        Self: WritetimeModel,
+    {
+        CharybdisQuery::new(Self::FIND_WRITETIME_BY_PRIMARY_KEY_QUERY, QueryValue::Owned(value))
+    }
+
+    fn find_writetime_by_primary_key(&self) -> CharybdisQuery<'_, Self::PrimaryKey, Self, ModelWritetime>
+    where
+        Self: WritetimeModel,
+    {
+        CharybdisQuery::new(
+            Self::FIND_WRITETIME_BY_PRIMARY_KEY_QUERY,
+            QueryValue::Owned(self.primary_key_values()),
+        )
+    }
 }
 
 impl<M: BaseModel + 'static> Find for M {}
@@ -5,10 +5,12 @@ use crate::query::{CharybdisCbQuery, CharybdisQuery, ModelMutation, QueryValue};
 pub trait Insert: Model {
     fn insert(&self) -> CharybdisQuery<Self, Self, ModelMutation> {
         CharybdisQuery::new(Self::INSERT_QUERY, QueryValue::Model(self))
+            .ttl_query(Self::INSERT_WITH_TTL_QUERY, Self::DEFAULT_TTL)
     }
 
     fn insert_if_not_exists(&self) -> CharybdisQuery<Self, Self, ModelMutation> {
         CharybdisQuery::new(Self::INSERT_IF_NOT_EXIST_QUERY, QueryValue::Model(self))
+            .ttl_query(Self::INSERT_IF_NOT_EXIST_WITH_TTL_QUERY, Self::DEFAULT_TTL)
     }
 }
 
@@ -17,6 +19,7 @@ impl<M: Model> Insert for M {}
 pub trait InsertWithCallbacks<'a>: Callbacks {
     fn insert_cb(&'a mut self, extension: &'a Self::Extension) -> CharybdisCbQuery<'a, Self, InsertAction<Self>, Self> {
         CharybdisCbQuery::new(Self::INSERT_QUERY, self, extension)
+            .ttl_query(Self::INSERT_WITH_TTL_QUERY, Self::DEFAULT_TTL)
     }
 }
 
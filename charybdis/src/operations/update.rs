@@ -5,6 +5,7 @@ use crate::query::{CharybdisCbQuery, CharybdisQuery, ModelMutation, QueryValue};
 pub trait Update: Model {
     fn update(&self) -> CharybdisQuery<Self, Self, ModelMutation> {
         CharybdisQuery::new(Self::UPDATE_QUERY, QueryValue::Model(self))
+            .ttl_query(Self::UPDATE_WITH_TTL_QUERY, Self::DEFAULT_TTL)
     }
 }
 
@@ -13,6 +14,7 @@ impl<M: Model> Update for M {}
 pub trait UpdateWithCallbacks<'a>: Callbacks {
     fn update_cb(&'a mut self, extension: &'a Self::Extension) -> CharybdisCbQuery<'a, Self, UpdateAction<Self>, Self> {
         CharybdisCbQuery::new(Self::UPDATE_QUERY, self, extension)
+            .ttl_query(Self::UPDATE_WITH_TTL_QUERY, Self::DEFAULT_TTL)
     }
 }
 
@@ -1,7 +1,7 @@
 use crate::callbacks::{CallbackAction, Callbacks};
-use crate::errors::CharybdisError;
+use crate::errors::{CharybdisError, TtlBindMarkerError};
 use crate::iterator::CharybdisModelIterator;
-use crate::model::BaseModel;
+use crate::model::{BaseModel, WritetimeModel};
 use crate::options::{Consistency, SerialConsistency};
 use crate::stream::CharybdisModelStream;
 use scylla::client::caching_session::CachingSession;
@@ -13,6 +13,7 @@ use scylla::policies::retry::RetryPolicy;
 use scylla::response::query_result::QueryResult;
 use scylla::response::{PagingState, PagingStateResponse};
 use scylla::serialize::row::{RowSerializationContext, SerializeRow};
+use scylla::serialize::value::SerializeValue;
 use scylla::serialize::{writers::RowWriter, SerializationError};
 use scylla::statement::Statement;
 use std::sync::Arc;
@@ -23,6 +24,7 @@ pub struct OptionalModelRow;
 pub struct ModelStream;
 pub struct ModelPaged;
 pub struct ModelMutation;
+pub struct ModelWritetime;
 
 pub trait QueryType<M: BaseModel> {
     type Output;
@@ -48,6 +50,10 @@ impl<M: BaseModel> QueryType<M> for ModelMutation {
     type Output = QueryResult;
 }
 
+impl<M: WritetimeModel> QueryType<M> for ModelWritetime {
+    type Output = M::Writetime;
+}
+
 pub trait QueryExecutor<M: BaseModel>: QueryType<M> {
     async fn execute<Val, Qe>(
         query: CharybdisQuery<Val, M, Qe>,
@@ -182,8 +188,13 @@ impl<M: BaseModel> QueryExecutor<M> for ModelMutation {
         Val: SerializeRow,
         Qe: QueryExecutor<M>,
     {
+        if query.ttl.is_some() && query.ttl_query_string == Some("") {
+            return Err(CharybdisError::MissingTtlQuery(query.query_string));
+        }
+
+        let values = TtlValues::new(query.values, query.ttl);
         let res = session
-            .execute_unpaged(query.inner, query.values)
+            .execute_unpaged(query.inner, values)
             .await
             .map_err(|e| CharybdisError::ExecutionError(query.query_string, e))?;
 
@@ -191,6 +202,34 @@ impl<M: BaseModel> QueryExecutor<M> for ModelMutation {
     }
 }
 
+impl<M: WritetimeModel> QueryExecutor<M> for ModelWritetime {
+    async fn execute<Val, Qe>(
+        query: CharybdisQuery<'_, Val, M, Qe>,
+        session: &CachingSession,
+    ) -> Result<Self::Output, CharybdisError>
+    where
+        M: BaseModel,
+        Val: SerializeRow,
+        Qe: QueryExecutor<M>,
+    {
+        let res = session
+            .execute_unpaged(query.inner, query.values)
+            .await
+            .map_err(|e| CharybdisError::ExecutionError(query.query_string, e))?;
+
+        let res = res
+            .into_rows_result()
+            .map_err(|e| CharybdisError::IntoRowsResultError(query.query_string, e))?;
+
+        let row = res.first_row::<M::Writetime>().map_err(|e| match e {
+            FirstRowError::RowsEmpty => CharybdisError::NotFoundError(query.query_string),
+            _ => CharybdisError::FirstRowError(query.query_string, e),
+        })?;
+
+        Ok(row)
+    }
+}
+
 #[derive(Default)]
 pub enum QueryValue<'a, Val: SerializeRow, M: BaseModel> {
     Owned(Val),
@@ -229,11 +268,73 @@ impl<Val: SerializeRow, M: BaseModel> SerializeRow for QueryValue<'_, Val, M> {
     }
 }
 
+/// Name of `USING TTL ?` bind marker in prepared statement metadata.
+const TTL_BIND_MARKER: &str = "[ttl]";
+
+/// Maximum TTL accepted by ScyllaDB and Cassandra, 20 years.
+pub const MAX_TTL: Duration = Duration::from_secs(20 * 365 * 24 * 60 * 60);
+
+/// TTL in seconds as bound to `USING TTL ?`, where TTL of 0 means that value doesn't expire. Sub-second TTL is
+/// rounded up, so it doesn't turn into TTL that never expires, and TTL above `MAX_TTL` is clamped to it.
+pub(crate) fn ttl_seconds(ttl: Duration) -> i32 {
+    let ttl = ttl.min(MAX_TTL);
+    let seconds = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+
+    seconds as i32
+}
+
+/// Query values with TTL bound to `USING TTL ?` marker, which is the first bind marker of update
+/// and the last bind marker of insert statements.
+pub(crate) struct TtlValues<V: SerializeRow> {
+    values: V,
+    ttl: Option<i32>,
+}
+
+impl<V: SerializeRow> TtlValues<V> {
+    pub(crate) fn new(values: V, ttl: Option<i32>) -> Self {
+        Self { values, ttl }
+    }
+}
+
+impl<V: SerializeRow> SerializeRow for TtlValues<V> {
+    fn serialize(&self, ctx: &RowSerializationContext<'_>, writer: &mut RowWriter) -> Result<(), SerializationError> {
+        let Some(ttl) = self.ttl else {
+            return self.values.serialize(ctx, writer);
+        };
+
+        let columns = ctx.columns();
+        let ttl_position = columns.iter().position(|column| column.name() == TTL_BIND_MARKER);
+
+        match ttl_position {
+            Some(0) => {
+                ttl.serialize(columns[0].typ(), writer.make_cell_writer())?;
+                self.values
+                    .serialize(&RowSerializationContext::from_specs(&columns[1..]), writer)
+            }
+            Some(position) if position == columns.len() - 1 => {
+                self.values
+                    .serialize(&RowSerializationContext::from_specs(&columns[..position]), writer)?;
+                ttl.serialize(columns[position].typ(), writer.make_cell_writer())?;
+
+                Ok(())
+            }
+            _ => Err(SerializationError::new(TtlBindMarkerError)),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ttl.is_none() && self.values.is_empty()
+    }
+}
+
 pub struct CharybdisQuery<'a, Val: SerializeRow, M: BaseModel, Qe: QueryExecutor<M>> {
     inner: Statement,
     paging_state: PagingState,
     pub(crate) query_string: &'static str,
     pub(crate) values: QueryValue<'a, Val, M>,
+    pub(crate) ttl: Option<i32>,
+    /// `USING TTL ?` variant of model query. It's empty for models implemented without macro that don't define it.
+    ttl_query_string: Option<&'static str>,
     _phantom: std::marker::PhantomData<Qe>,
 }
 
@@ -244,6 +345,8 @@ impl<'a, Val: SerializeRow, M: BaseModel, Qe: QueryExecutor<M>> CharybdisQuery<'
             query_string: query,
             values,
             paging_state: PagingState::start(),
+            ttl: None,
+            ttl_query_string: None,
             _phantom: Default::default(),
         }
     }
@@ -332,6 +435,34 @@ impl<'a, Val: SerializeRow, M: BaseModel, Qe: QueryExecutor<M>> CharybdisQuery<'
     }
 }
 
+impl<Val: SerializeRow, M: BaseModel> CharybdisQuery<'_, Val, M, ModelMutation> {
+    /// Sets `USING TTL ?` variant of the query, that is executed once TTL is set, and TTL that is used by default,
+    /// e.g. model's `DEFAULT_TTL`.
+    /// Models implemented without macro don't have to define TTL queries, but setting TTL on their queries is an
+    /// error.
+    pub fn ttl_query(mut self, ttl_query: &'static str, default_ttl: Option<Duration>) -> Self {
+        self.ttl_query_string = Some(ttl_query);
+
+        match default_ttl {
+            Some(ttl) => self.ttl(ttl),
+            None => self,
+        }
+    }
+
+    /// Sets TTL of written values. Generated insert, update and collection queries are replaced with their
+    /// `USING TTL ?` variant, while custom statements must have `USING TTL ?` as their first or last bind marker.
+    /// TTL of zero seconds means that values don't expire, even if model has `default_ttl`.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        if let Some(ttl_query) = self.ttl_query_string.filter(|ttl_query| !ttl_query.is_empty()) {
+            self.inner.contents = ttl_query.to_string();
+            self.query_string = ttl_query;
+        }
+
+        self.ttl = Some(ttl_seconds(ttl));
+        self
+    }
+}
+
 macro_rules! delegate_inner_query_methods {
     ($($method:ident($($param_name:ident: $param_type:ty),*)  ),* $(,)? ) => {
         $(
@@ -373,7 +504,13 @@ impl<'a, M: Callbacks, CbA: CallbackAction<M>, Val: SerializeRow> CharybdisCbQue
         history_listener(history_listener: Arc<dyn HistoryListener>),
         remove_history_listener(),
         profile_handle(profile_handle: Option<ExecutionProfileHandle>),
-        load_balancing_policy(load_balancing_policy: Option<Arc<dyn LoadBalancingPolicy>>)
+        load_balancing_policy(load_balancing_policy: Option<Arc<dyn LoadBalancingPolicy>>),
+        ttl(ttl: Duration)
+    }
+
+    pub(crate) fn ttl_query(mut self, ttl_query: &'static str, default_ttl: Option<Duration>) -> Self {
+        self.inner = self.inner.ttl_query(ttl_query, default_ttl);
+        self
     }
 
     pub async fn execute(self, session: &CachingSession) -> Result<QueryResult, M::Error> {
@@ -387,3 +524,23 @@ impl<'a, M: Callbacks, CbA: CallbackAction<M>, Val: SerializeRow> CharybdisCbQue
         Ok(res)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ttl_seconds_rounds_up_sub_second_ttl() {
+        assert_eq!(ttl_seconds(Duration::ZERO), 0);
+        assert_eq!(ttl_seconds(Duration::from_millis(1)), 1);
+        assert_eq!(ttl_seconds(Duration::from_millis(1500)), 2);
+        assert_eq!(ttl_seconds(Duration::from_secs(60)), 60);
+    }
+
+    #[test]
+    fn ttl_seconds_is_clamped_to_max_ttl() {
+        assert_eq!(ttl_seconds(MAX_TTL), 630_720_000);
+        assert_eq!(ttl_seconds(MAX_TTL + Duration::from_millis(1)), 630_720_000);
+        assert_eq!(ttl_seconds(Duration::MAX), 630_720_000);
+    }
+}
@@ -36,7 +36,25 @@ pub type Map<K, V> = HashMap<K, V>;
 pub type List<T> = Vec<T>;
 pub type Set<T> = HashSet<T>;
 pub type Tuple<T1, T2> = (T1, T2);
-// TODO: Tuple with more than 2 elements
+pub type Tuple3<T1, T2, T3> = (T1, T2, T3);
+pub type Tuple4<T1, T2, T3, T4> = (T1, T2, T3, T4);
+pub type Tuple5<T1, T2, T3, T4, T5> = (T1, T2, T3, T4, T5);
+pub type Tuple6<T1, T2, T3, T4, T5, T6> = (T1, T2, T3, T4, T5, T6);
+pub type Tuple7<T1, T2, T3, T4, T5, T6, T7> = (T1, T2, T3, T4, T5, T6, T7);
+pub type Tuple8<T1, T2, T3, T4, T5, T6, T7, T8> = (T1, T2, T3, T4, T5, T6, T7, T8);
+pub type Tuple9<T1, T2, T3, T4, T5, T6, T7, T8, T9> = (T1, T2, T3, T4, T5, T6, T7, T8, T9);
+pub type Tuple10<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10> = (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+pub type Tuple11<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11> = (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+pub type Tuple12<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12> =
+    (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+pub type Tuple13<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13> =
+    (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+pub type Tuple14<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14> =
+    (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+pub type Tuple15<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15> =
+    (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15);
+pub type Tuple16<T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16> =
+    (T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16);
 
 pub type Frozen<T> = T;
 
@@ -386,3 +404,179 @@ impl SerializeValue for Timeuuid {
         Ok(proof)
     }
 }
+
+/// Fixed size vector of `N` elements used for vector search, e.g. `Vector<Float, 768>` for `vector<float, 768>`
+/// column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vector<T, const N: usize>(pub [T; N]);
+
+impl<T: Default, const N: usize> Default for Vector<T, N> {
+    fn default() -> Self {
+        Vector(std::array::from_fn(|_| T::default()))
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Vector<T, N> {
+    fn from(value: [T; N]) -> Self {
+        Vector(value)
+    }
+}
+
+impl<T, const N: usize> TryFrom<Vec<T>> for Vector<T, N> {
+    type Error = VectorDimensionsError;
+
+    fn try_from(value: Vec<T>) -> Result<Self, Self::Error> {
+        let actual = value.len();
+
+        value
+            .try_into()
+            .map(Vector)
+            .map_err(|_| VectorDimensionsError { expected: N, actual })
+    }
+}
+
+impl<T, const N: usize> std::ops::Deref for Vector<T, N> {
+    type Target = [T; N];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T: Serialize, const N: usize> Serialize for Vector<T, N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+impl<'de, T: Deserialize<'de>, const N: usize> Deserialize<'de> for Vector<T, N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let elements = Vec::<T>::deserialize(deserializer)?;
+
+        Vector::try_from(elements).map_err(serde::de::Error::custom)
+    }
+}
+
+impl<'frame, 'metadata, T, const N: usize> DeserializeValue<'frame, 'metadata> for Vector<T, N>
+where
+    T: DeserializeValue<'frame, 'metadata>,
+{
+    fn type_check(typ: &ColumnType) -> Result<(), TypeCheckError> {
+        match typ {
+            ColumnType::Vector { dimensions, .. } if *dimensions as usize != N => {
+                Err(TypeCheckError::new(VectorDimensionsError {
+                    expected: N,
+                    actual: *dimensions as usize,
+                }))
+            }
+            _ => Vec::<T>::type_check(typ),
+        }
+    }
+
+    fn deserialize(
+        typ: &'metadata ColumnType<'metadata>,
+        v: Option<FrameSlice<'frame>>,
+    ) -> Result<Self, DeserializationError> {
+        let elements = Vec::<T>::deserialize(typ, v)?;
+
+        Vector::try_from(elements).map_err(DeserializationError::new)
+    }
+}
+
+impl<T: SerializeValue, const N: usize> SerializeValue for Vector<T, N> {
+    fn serialize<'b>(
+        &self,
+        typ: &ColumnType,
+        writer: CellWriter<'b>,
+    ) -> Result<WrittenCellProof<'b>, SerializationError> {
+        self.0.as_slice().serialize(typ, writer)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VectorDimensionsError {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl std::fmt::Display for VectorDimensionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Vector has {} dimensions, but {} were expected",
+            self.actual, self.expected
+        )
+    }
+}
+
+impl std::error::Error for VectorDimensionsError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+
+    fn vector_type(dimensions: u16) -> ColumnType<'static> {
+        ColumnType::Vector {
+            typ: Box::new(ColumnType::Native(NativeType::Float)),
+            dimensions,
+        }
+    }
+
+    fn serialize_value<T: SerializeValue>(value: &T, typ: &ColumnType) -> Bytes {
+        let mut buf = Vec::new();
+        value.serialize(typ, CellWriter::new_without_size(&mut buf)).unwrap();
+
+        Bytes::from(buf)
+    }
+
+    fn deserialize_value<const N: usize>(typ: &ColumnType, bytes: &Bytes) -> Result<Vector<Float, N>, String> {
+        <Vector<Float, N> as DeserializeValue>::type_check(typ).map_err(|e| e.to_string())?;
+        <Vector<Float, N> as DeserializeValue>::deserialize(typ, Some(FrameSlice::new(bytes)))
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn vector_cql_round_trip() {
+        let vector = Vector::<Float, 3>([1.0, 2.5, -3.0]);
+        let bytes = serialize_value(&vector, &vector_type(3));
+
+        assert_eq!(deserialize_value::<3>(&vector_type(3), &bytes), Ok(vector));
+    }
+
+    #[test]
+    fn vector_cql_dimensions_mismatch_is_error() {
+        let bytes = serialize_value(&Vector::<Float, 2>([1.0, 2.0]), &vector_type(2));
+
+        assert!(deserialize_value::<3>(&vector_type(2), &bytes).is_err());
+        assert!(<Vector<Float, 3> as DeserializeValue>::type_check(&vector_type(4)).is_err());
+    }
+
+    #[test]
+    fn vector_serde_round_trip() {
+        let vector = Vector::<Float, 3>([1.0, 2.5, -3.0]);
+        let json = serde_json::to_string(&vector).unwrap();
+
+        assert_eq!(json, "[1.0,2.5,-3.0]");
+        assert_eq!(serde_json::from_str::<Vector<Float, 3>>(&json).unwrap(), vector);
+    }
+
+    #[test]
+    fn vector_serde_length_mismatch_is_error() {
+        let error = serde_json::from_str::<Vector<Float, 3>>("[1.0,2.0]").unwrap_err();
+
+        assert!(error
+            .to_string()
+            .contains("Vector has 2 dimensions, but 3 were expected"));
+        assert_eq!(
+            Vector::<Float, 3>::try_from(vec![1.0, 2.0, 3.0, 4.0]),
+            Err(VectorDimensionsError { expected: 3, actual: 4 })
+        );
+    }
+}
@@ -3,10 +3,12 @@ use crate::custom_fields::{AddressTypeCustomField, UserExtraDataCustomField};
 
 use charybdis::batch::ModelBatch;
 use charybdis::errors::CharybdisError;
-use charybdis::model::{BaseModel, Model};
+use charybdis::model::{BaseModel, Model, WritetimeModel};
+use charybdis::operations::{Find, Insert, Update};
 use charybdis::stream::CharybdisModelStream;
-use charybdis::types::{Boolean, Int, Text, Uuid};
+use charybdis::types::{Boolean, Float, Int, Map, Set, Text, Uuid, Vector};
 use charybdis_macros::{charybdis_model, charybdis_udt_model, charybdis_view_model};
+use std::time::Duration;
 
 pub const SAMPLE_MODEL_COUNT: usize = 32;
 
@@ -142,6 +144,290 @@ pub struct UserByEmail {
     pub username: Text,
 }
 
+#[charybdis_model(
+    table_name = accounts,
+    partition_keys = [id],
+    clustering_keys = [display_name],
+    global_secondary_indexes = [email_address],
+)]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Account {
+    pub id: Uuid,
+    #[charybdis(column = "displayName")]
+    pub display_name: Text,
+    #[charybdis(column = "emailAddress")]
+    pub email_address: Text,
+    #[charybdis(column = "order")]
+    pub sort_order: Int,
+}
+
+partial_account!(UpdateSortOrderAccount, id, display_name, sort_order);
+
+#[test]
+fn account_model_queries() {
+    assert_eq!(
+        Account::FIND_BY_PRIMARY_KEY_QUERY,
+        r#"SELECT id, "displayName", "emailAddress", "order" FROM accounts WHERE id = ? AND "displayName" = ?"#
+    );
+    assert_eq!(
+        Account::INSERT_QUERY,
+        r#"INSERT INTO accounts (id, "displayName", "emailAddress", "order") VALUES (:id, :"displayName", :"emailAddress", :"order")"#
+    );
+    assert_eq!(
+        Account::UPDATE_QUERY,
+        r#"UPDATE accounts SET "emailAddress" = :"emailAddress", "order" = :"order" WHERE id = :id AND "displayName" = :"displayName""#
+    );
+    assert!(UpdateSortOrderAccount::UPDATE_QUERY
+        .ends_with(r#"SET "order" = :"order" WHERE id = :id AND "displayName" = :"displayName""#));
+    assert_eq!(
+        find_account_query!("id = ?"),
+        r#"SELECT id, "displayName", "emailAddress", "order" FROM accounts WHERE id = ?"#
+    );
+}
+
+#[charybdis_model(
+    table_name = products,
+    partition_keys = [id],
+    clustering_keys = [],
+    vector_indexes = [embedding],
+)]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Product {
+    pub id: Uuid,
+    pub category_id: Uuid,
+    pub embedding: Vector<Float, 3>,
+}
+
+#[tokio::test]
+async fn product_ann_queries() {
+    let embedding = Vector::from([0.1, 0.2, 0.3]);
+
+    assert_eq!(
+        Product::find_by_embedding_ann(embedding, 5).query_string(),
+        "SELECT id, category_id, embedding FROM products ORDER BY embedding ANN OF ? LIMIT ?"
+    );
+    assert_eq!(
+        find_product_ann!(embedding, "category_id = ?", (Uuid::new_v4(), embedding, 5)).query_string(),
+        "SELECT id, category_id, embedding FROM products WHERE category_id = ? ORDER BY embedding ANN OF ? LIMIT ?"
+    );
+
+    let error = Vector::<Float, 3>::try_from(vec![0.1, 0.2]).unwrap_err();
+
+    assert_eq!(error.to_string(), "Vector has 2 dimensions, but 3 were expected");
+}
+
+#[charybdis_model(
+    table_name = articles,
+    partition_keys = [id],
+    clustering_keys = [],
+    writetime_struct = ArticleTimestamps,
+    indexes = [
+        sai(title, options = "{'case_sensitive': 'false'}"),
+        values(tags),
+        index(keys(attributes), name = articles_attribute_keys),
+        entries(attributes),
+    ],
+)]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Article {
+    pub id: Uuid,
+    pub title: Text,
+    pub tags: Set<Text>,
+    pub attributes: Map<Text, Text>,
+}
+
+#[tokio::test]
+async fn article_index_queries() {
+    assert_eq!(
+        Article::find_by_title("Title".to_string()).query_string(),
+        "SELECT id, title, tags, attributes FROM articles WHERE title = ?"
+    );
+    assert_eq!(
+        Article::find_by_tags_containing("rust").query_string(),
+        "SELECT id, title, tags, attributes FROM articles WHERE tags CONTAINS ?"
+    );
+    assert_eq!(
+        Article::find_by_attributes_containing_key("color").query_string(),
+        "SELECT id, title, tags, attributes FROM articles WHERE attributes CONTAINS KEY ?"
+    );
+    assert_eq!(
+        Article::find_by_attributes_entry("color", "red").query_string(),
+        "SELECT id, title, tags, attributes FROM articles WHERE attributes[?] = ?"
+    );
+}
+
+#[charybdis_model(
+    table_name = user_sessions,
+    partition_keys = [id],
+    clustering_keys = [],
+    default_ttl = 3600,
+)]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UserSession {
+    pub id: Uuid,
+    pub device: Text,
+    pub scopes: Set<Text>,
+}
+
+#[tokio::test]
+async fn ttl_queries() {
+    assert_eq!(UserSession::DEFAULT_TTL, Some(Duration::from_secs(3600)));
+    assert_eq!(
+        UserSession::INSERT_WITH_TTL_QUERY,
+        "INSERT INTO user_sessions (id, device, scopes) VALUES (:id, :device, :scopes) USING TTL ?"
+    );
+    assert_eq!(
+        UserSession::UPDATE_WITH_TTL_QUERY,
+        "UPDATE user_sessions USING TTL ? SET device = :device, scopes = :scopes WHERE id = :id"
+    );
+    assert_eq!(
+        UserSession::PUSH_SCOPES_WITH_TTL_QUERY,
+        "UPDATE user_sessions USING TTL ? SET scopes = scopes + ? WHERE id = ?"
+    );
+
+    let session = UserSession::default();
+
+    assert_eq!(session.insert().query_string(), UserSession::INSERT_WITH_TTL_QUERY);
+    assert_eq!(session.update().query_string(), UserSession::UPDATE_WITH_TTL_QUERY);
+    assert_eq!(
+        session.push_scopes(vec!["read"]).query_string(),
+        UserSession::PUSH_SCOPES_WITH_TTL_QUERY
+    );
+
+    let account = Account::default();
+
+    assert_eq!(Account::DEFAULT_TTL, None);
+    assert_eq!(account.insert().query_string(), Account::INSERT_QUERY);
+    assert_eq!(
+        account.insert().ttl(Duration::from_secs(60)).query_string(),
+        Account::INSERT_WITH_TTL_QUERY
+    );
+}
+
+#[tokio::test]
+async fn ttl_is_applied() {
+    let db_session = &db_session().await;
+    let ttl_of_device = |id: Uuid| async move {
+        db_session
+            .get_session()
+            .query_unpaged("SELECT TTL(device) FROM user_sessions WHERE id = ?", (id,))
+            .await
+            .unwrap()
+            .into_rows_result()
+            .unwrap()
+            .single_row::<(Option<i32>,)>()
+            .unwrap()
+            .0
+    };
+
+    let session = UserSession {
+        id: Uuid::new_v4(),
+        device: "phone".to_string(),
+        ..Default::default()
+    };
+
+    session.insert().execute(db_session).await.unwrap();
+    assert!(ttl_of_device(session.id)
+        .await
+        .is_some_and(|ttl| ttl > 3500 && ttl <= 3600));
+
+    session
+        .update()
+        .ttl(Duration::from_millis(1500))
+        .execute(db_session)
+        .await
+        .unwrap();
+    assert!(ttl_of_device(session.id).await.is_some_and(|ttl| ttl <= 2));
+
+    session.update().ttl(Duration::ZERO).execute(db_session).await.unwrap();
+    assert_eq!(ttl_of_device(session.id).await, None);
+}
+
+#[charybdis_model(
+    table_name = followers,
+    partition_keys = [user_id],
+    clustering_keys = [follower_id],
+)]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Follower {
+    pub user_id: Uuid,
+    pub follower_id: Uuid,
+}
+
+// Key-only models don't implement `WritetimeModel`, so the default name of its struct is free to use.
+#[derive(Debug, Default)]
+pub struct FollowerWritetime {
+    pub followed_at: Option<i64>,
+}
+
+#[tokio::test]
+async fn writetime_queries() {
+    assert_eq!(
+        UserSession::FIND_WRITETIME_BY_PRIMARY_KEY_QUERY,
+        "SELECT WRITETIME(device), TTL(device) FROM user_sessions WHERE id = ?"
+    );
+    assert_eq!(
+        Account::FIND_WRITETIME_BY_PRIMARY_KEY_QUERY,
+        r#"SELECT WRITETIME("emailAddress"), TTL("emailAddress"), WRITETIME("order"), TTL("order") FROM accounts WHERE id = ? AND "displayName" = ?"#
+    );
+    assert_eq!(
+        UserSession::default().find_writetime_by_primary_key().query_string(),
+        UserSession::FIND_WRITETIME_BY_PRIMARY_KEY_QUERY
+    );
+
+    let article_timestamps: <Article as WritetimeModel>::Writetime = ArticleTimestamps {
+        title_writetime: Some(1),
+        title_ttl: None,
+    };
+
+    assert_eq!(article_timestamps.title_writetime, Some(1));
+    assert_eq!(
+        Article::FIND_WRITETIME_BY_PRIMARY_KEY_QUERY,
+        "SELECT WRITETIME(title), TTL(title) FROM articles WHERE id = ?"
+    );
+    assert_eq!(FollowerWritetime::default().followed_at, None);
+}
+
+#[tokio::test]
+async fn writetime_is_found() {
+    let db_session = &db_session().await;
+    let session = UserSession {
+        id: Uuid::new_v4(),
+        device: "tablet".to_string(),
+        ..Default::default()
+    };
+
+    session
+        .insert()
+        .ttl(Duration::from_secs(120))
+        .execute(db_session)
+        .await
+        .unwrap();
+
+    let writetime: UserSessionWritetime = session
+        .find_writetime_by_primary_key()
+        .execute(db_session)
+        .await
+        .unwrap();
+
+    assert!(writetime.device_writetime.is_some_and(|micros| micros > 0));
+    assert!(writetime.device_ttl.is_some_and(|ttl| ttl > 60 && ttl <= 120));
+    assert_eq!(
+        UserSession::find_writetime_by_primary_key_value((session.id,))
+            .execute(db_session)
+            .await
+            .unwrap(),
+        writetime
+    );
+
+    let error = UserSession::find_writetime_by_primary_key_value((Uuid::new_v4(),))
+        .execute(db_session)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, CharybdisError::NotFoundError(_)));
+}
+
 #[charybdis_model(
     table_name = posts,
     partition_keys = [category_id],